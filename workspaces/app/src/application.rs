@@ -11,10 +11,14 @@ use common::{
     cache_settings::CacheSettings,
     config::{self},
     fetch::Fetch,
+    user_settings::UserSettings,
     utils::{self, OnceLockExt},
 };
 use error_dialog::ErrorDialog;
-use gtk::{IconTheme, Image, Settings, gdk};
+use gtk::{
+    IconTheme, Image, Settings, gdk,
+    gio::{self, prelude::ApplicationExt},
+};
 use pages::{Page, Pages};
 use std::{cell::RefCell, path::Path, rc::Rc};
 use tracing::{debug, error};
@@ -27,6 +31,7 @@ pub struct Locale {
 
 pub struct App {
     pub cache_settings: RefCell<CacheSettings>,
+    pub user_settings: RefCell<UserSettings>,
     pub dirs: Rc<AppDirs>,
     pub browser_configs: Rc<BrowserConfigs>,
     pub error_dialog: ErrorDialog,
@@ -46,6 +51,7 @@ impl App {
             let app_dirs = AppDirs::new().expect("Failed to get all needed directories");
             let settings = Settings::default().expect("Failed to load gtk settings");
             let cache_settings = RefCell::new(CacheSettings::new(&app_dirs));
+            let user_settings = RefCell::new(UserSettings::new(&app_dirs));
             let window = AppWindow::new(adw_application);
             let fetch = Fetch::new();
             let pages = Pages::new();
@@ -61,6 +67,7 @@ impl App {
 
             Self {
                 cache_settings,
+                user_settings,
                 dirs: app_dirs,
                 browser_configs: browsers,
                 error_dialog,
@@ -126,11 +133,20 @@ impl App {
         self.error_dialog.show(self, error);
     }
 
+    /// Desktop notification, a notification with the same id replaces the previous one
+    pub fn send_notification(self: &Rc<Self>, id: &str, title: &str, body: &str) {
+        let notification = gio::Notification::new(title);
+        notification.set_body(Some(body));
+        self.adw_application
+            .send_notification(Some(id), &notification);
+    }
+
     pub fn close(self: &Rc<Self>) {
         self.window.close();
     }
 
     pub fn restart(mut self: Rc<Self>) {
+        self.pages.stop_file_monitors();
         self.close();
         self.cache_settings.borrow_mut().reset();
         let new_self = Self::new(&self.adw_application);
@@ -142,6 +158,11 @@ impl App {
         self.window.view.on_app_update();
     }
 
+    /// Browser configs have been read again, show the browsers that changed
+    pub fn on_browser_configs_change(self: &Rc<Self>) {
+        self.pages.reset_browsers(self);
+    }
+
     fn add_system_icon_paths(self: &Rc<Self>) {
         if utils::env::is_flatpak_container() {
             for path in &self.dirs.system_icons {
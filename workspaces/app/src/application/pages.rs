@@ -55,6 +55,14 @@ impl Pages {
         sidebar.add_nav_row(app.clone(), Page::Info);
     }
 
+    pub fn reset_browsers(&self, app: &Rc<App>) {
+        self.browsers.reset(app);
+    }
+
+    pub fn stop_file_monitors(&self) {
+        self.web_apps.stop_file_monitors();
+    }
+
     pub fn get(&self, page: &Page) -> Rc<dyn NavPage> {
         match page {
             Page::Home => self.home.clone(),
@@ -2,20 +2,22 @@ use super::NavPage;
 use crate::application::{App, pages::PrefPage};
 use common::browsers::{Base, Browser};
 use gtk::{
-    Align, Label, Orientation,
-    prelude::{BoxExt, WidgetExt},
+    Align, InputPurpose, Label, Orientation,
+    prelude::{BoxExt, EditableExt, WidgetExt},
 };
 use libadwaita::{
-    ActionRow, ExpanderRow, NavigationPage, PreferencesGroup, PreferencesPage, StatusPage,
-    prelude::{ExpanderRowExt, PreferencesGroupExt, PreferencesPageExt},
+    ActionRow, EntryRow, ExpanderRow, NavigationPage, PreferencesGroup, PreferencesPage,
+    StatusPage,
+    prelude::{EntryRowExt, ExpanderRowExt, PreferencesGroupExt, PreferencesPageExt},
 };
 use std::fmt::Write as _;
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 pub struct BrowsersPage {
     nav_page: NavigationPage,
     nav_row: ActionRow,
     prefs_page: PreferencesPage,
+    pref_groups: RefCell<Vec<PreferencesGroup>>,
 }
 impl NavPage for BrowsersPage {
     fn get_navpage(&self) -> &NavigationPage {
@@ -42,15 +44,73 @@ impl BrowsersPage {
             nav_page,
             nav_row,
             prefs_page,
+            pref_groups: RefCell::new(Vec::new()),
         })
     }
 
     pub fn init(self: &Rc<Self>, app: &Rc<App>) {
-        let browser_pref_groups = Self::build_browser_sections(app);
+        let mut pref_groups = Self::build_browser_sections(app);
+        pref_groups.push(Self::build_fallback_section(app));
 
-        for pref_group in browser_pref_groups {
-            self.prefs_page.add(&pref_group);
+        for pref_group in &pref_groups {
+            self.prefs_page.add(pref_group);
         }
+        *self.pref_groups.borrow_mut() = pref_groups;
+    }
+
+    pub fn reset(self: &Rc<Self>, app: &Rc<App>) {
+        for pref_group in self.pref_groups.take() {
+            self.prefs_page.remove(&pref_group);
+        }
+        self.init(app);
+    }
+
+    /// Browsers for all web apps of which the browser is uninstalled, a web app can have its own
+    fn build_fallback_section(app: &Rc<App>) -> PreferencesGroup {
+        let fallback_browsers = app
+            .user_settings
+            .borrow()
+            .settings
+            .fallback_browsers
+            .join("; ");
+        let fallback_browsers_row = EntryRow::builder()
+            .title(t!("browsers.fallback.browsers"))
+            .text(fallback_browsers)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build();
+
+        let app_clone = app.clone();
+        fallback_browsers_row.connect_apply(move |entry_row| {
+            let browser_ids = entry_row
+                .text()
+                .split([';', ','])
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            let is_valid = browser_ids
+                .iter()
+                .all(|id| app_clone.browser_configs.get_base_by_id(id).is_some());
+            if !is_valid {
+                entry_row.add_css_class("error");
+                return;
+            }
+            entry_row.remove_css_class("error");
+            entry_row.set_text(&browser_ids.join("; "));
+
+            let mut user_settings_borrow = app_clone.user_settings.borrow_mut();
+            user_settings_borrow.set_fallback_browsers(&browser_ids);
+            let _ = user_settings_borrow.save();
+        });
+
+        let pref_group = PreferencesGroup::builder()
+            .title(t!("browsers.fallback.title"))
+            .description(t!("browsers.fallback.description"))
+            .build();
+        pref_group.add(&fallback_browsers_row);
+
+        pref_group
     }
 
     fn build_browser_sections(app: &Rc<App>) -> Vec<PreferencesGroup> {
@@ -1,21 +1,39 @@
+mod adopt_dialog;
+mod cleanup_dialog;
+mod duplicate_dialog;
+mod import_dialog;
 mod web_app_view;
 
 use super::NavPage;
 use crate::application::{App, pages::PrefNavPage};
+use adopt_dialog::AdoptDialog;
+use cleanup_dialog::CleanupDialog;
 use common::{
-    desktop_file::{DesktopFile, error::DesktopFileError},
-    utils,
+    browser_apps::BrowserApp,
+    desktop_file::{DesktopFile, OwnedDesktopFile},
 };
+use duplicate_dialog::DuplicateDialog;
 use gtk::{
-    Button, Image,
-    prelude::{ButtonExt, WidgetExt},
+    Align, Button, Image, gio,
+    glib::{self, SourceId},
+    prelude::{BoxExt, ButtonExt, FileExt, FileMonitorExt, ListModelExt, WidgetExt},
 };
+use import_dialog::ImportDialog;
 use libadwaita::{
-    ActionRow, ButtonContent, NavigationPage, NavigationView, PreferencesGroup, PreferencesPage,
-    StatusPage,
-    prelude::{ActionRowExt, PreferencesGroupExt, PreferencesPageExt},
+    ActionRow, AlertDialog, ButtonContent, NavigationPage, NavigationView, PreferencesGroup,
+    PreferencesPage, StatusPage,
+    prelude::{
+        ActionRowExt, AdwDialogExt, AlertDialogExt, PreferencesGroupExt, PreferencesPageExt,
+        PreferencesRowExt,
+    },
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
 };
-use std::{cell::RefCell, rc::Rc};
 use tracing::{debug, error};
 use web_app_view::WebAppView;
 
@@ -25,6 +43,11 @@ pub struct WebAppsPage {
     nav_view: Rc<NavigationView>,
     prefs_page: PreferencesPage,
     app_section: RefCell<PreferencesGroup>,
+    browser_apps_section: RefCell<PreferencesGroup>,
+    app_rows: RefCell<HashMap<PathBuf, Rc<AppRow>>>,
+    file_monitors: RefCell<Vec<gio::FileMonitor>>,
+    changed_paths: RefCell<HashSet<PathBuf>>,
+    refresh_source: RefCell<Option<SourceId>>,
 }
 impl NavPage for WebAppsPage {
     fn get_navpage(&self) -> &NavigationPage {
@@ -36,10 +59,14 @@ impl NavPage for WebAppsPage {
     }
 }
 impl WebAppsPage {
+    /// Writing a desktop file or extracting configs gives a burst of events, wait for it to end
+    const REFRESH_DELAY: Duration = Duration::from_millis(500);
+
     pub fn new() -> Rc<Self> {
         let title = t!("web_apps.title");
         let icon = "preferences-desktop-apps-symbolic";
         let app_section = RefCell::new(PreferencesGroup::new());
+        let browser_apps_section = RefCell::new(PreferencesGroup::new());
 
         let PrefNavPage {
             nav_page,
@@ -55,6 +82,11 @@ impl WebAppsPage {
             nav_view: Rc::new(nav_view),
             prefs_page,
             app_section,
+            browser_apps_section,
+            app_rows: RefCell::new(HashMap::new()),
+            file_monitors: RefCell::new(Vec::new()),
+            changed_paths: RefCell::new(HashSet::new()),
+            refresh_source: RefCell::new(None),
         })
     }
 
@@ -62,15 +94,177 @@ impl WebAppsPage {
         let app_section = self.clone().build_apps_section(app);
         self.prefs_page.add(&app_section);
         *self.app_section.borrow_mut() = app_section;
+        let browser_apps_section = self.clone().build_browser_apps_section(app);
+        self.prefs_page.add(&browser_apps_section);
+        *self.browser_apps_section.borrow_mut() = browser_apps_section;
 
         let self_clone = self.clone();
         let app_clone = app.clone();
 
         self.nav_view
             .connect_popped(move |_, _| self_clone.reset_app_section(&app_clone));
+
+        self.start_file_monitors(app);
+    }
+
+    /// Desktop files, icons and browser configs can also change by the cli, a sync tool
+    /// or another instance of the app
+    fn start_file_monitors(self: &Rc<Self>, app: &Rc<App>) {
+        let dirs = [
+            &app.dirs.user_applications,
+            &app.dirs.app_data_icons,
+            &app.dirs.app_config_browser_configs,
+        ];
+
+        for dir in dirs {
+            let monitor = match gio::File::for_path(dir)
+                .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => monitor,
+                Err(error) => {
+                    error!(path = %dir.display(), "Failed to monitor directory: {error:?}");
+                    continue;
+                }
+            };
+
+            let self_clone = self.clone();
+            let app_clone = app.clone();
+
+            monitor.connect_changed(move |_, file, other_file, event| {
+                if matches!(
+                    event,
+                    gio::FileMonitorEvent::AttributeChanged
+                        | gio::FileMonitorEvent::PreUnmount
+                        | gio::FileMonitorEvent::Unmounted
+                ) {
+                    return;
+                }
+
+                let paths = [Some(file), other_file]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(FileExt::path);
+                self_clone.queue_refresh(&app_clone, paths);
+            });
+
+            self.file_monitors.borrow_mut().push(monitor);
+        }
+    }
+
+    pub fn stop_file_monitors(&self) {
+        for monitor in self.file_monitors.take() {
+            monitor.cancel();
+        }
+        if let Some(source_id) = self.refresh_source.take() {
+            source_id.remove();
+        }
+    }
+
+    /// Every event restarts the delay, so the rows refresh once after the last one
+    fn queue_refresh(self: &Rc<Self>, app: &Rc<App>, paths: impl IntoIterator<Item = PathBuf>) {
+        self.changed_paths.borrow_mut().extend(paths);
+
+        if let Some(source_id) = self.refresh_source.take() {
+            source_id.remove();
+        }
+
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+
+        let source_id = glib::timeout_add_local_once(Self::REFRESH_DELAY, move || {
+            // Already removed by finishing, dropping the id is enough
+            self_clone.refresh_source.take();
+            self_clone.refresh_changed(&app_clone);
+        });
+        *self.refresh_source.borrow_mut() = Some(source_id);
+    }
+
+    fn refresh_changed(self: &Rc<Self>, app: &Rc<App>) {
+        let changed_paths = self.changed_paths.take();
+        // An open web app rebuilds the list when closed, replacing its desktop file
+        // now would throw away unsaved changes
+        let is_editing = self.nav_view.navigation_stack().n_items() > 1;
+        let mut needs_reset = false;
+        let mut browser_apps_changed = false;
+        let mut app_has_updated = false;
+
+        // Before loading desktop files, so they find the changed browsers
+        let browser_configs_changed = changed_paths
+            .iter()
+            .any(|path| path.starts_with(&app.dirs.app_config_browser_configs));
+        if browser_configs_changed {
+            debug!("Browser configs changed");
+            app.browser_configs.init();
+            app.on_browser_configs_change();
+            browser_apps_changed = true;
+        }
+
+        for path in &changed_paths {
+            if path.starts_with(&app.dirs.app_data_icons) {
+                for app_row in self.app_rows.borrow().values() {
+                    if app_row.desktop_file.borrow().get_icon_path().as_deref() == Some(path) {
+                        app_row.reload_icon();
+                    }
+                }
+            } else if !is_editing
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "desktop")
+            {
+                debug!(path = %path.display(), "Desktop file changed");
+
+                let app_row = self.app_rows.borrow().get(path).cloned();
+                let desktop_file = if path.is_file() {
+                    Self::load_owned_desktop_file(app, path)
+                } else {
+                    None
+                };
+
+                match (app_row, desktop_file) {
+                    (Some(app_row), Some((desktop_file, is_updated))) => {
+                        app_row.update(desktop_file);
+                        app_has_updated |= is_updated;
+                    }
+                    (Some(app_row), None) => {
+                        self.app_section.borrow().remove(&app_row.row);
+                        self.app_rows.borrow_mut().remove(path);
+                        // The empty list has a status page instead
+                        needs_reset |= self.app_rows.borrow().is_empty();
+                    }
+                    // A new web app, a full rebuild keeps the list sorted
+                    (None, Some(_)) => needs_reset = true,
+                    // Maybe a shortcut of a browser
+                    (None, None) => browser_apps_changed = true,
+                }
+            }
+        }
+
+        // Every web app can have another (or no) browser now
+        if browser_configs_changed && !is_editing && !needs_reset {
+            let app_rows = self.app_rows.borrow().clone();
+            for (path, app_row) in app_rows {
+                if let Some((desktop_file, is_updated)) = Self::load_owned_desktop_file(app, &path)
+                {
+                    app_row.update(desktop_file);
+                    app_has_updated |= is_updated;
+                }
+            }
+        }
+
+        if needs_reset {
+            self.reset_app_section(app);
+        } else if browser_apps_changed {
+            self.reset_browser_apps_section(app);
+        }
+
+        if app_has_updated {
+            app.on_app_update();
+        }
     }
 
     fn build_apps_section(self: Rc<Self>, app: &Rc<App>) -> PreferencesGroup {
+        self.app_rows.borrow_mut().clear();
+
         let button_content = ButtonContent::builder()
             .label(t!("web_apps.button.new_app"))
             .icon_name("list-add-symbolic")
@@ -102,12 +296,49 @@ impl WebAppsPage {
             self_clone.nav_view.push(nav_page);
         });
 
+        let import_button_content = ButtonContent::builder()
+            .label(t!("web_apps.button.import"))
+            .icon_name("document-open-symbolic")
+            .build();
+        let import_button = Button::builder()
+            .css_classes(["flat"])
+            .tooltip_text(t!("web_apps.button.import_tooltip"))
+            .child(&import_button_content)
+            .build();
+        let cleanup_button = Button::builder()
+            .css_classes(["flat"])
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(t!("web_apps.button.cleanup_tooltip"))
+            .build();
+        let app_clone = app.clone();
+        cleanup_button.connect_clicked(move |_| CleanupDialog::show(&app_clone));
+
+        let header_buttons = gtk::Box::builder().spacing(6).build();
+        header_buttons.append(&cleanup_button);
+        header_buttons.append(&import_button);
+        header_buttons.append(&new_app_button);
+
         let pref_group = PreferencesGroup::builder()
-            .header_suffix(&new_app_button)
+            .header_suffix(&header_buttons)
             .build();
 
         let (web_app_desktop_files, desktop_files_have_updated) =
             Self::get_owned_desktop_files(app);
+
+        let existing_urls = web_app_desktop_files
+            .iter()
+            .filter_map(|desktop_file| desktop_file.borrow().get_url())
+            .collect::<HashSet<_>>();
+        let self_clone = self.clone();
+        let app_clone = app.clone();
+        import_button.connect_clicked(move |_| {
+            let self_clone_import = self_clone.clone();
+            let app_clone_import = app_clone.clone();
+
+            ImportDialog::show(&app_clone, &existing_urls, move || {
+                self_clone_import.reset_app_section(&app_clone_import);
+            });
+        });
         if web_app_desktop_files.is_empty() {
             let status_page = StatusPage::builder()
                 .title(t!("web_apps.no_apps.title"))
@@ -130,6 +361,69 @@ impl WebAppsPage {
         pref_group
     }
 
+    /// Apps the browser installed itself, which can be adopted as web app
+    fn build_browser_apps_section(self: Rc<Self>, app: &Rc<App>) -> PreferencesGroup {
+        let browser_apps = BrowserApp::scan(&app.browser_configs, &app.dirs);
+        let pref_group = PreferencesGroup::builder()
+            .title(t!("web_apps.browser_apps.title"))
+            .description(t!("web_apps.browser_apps.description"))
+            .visible(!browser_apps.is_empty())
+            .build();
+
+        for browser_app in browser_apps {
+            let browser_app_row = self
+                .clone()
+                .build_browser_app_row(app, Rc::new(browser_app));
+            pref_group.add(&browser_app_row);
+        }
+
+        pref_group
+    }
+
+    fn build_browser_app_row(
+        self: Rc<Self>,
+        app: &Rc<App>,
+        browser_app: Rc<BrowserApp>,
+    ) -> ActionRow {
+        let browser_name = browser_app.browser.as_ref().map_or(
+            t!("web_apps.browser_apps.no_browser").to_string(),
+            |browser| browser.get_name_with_installation(),
+        );
+        let subtitle = match &browser_app.url {
+            Some(url) => format!("{browser_name} · {url}"),
+            None => browser_name,
+        };
+        let app_row = ActionRow::builder()
+            .title(&browser_app.name)
+            .subtitle(subtitle)
+            .build();
+
+        let app_icon = browser_app.icon_path.as_ref().map_or(
+            Image::from_icon_name("image-missing-symbolic"),
+            Image::from_file,
+        );
+        let adopt_button = Button::builder()
+            .label(t!("web_apps.browser_apps.adopt"))
+            .valign(Align::Center)
+            .sensitive(browser_app.browser.is_some() && browser_app.icon_path.is_some())
+            .build();
+
+        app_row.add_prefix(&app_icon);
+        app_row.add_suffix(&adopt_button);
+
+        let app_clone = app.clone();
+        adopt_button.connect_clicked(move |_| {
+            let self_clone = self.clone();
+            let app_clone_adopt = app_clone.clone();
+
+            AdoptDialog::show(&app_clone, &browser_app, move || {
+                self_clone.reset_app_section(&app_clone_adopt);
+            });
+        });
+
+        app_row
+    }
+
     fn build_app_row(
         self: Rc<Self>,
         app: &Rc<App>,
@@ -146,12 +440,43 @@ impl WebAppsPage {
             .build();
 
         let app_icon = desktop_file_borrow.get_icon();
+        self.app_rows.borrow_mut().insert(
+            desktop_file_borrow.get_path(),
+            Rc::new(AppRow {
+                row: app_row.clone(),
+                icon: RefCell::new(app_icon.clone()),
+                desktop_file: desktop_file.clone(),
+            }),
+        );
+        let duplicate_button = Button::builder()
+            .icon_name("edit-copy-symbolic")
+            .tooltip_text(t!("web_apps.button.duplicate"))
+            .css_classes(["flat"])
+            .valign(Align::Center)
+            .build();
         let suffix = Image::from_icon_name("go-next-symbolic");
 
         app_row.add_prefix(&app_icon);
+        app_row.add_suffix(&duplicate_button);
         app_row.add_suffix(&suffix);
 
         drop(desktop_file_borrow);
+        let app_clone = app.clone();
+        let nav_view_clone = self.nav_view.clone();
+        let desktop_file_clone = desktop_file.clone();
+
+        duplicate_button.connect_clicked(move |_| {
+            let app_clone_duplicate = app_clone.clone();
+            let nav_view_clone = nav_view_clone.clone();
+
+            DuplicateDialog::show(&app_clone, &desktop_file_clone, move |duplicate| {
+                let app_page =
+                    WebAppView::new(&app_clone_duplicate, &nav_view_clone, &duplicate, false);
+                app_page.init();
+                nav_view_clone.push(app_page.get_navpage());
+            });
+        });
+
         let app_clone = app.clone();
         let nav_view_clone = self.nav_view.clone();
 
@@ -165,89 +490,121 @@ impl WebAppsPage {
         app_row
     }
 
+    /// Also returns if one of the desktop files was updated
     fn get_owned_desktop_files(app: &Rc<App>) -> (Vec<Rc<RefCell<DesktopFile>>>, bool) {
-        debug!("Reading user desktop files");
-
         let mut owned_desktop_files = Vec::new();
-        let applications_path = &app.dirs.user_applications;
         let mut app_has_updated = false;
 
-        for file in utils::files::get_entries_in_dir(applications_path).unwrap_or_default() {
-            if file
-                .path()
-                .extension()
-                .is_some_and(|extension| extension != "desktop")
-            {
-                continue;
-            }
+        for owned in DesktopFile::get_owned(
+            &app.browser_configs,
+            &app.dirs,
+            &Self::get_fallback_browser_ids(app),
+        ) {
+            let (desktop_file, is_updated) = Self::on_owned_loaded(app, owned);
+            app_has_updated |= is_updated;
 
-            let Ok(is_owned) = DesktopFile::is_owned(&file.path()) else {
-                error!(path = %file.path().display(), "Failed to read desktop file");
-                continue;
-            };
-            if !is_owned {
-                continue;
-            }
+            owned_desktop_files.push(Rc::new(RefCell::new(desktop_file)));
+        }
 
-            let Ok(mut desktop_file) =
-                DesktopFile::from_path(&file.path(), &app.browser_configs, &app.dirs)
-            else {
-                error!(path = %file.path().display(), "Failed to read desktop file");
-                continue;
-            };
+        *app.has_created_apps.borrow_mut() = !owned_desktop_files.is_empty();
 
-            let file_name = desktop_file
+        (owned_desktop_files, app_has_updated)
+    }
+
+    /// Reads a desktop file of a web app, switches it to a fallback browser if needed and
+    /// updates it. Also returns if the desktop file was updated.
+    fn load_owned_desktop_file(app: &Rc<App>, path: &Path) -> Option<(DesktopFile, bool)> {
+        DesktopFile::load_owned(
+            path,
+            &app.browser_configs,
+            &app.dirs,
+            &Self::get_fallback_browser_ids(app),
+        )
+        .map(|owned| Self::on_owned_loaded(app, owned))
+    }
+
+    fn get_fallback_browser_ids(app: &Rc<App>) -> Vec<String> {
+        app.user_settings
+            .borrow()
+            .settings
+            .fallback_browsers
+            .clone()
+    }
+
+    /// Notifies a switch to a fallback browser
+    fn on_owned_loaded(app: &Rc<App>, owned: OwnedDesktopFile) -> (DesktopFile, bool) {
+        if let Some(browser) = &owned.fallback_browser {
+            let file_name = owned
+                .desktop_file
                 .get_path()
                 .file_name()
                 .unwrap_or_default()
                 .to_string_lossy()
                 .to_string();
-
-            debug!(file_name = &file_name, "Found desktop file");
-
-            let is_updated = match desktop_file.update() {
-                Ok(is_updated) => is_updated,
-                Err(error) => {
-                    match error {
-                        DesktopFileError::ValidationError(error) => error!(
-                            error = error.to_string(),
-                            desktop_file = &file_name,
-                            "Failed to validate after updating 'DesktopFile'"
-                        ),
-                        DesktopFileError::Other(error) => error!(
-                            error = error.to_string(),
-                            desktop_file = &file_name,
-                            "Failed to update 'DesktopFile'"
-                        ),
-                    }
-                    continue;
-                }
-            };
-            if is_updated {
-                debug!(file_name = &file_name, "Updated desktop file");
-                app_has_updated = true;
-            }
-
-            debug!(file_name = &file_name, "Checking paths");
-            desktop_file.check_paths();
-
-            owned_desktop_files.push(Rc::new(RefCell::new(desktop_file)));
+            app.send_notification(
+                &format!("browser-fallback-{file_name}"),
+                &t!("web_apps.fallback.title"),
+                &t!(
+                    "web_apps.fallback.body",
+                    name = owned.desktop_file.get_name().unwrap_or_default(),
+                    browser = browser.get_name_with_installation()
+                ),
+            );
         }
-        owned_desktop_files.sort_by_key(|desktop_file| {
-            desktop_file
-                .borrow()
-                .get_name()
-                .unwrap_or(char::MAX.to_string())
-        });
-
-        *app.has_created_apps.borrow_mut() = !owned_desktop_files.is_empty();
+        let is_updated = owned.fallback_browser.is_some() || owned.is_updated;
 
-        (owned_desktop_files, app_has_updated)
+        (owned.desktop_file, is_updated)
     }
 
     fn reset_app_section(self: &Rc<Self>, app: &Rc<App>) {
         self.prefs_page.remove(&*self.app_section.borrow());
+        self.prefs_page.remove(&*self.browser_apps_section.borrow());
         *self.app_section.borrow_mut() = self.clone().build_apps_section(app);
+        *self.browser_apps_section.borrow_mut() = self.clone().build_browser_apps_section(app);
         self.prefs_page.add(&*self.app_section.borrow());
+        self.prefs_page.add(&*self.browser_apps_section.borrow());
+    }
+
+    fn reset_browser_apps_section(self: &Rc<Self>, app: &Rc<App>) {
+        self.prefs_page.remove(&*self.browser_apps_section.borrow());
+        *self.browser_apps_section.borrow_mut() = self.clone().build_browser_apps_section(app);
+        self.prefs_page.add(&*self.browser_apps_section.borrow());
+    }
+
+    fn show_failed_dialog(app: &Rc<App>, heading: &str, message: &str) {
+        let dialog_close = "close";
+        let dialog = AlertDialog::builder()
+            .heading(heading)
+            .body(message)
+            .build();
+        dialog.add_response(dialog_close, &t!("web_apps.failed_dialog.close"));
+        dialog.set_default_response(Some(dialog_close));
+        dialog.set_close_response(dialog_close);
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+}
+
+/// A row in the list of web apps, updated in place when its desktop file changes on disk
+struct AppRow {
+    row: ActionRow,
+    icon: RefCell<Image>,
+    desktop_file: Rc<RefCell<DesktopFile>>,
+}
+impl AppRow {
+    fn update(&self, desktop_file: DesktopFile) {
+        let app_name = desktop_file
+            .get_name()
+            .unwrap_or(t!("web_apps.no_name").to_string());
+        self.row.set_title(&app_name);
+        *self.desktop_file.borrow_mut() = desktop_file;
+        self.reload_icon();
+    }
+
+    fn reload_icon(&self) {
+        let app_icon = self.desktop_file.borrow().get_icon();
+        self.row.remove(&*self.icon.borrow());
+        self.row.add_prefix(&app_icon);
+        *self.icon.borrow_mut() = app_icon;
     }
 }
@@ -0,0 +1,82 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::browser_apps::BrowserApp;
+use gtk::{
+    InputPurpose,
+    glib::object::ObjectExt,
+    prelude::{EditableExt, WidgetExt},
+};
+use libadwaita::{
+    AlertDialog, EntryRow, PreferencesGroup, ResponseAppearance,
+    prelude::{AdwDialogExt, AlertDialogExt, PreferencesGroupExt},
+};
+use std::rc::Rc;
+use tracing::error;
+use url::Url;
+
+pub struct AdoptDialog;
+impl AdoptDialog {
+    pub const DIALOG_ADOPT: &str = "adopt";
+    pub const DIALOG_CANCEL: &str = "cancel";
+
+    /// Confirms converting a browser app into a web app, asks for the url when it is unknown
+    pub fn show<F>(app: &Rc<App>, browser_app: &Rc<BrowserApp>, on_adopt: F)
+    where
+        F: Fn() + 'static,
+    {
+        let url_row = EntryRow::builder()
+            .title(t!("web_apps.adopt.dialog.url"))
+            .text(browser_app.url.clone().unwrap_or_default())
+            .input_purpose(InputPurpose::Url)
+            .build();
+        let pref_group = PreferencesGroup::new();
+        pref_group.add(&url_row);
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.adopt.dialog.title", name = browser_app.name))
+            .body(t!("web_apps.adopt.dialog.text"))
+            .width_request(400)
+            .extra_child(&pref_group)
+            .build();
+        dialog.add_response(Self::DIALOG_CANCEL, &t!("web_apps.adopt.dialog.cancel"));
+        dialog.add_response(Self::DIALOG_ADOPT, &t!("web_apps.adopt.dialog.ok"));
+        dialog.set_response_appearance(Self::DIALOG_ADOPT, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(Self::DIALOG_ADOPT));
+        dialog.set_close_response(Self::DIALOG_CANCEL);
+        dialog.set_response_enabled(Self::DIALOG_ADOPT, browser_app.url.is_some());
+
+        let dialog_weak = dialog.downgrade();
+        url_row.connect_changed(move |url_row| {
+            let Some(dialog) = dialog_weak.upgrade() else {
+                return;
+            };
+            let is_valid = Url::parse(&url_row.text()).is_ok();
+            dialog.set_response_enabled(Self::DIALOG_ADOPT, is_valid);
+            if is_valid {
+                url_row.remove_css_class("error");
+            } else {
+                url_row.add_css_class("error");
+            }
+        });
+
+        let app_clone = app.clone();
+        let browser_app_clone = browser_app.clone();
+        dialog.connect_response(Some(Self::DIALOG_ADOPT), move |_, _| {
+            let url = url_row.text();
+
+            match browser_app_clone.adopt(Some(&url), &app_clone.browser_configs, &app_clone.dirs) {
+                Ok(_) => on_adopt(),
+                Err(error) => {
+                    error!("Failed to adopt browser app: {error:?}");
+                    WebAppsPage::show_failed_dialog(
+                        &app_clone,
+                        &t!("web_apps.adopt.failed"),
+                        &error.to_string(),
+                    );
+                }
+            }
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+}
@@ -0,0 +1,107 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::orphans::{Orphan, OrphanKind};
+use gtk::{CheckButton, ScrolledWindow, glib, prelude::CheckButtonExt};
+use libadwaita::{
+    ActionRow, AlertDialog, PreferencesGroup, ResponseAppearance,
+    prelude::{ActionRowExt, AdwDialogExt, AlertDialogExt, PreferencesGroupExt},
+};
+use std::rc::Rc;
+use tracing::error;
+
+pub struct CleanupDialog;
+impl CleanupDialog {
+    pub const DIALOG_DELETE: &str = "delete";
+    pub const DIALOG_CANCEL: &str = "cancel";
+
+    /// Lists profiles and icons that no web app uses anymore, deletes the selected ones
+    pub fn show(app: &Rc<App>) {
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.cleanup.dialog.title"))
+            .width_request(500)
+            .build();
+        dialog.add_response(Self::DIALOG_CANCEL, &t!("web_apps.cleanup.dialog.cancel"));
+        dialog.set_close_response(Self::DIALOG_CANCEL);
+
+        let orphans = match Orphan::scan(&app.dirs) {
+            Ok(orphans) => orphans,
+            Err(error) => {
+                error!("Failed to scan for orphans: {error:?}");
+                WebAppsPage::show_failed_dialog(
+                    app,
+                    &t!("web_apps.cleanup.failed"),
+                    &error.to_string(),
+                );
+                return;
+            }
+        };
+
+        if orphans.is_empty() {
+            dialog.set_body(&t!("web_apps.cleanup.dialog.no_orphans"));
+            dialog.present(Some(&app.window.adw_window));
+            return;
+        }
+
+        let total_size = orphans.iter().map(|orphan| orphan.size).sum::<u64>();
+        dialog.set_body(&t!(
+            "web_apps.cleanup.dialog.text",
+            size = glib::format_size(total_size)
+        ));
+        dialog.add_response(Self::DIALOG_DELETE, &t!("web_apps.cleanup.dialog.ok"));
+        dialog.set_response_appearance(Self::DIALOG_DELETE, ResponseAppearance::Destructive);
+
+        let pref_group = PreferencesGroup::new();
+        let mut selections = Vec::new();
+
+        for orphan in orphans {
+            let check_button = CheckButton::builder().active(true).build();
+            let orphan_row = Self::build_orphan_row(&orphan, &check_button);
+            pref_group.add(&orphan_row);
+            selections.push((orphan, check_button));
+        }
+
+        let scrolled_window = ScrolledWindow::builder()
+            .child(&pref_group)
+            .propagate_natural_height(true)
+            .max_content_height(400)
+            .build();
+        dialog.set_extra_child(Some(&scrolled_window));
+
+        let app_clone = app.clone();
+        dialog.connect_response(Some(Self::DIALOG_DELETE), move |_, _| {
+            let errors = selections
+                .iter()
+                .filter(|(_, check_button)| check_button.is_active())
+                .filter_map(|(orphan, _)| orphan.delete().err())
+                .inspect(|error| error!("Failed to delete orphan: {error:?}"))
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>();
+
+            if !errors.is_empty() {
+                WebAppsPage::show_failed_dialog(
+                    &app_clone,
+                    &t!("web_apps.cleanup.failed"),
+                    &errors.join("\n"),
+                );
+            }
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+
+    fn build_orphan_row(orphan: &Orphan, check_button: &CheckButton) -> ActionRow {
+        let kind = match orphan.kind {
+            OrphanKind::Profile => t!("web_apps.cleanup.dialog.profile"),
+            OrphanKind::Icon => t!("web_apps.cleanup.dialog.icon"),
+        };
+
+        let orphan_row = ActionRow::builder()
+            .title(format!("{kind} · {}", glib::format_size(orphan.size)))
+            .subtitle(orphan.path.display().to_string())
+            .activatable_widget(check_button)
+            .build();
+        orphan_row.add_prefix(check_button);
+
+        orphan_row
+    }
+}
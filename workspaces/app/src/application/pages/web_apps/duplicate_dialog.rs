@@ -0,0 +1,86 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::desktop_file::DesktopFile;
+use gtk::{
+    glib::object::ObjectExt,
+    prelude::{EditableExt, WidgetExt},
+};
+use libadwaita::{
+    AlertDialog, EntryRow, PreferencesGroup, ResponseAppearance, SwitchRow,
+    prelude::{AdwDialogExt, AlertDialogExt, PreferencesGroupExt},
+};
+use std::{cell::RefCell, rc::Rc};
+use tracing::error;
+
+pub struct DuplicateDialog;
+impl DuplicateDialog {
+    pub const DIALOG_DUPLICATE: &str = "duplicate";
+    pub const DIALOG_CANCEL: &str = "cancel";
+
+    /// Asks for a name and duplicates the web app, `on_duplicate` gets the saved copy
+    pub fn show<F>(app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>, on_duplicate: F)
+    where
+        F: Fn(Rc<RefCell<DesktopFile>>) + 'static,
+    {
+        let desktop_file_borrow = desktop_file.borrow();
+        let name = desktop_file_borrow.get_name().unwrap_or_default();
+        let is_isolated = desktop_file_borrow.get_isolated().unwrap_or(false);
+        drop(desktop_file_borrow);
+
+        let name_row = EntryRow::builder()
+            .title(t!("web_apps.duplicate.dialog.name"))
+            .text(t!("web_apps.duplicate.dialog.default_name", name = name))
+            .build();
+        let copy_profile_row = SwitchRow::builder()
+            .title(t!("web_apps.duplicate.dialog.copy_profile.title"))
+            .subtitle(t!("web_apps.duplicate.dialog.copy_profile.subtitle"))
+            .visible(is_isolated)
+            .build();
+
+        let pref_group = PreferencesGroup::new();
+        pref_group.add(&name_row);
+        pref_group.add(&copy_profile_row);
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.duplicate.dialog.title"))
+            .body(t!("web_apps.duplicate.dialog.text"))
+            .width_request(400)
+            .extra_child(&pref_group)
+            .build();
+        dialog.add_response(Self::DIALOG_CANCEL, &t!("web_apps.duplicate.dialog.cancel"));
+        dialog.add_response(Self::DIALOG_DUPLICATE, &t!("web_apps.duplicate.dialog.ok"));
+        dialog.set_response_appearance(Self::DIALOG_DUPLICATE, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(Self::DIALOG_DUPLICATE));
+        dialog.set_close_response(Self::DIALOG_CANCEL);
+
+        let dialog_weak = dialog.downgrade();
+        name_row.connect_changed(move |name_row| {
+            let Some(dialog) = dialog_weak.upgrade() else {
+                return;
+            };
+            dialog.set_response_enabled(Self::DIALOG_DUPLICATE, !name_row.text().trim().is_empty());
+        });
+
+        let app_clone = app.clone();
+        let desktop_file_clone = desktop_file.clone();
+        dialog.connect_response(Some(Self::DIALOG_DUPLICATE), move |_, _| {
+            let name = name_row.text().trim().to_string();
+            let copy_profile = is_isolated && copy_profile_row.is_active();
+
+            let result = desktop_file_clone.borrow().duplicate(&name, copy_profile);
+            match result {
+                Ok(duplicate) => on_duplicate(Rc::new(RefCell::new(duplicate))),
+                Err(error) => {
+                    error!("Failed to duplicate web app: {error:?}");
+                    WebAppsPage::show_failed_dialog(
+                        &app_clone,
+                        &t!("web_apps.duplicate.failed"),
+                        &error.to_string(),
+                    );
+                }
+            }
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+}
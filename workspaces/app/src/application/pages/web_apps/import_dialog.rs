@@ -0,0 +1,153 @@
+use super::WebAppsPage;
+use crate::application::App;
+use common::{browsers::Browser, importers::ForeignApp};
+use gtk::{
+    CheckButton, ScrolledWindow, StringList,
+    prelude::{CheckButtonExt, WidgetExt},
+};
+use libadwaita::{
+    ActionRow, AlertDialog, ComboRow, PreferencesGroup, ResponseAppearance,
+    prelude::{ActionRowExt, AdwDialogExt, AlertDialogExt, ComboRowExt, PreferencesGroupExt},
+};
+use std::{collections::HashSet, rc::Rc};
+use tracing::error;
+use url::Url;
+
+pub struct ImportDialog;
+impl ImportDialog {
+    pub const DIALOG_IMPORT: &str = "import";
+    pub const DIALOG_CANCEL: &str = "cancel";
+
+    /// Preview of the web apps of other managers, imports the selected apps.
+    /// Apps with a url of an existing web app are not selected by default.
+    pub fn show<F>(app: &Rc<App>, existing_urls: &HashSet<String>, on_import: F)
+    where
+        F: Fn() + 'static,
+    {
+        let foreign_apps = ForeignApp::scan(&app.browser_configs, &app.dirs);
+        let browsers = app
+            .browser_configs
+            .get_all_browsers()
+            .iter()
+            .filter(|browser| browser.is_installed() && !browser.is_no_browser())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let dialog = AlertDialog::builder()
+            .heading(t!("web_apps.import.dialog.title"))
+            .width_request(500)
+            .build();
+        dialog.add_response(Self::DIALOG_CANCEL, &t!("web_apps.import.dialog.cancel"));
+        dialog.set_close_response(Self::DIALOG_CANCEL);
+
+        if foreign_apps.is_empty() {
+            dialog.set_body(&t!("web_apps.import.dialog.no_apps"));
+            dialog.present(Some(&app.window.adw_window));
+            return;
+        }
+
+        dialog.set_body(&t!("web_apps.import.dialog.text"));
+        dialog.add_response(Self::DIALOG_IMPORT, &t!("web_apps.import.dialog.ok"));
+        dialog.set_response_appearance(Self::DIALOG_IMPORT, ResponseAppearance::Suggested);
+        dialog.set_default_response(Some(Self::DIALOG_IMPORT));
+
+        let pref_group = PreferencesGroup::new();
+        let browser_row = Self::build_browser_row(&browsers);
+        browser_row.set_visible(
+            foreign_apps
+                .iter()
+                .any(|foreign_app| foreign_app.browser.is_none()),
+        );
+        pref_group.add(&browser_row);
+
+        let mut selections = Vec::new();
+        for foreign_app in foreign_apps {
+            // Saved urls are normalized
+            let is_existing =
+                Url::parse(&foreign_app.url).is_ok_and(|url| existing_urls.contains(url.as_str()));
+            let check_button = CheckButton::builder()
+                .active(foreign_app.icon_path.is_some() && !is_existing)
+                .build();
+            let app_row = Self::build_app_row(&foreign_app, &check_button);
+            pref_group.add(&app_row);
+            selections.push((foreign_app, check_button));
+        }
+
+        let scrolled_window = ScrolledWindow::builder()
+            .child(&pref_group)
+            .propagate_natural_height(true)
+            .max_content_height(400)
+            .build();
+        dialog.set_extra_child(Some(&scrolled_window));
+
+        let app_clone = app.clone();
+        dialog.connect_response(Some(Self::DIALOG_IMPORT), move |_, _| {
+            let fallback_browser = browsers.get(browser_row.selected() as usize);
+            let mut errors = Vec::new();
+
+            for (foreign_app, check_button) in &selections {
+                if !check_button.is_active() {
+                    continue;
+                }
+                if let Err(error) = foreign_app.import(
+                    fallback_browser,
+                    &app_clone.browser_configs,
+                    &app_clone.dirs,
+                ) {
+                    error!("Failed to import web app: {error:?}");
+                    errors.push(format!("{}: {error}", foreign_app.name));
+                }
+            }
+
+            on_import();
+            if !errors.is_empty() {
+                WebAppsPage::show_failed_dialog(
+                    &app_clone,
+                    &t!("web_apps.import.failed"),
+                    &errors.join("\n"),
+                );
+            }
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+
+    fn build_browser_row(browsers: &[Rc<Browser>]) -> ComboRow {
+        let names = browsers
+            .iter()
+            .map(|browser| browser.get_name_with_installation())
+            .collect::<Vec<_>>();
+        let model = StringList::new(&names.iter().map(String::as_str).collect::<Vec<_>>());
+
+        ComboRow::builder()
+            .title(t!("web_apps.import.dialog.browser.title"))
+            .subtitle(t!("web_apps.import.dialog.browser.subtitle"))
+            .model(&model)
+            .build()
+    }
+
+    fn build_app_row(foreign_app: &ForeignApp, check_button: &CheckButton) -> ActionRow {
+        let browser_name = foreign_app.browser.as_ref().map_or(
+            t!("web_apps.import.dialog.other_browser").to_string(),
+            |browser| browser.get_name_with_installation(),
+        );
+        let subtitle = if foreign_app.icon_path.is_some() {
+            format!(
+                "{} · {browser_name} · {}",
+                foreign_app.source, foreign_app.url
+            )
+        } else {
+            t!("web_apps.import.dialog.no_icon").to_string()
+        };
+
+        let app_row = ActionRow::builder()
+            .title(&foreign_app.name)
+            .subtitle(subtitle)
+            .activatable_widget(check_button)
+            .sensitive(foreign_app.icon_path.is_some())
+            .build();
+        app_row.add_prefix(check_button);
+
+        app_row
+    }
+}
@@ -1,14 +1,25 @@
 mod icon_picker;
 mod optional_settings;
+mod profile_migration_dialog;
 
 use crate::application::{
     App,
-    pages::{NavPage, PrefPage, web_apps::web_app_view::optional_settings::OptionalSettings},
+    pages::{
+        NavPage, PrefPage,
+        web_apps::{
+            duplicate_dialog::DuplicateDialog, web_app_view::optional_settings::OptionalSettings,
+        },
+    },
 };
 use anyhow::anyhow;
 use common::{
     browsers::{Base, Browser},
-    desktop_file::{DesktopFile, error::DesktopFileError},
+    desktop_file::{
+        DesktopFile, ProfileMigration,
+        error::{DesktopFileError, Severity, ValidationIssue},
+        key::Key,
+    },
+    transaction::Transaction,
     utils,
 };
 use gtk::{
@@ -30,12 +41,9 @@ use libadwaita::{
         PreferencesPageExt, PreferencesRowExt,
     },
 };
-use std::{
-    cell::RefCell,
-    path::{Path, PathBuf},
-    rc::Rc,
-};
-use std::{fmt::Write as _, fs};
+use profile_migration_dialog::ProfileMigrationDialog;
+use std::fmt::Write as _;
+use std::{cell::RefCell, path::Path, rc::Rc};
 use tracing::{debug, error};
 use url::Url;
 
@@ -54,6 +62,7 @@ pub struct WebAppView {
     change_icon_button: Button,
     run_app_button: Button,
     save_button: Button,
+    duplicate_button: Button,
     delete_button: Button,
     name_row: EntryRow,
     url_row: EntryRow,
@@ -106,6 +115,7 @@ impl WebAppView {
         let change_icon_button = Self::build_change_icon_button();
         let run_app_button = Self::build_run_app_button(is_new);
         let save_button = Self::build_save_button(is_new);
+        let duplicate_button = Self::build_duplicate_button(is_new);
         let delete_button = Self::build_delete_button();
         let name_row = Self::build_name_row(desktop_file);
         let url_row = Self::build_url_row(desktop_file);
@@ -129,6 +139,7 @@ impl WebAppView {
             change_icon_button,
             run_app_button,
             save_button,
+            duplicate_button,
             delete_button,
             name_row,
             url_row,
@@ -402,7 +413,7 @@ impl WebAppView {
         // First create all data structures, then set data from ListStore.
         // Why is this so unnecessary complicated? ¯\_(ツ)_/¯
         let list = gio::ListStore::new::<BoxedAnyObject>();
-        for browser in all_browsers {
+        for browser in &all_browsers {
             let boxed = BoxedAnyObject::new(browser.clone());
             list.append(&boxed);
         }
@@ -503,10 +514,12 @@ impl WebAppView {
             .build();
 
         content_box.append(&button_wrap_box(&self.save_button));
+        content_box.append(&button_wrap_box(&self.duplicate_button));
         content_box.append(&button_wrap_box(&self.delete_button));
         pref_group.add(&content_box);
 
         self.connect_save_button();
+        self.connect_duplicate_button();
         self.connect_delete_button();
 
         pref_group
@@ -545,6 +558,14 @@ impl WebAppView {
             .build()
     }
 
+    fn build_duplicate_button(is_new: bool) -> Button {
+        Button::builder()
+            .label(t!("web_apps.web_app_view.button.duplicate"))
+            .css_classes(["pill"])
+            .visible(!is_new)
+            .build()
+    }
+
     fn build_delete_button() -> Button {
         let button = Button::builder()
             .label(t!("web_apps.web_app_view.button.delete"))
@@ -621,7 +642,7 @@ impl WebAppView {
 
         self.run_app_button.connect_clicked(move |_| {
             let desktop_file_borrow = self_clone.desktop_file.borrow();
-            let Some(mut executable) = desktop_file_borrow.get_exec() else {
+            let Some(mut executable) = desktop_file_borrow.get_launch_exec() else {
                 return;
             };
 
@@ -654,6 +675,29 @@ impl WebAppView {
         });
     }
 
+    fn connect_duplicate_button(self: &Rc<Self>) {
+        let self_clone = self.clone();
+
+        self.duplicate_button.connect_clicked(move |_| {
+            let self_clone_duplicate = self_clone.clone();
+
+            DuplicateDialog::show(
+                &self_clone.app,
+                &self_clone.desktop_file,
+                move |duplicate| {
+                    let app_page = WebAppView::new(
+                        &self_clone_duplicate.app,
+                        &self_clone_duplicate.nav_view,
+                        &duplicate,
+                        false,
+                    );
+                    app_page.init();
+                    self_clone_duplicate.nav_view.push(app_page.get_navpage());
+                },
+            );
+        });
+    }
+
     fn connect_delete_button(self: &Rc<Self>) {
         let self_clone = self.clone();
 
@@ -813,7 +857,7 @@ impl WebAppView {
                 .borrow_mut()
                 .set_isolated(switch_row.is_active());
 
-            self_clone.on_isolation_change();
+            self_clone.on_isolation_change(ProfileMigration::Discard);
             self_clone.on_desktop_file_change();
         });
     }
@@ -848,15 +892,53 @@ impl WebAppView {
                     );
                     return;
                 };
-                let browser = browser_item_boxed.borrow::<Rc<Browser>>();
-
-                desktop_file_clone.borrow_mut().set_browser(&browser);
+                let browser = browser_item_boxed.borrow::<Rc<Browser>>().clone();
+
+                let desktop_file_borrow = desktop_file_clone.borrow();
+                let current_browser = desktop_file_borrow.get_browser();
+                let is_same_browser = current_browser
+                    .as_ref()
+                    .is_some_and(|current_browser| current_browser.id == browser.id);
+                // The profile of a new web app has no data yet
+                let has_profile_data = !*self_clone.is_new.borrow()
+                    && !is_same_browser
+                    && desktop_file_borrow.has_profile();
+                let can_migrate = desktop_file_borrow.can_migrate_profile_to(&browser);
+                let previous_index = current_browser
+                    .and_then(|current_browser| current_browser.get_index())
+                    .and_then(|index| index.try_into().ok());
+                drop(desktop_file_borrow);
+
+                if !has_profile_data {
+                    self_clone.on_browser_change(&browser, ProfileMigration::Discard);
+                    return;
+                }
 
-                self_clone.on_isolation_change();
-                self_clone.on_desktop_file_change();
+                let self_clone_dialog = self_clone.clone();
+                let combo_row_clone = combo_row.clone();
+                ProfileMigrationDialog::show(
+                    &self_clone.app,
+                    &browser,
+                    can_migrate,
+                    move |migration| match migration {
+                        Some(migration) => self_clone_dialog.on_browser_change(&browser, migration),
+                        None => {
+                            if let Some(index) = previous_index {
+                                combo_row_clone.set_selected(index);
+                            }
+                        }
+                    },
+                );
             });
     }
 
+    fn on_browser_change(self: &Rc<Self>, browser: &Rc<Browser>, migration: ProfileMigration) {
+        self.desktop_file.borrow_mut().set_browser(browser);
+
+        self.on_isolation_change(migration);
+        self.on_desktop_file_change();
+    }
+
     fn connect_optional_row(self: &Rc<Self>) {
         let app_clone = self.app.clone();
         let desktop_file_clone = self.desktop_file.clone();
@@ -931,16 +1013,86 @@ impl WebAppView {
         }
     }
 
-    fn reset_change_icon_button(self: &Rc<Self>) {
-        if self
-            .desktop_file
-            .borrow()
-            .get_icon_path()
-            .is_some_and(|icon_path| !icon_path.to_string_lossy().is_empty())
-        {
-            self.change_icon_button.remove_css_class("error");
-        } else {
-            self.change_icon_button.add_css_class("error");
+    /// Highlight every row with a problem, not only the first
+    fn reset_validation(self: &Rc<Self>) {
+        let report = self.desktop_file.borrow().get_validation_report();
+
+        Self::mark_issues(
+            self.name_row.upcast_ref(),
+            &report.get_issues_for(&[Key::Name]),
+            true,
+        );
+        Self::mark_issues(
+            self.url_row.upcast_ref(),
+            &report.get_issues_for(&[Key::Url]),
+            true,
+        );
+        Self::mark_issues(
+            self.browser_row.upcast_ref(),
+            &report.get_issues_for(&[Key::BrowserId]),
+            true,
+        );
+        Self::mark_issues(
+            self.change_icon_button.upcast_ref(),
+            &report.get_issues_for(&[Key::Icon]),
+            true,
+        );
+        // These rows use the tooltip to explain why they are disabled
+        Self::mark_issues(
+            self.isolate_row.upcast_ref(),
+            &report.get_issues_for(&[Key::Isolate, Key::Profile]),
+            false,
+        );
+        Self::mark_issues(
+            self.maximize_row.upcast_ref(),
+            &report.get_issues_for(&[Key::Maximize]),
+            false,
+        );
+        Self::mark_issues(
+            self.optional_row.upcast_ref(),
+            &report.get_issues_for(&[
+                Key::Categories,
+                Key::Comment,
+                Key::GenericName,
+                Key::Keywords,
+                Key::Actions,
+                Key::MimeType,
+                Key::HandlerUrl,
+                Key::ExtraArgs,
+                Key::Env,
+                Key::UserAgent,
+                Key::ProxyMode,
+                Key::ProxyServer,
+                Key::ProxyBypass,
+                Key::FirefoxPrefs,
+            ]),
+            true,
+        );
+    }
+
+    fn mark_issues(widget: &gtk::Widget, issues: &[&ValidationIssue], with_tooltip: bool) {
+        widget.remove_css_class("error");
+        widget.remove_css_class("warning");
+
+        let Some(severity) = issues.iter().map(|issue| issue.severity).max() else {
+            if with_tooltip {
+                widget.set_tooltip_text(None);
+            }
+            return;
+        };
+
+        widget.add_css_class(match severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        });
+
+        if with_tooltip {
+            let messages = issues
+                .iter()
+                .map(|issue| issue.error.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            widget.set_tooltip_text(Some(&messages));
         }
     }
 
@@ -975,7 +1127,7 @@ impl WebAppView {
     fn on_desktop_file_change(self: &Rc<Self>) {
         debug!("Desktop file changed");
 
-        self.reset_change_icon_button();
+        self.reset_validation();
         self.reset_reset_button();
         self.reset_browser_isolation();
         self.reset_browser_maximize();
@@ -1020,39 +1172,44 @@ impl WebAppView {
         *self.desktop_file_original.borrow_mut() = self.desktop_file.borrow().clone();
 
         self.run_app_button.set_visible(true);
+        self.duplicate_button.set_visible(true);
         self.save_button.set_visible(false);
         self.on_desktop_file_change();
     }
 
-    fn on_isolation_change(self: &Rc<Self>) {
+    fn on_isolation_change(self: &Rc<Self>, migration: ProfileMigration) {
         let mut desktop_file_borrow = self.desktop_file.borrow_mut();
         let is_isolated = self.isolate_row.is_active();
+        // New profile, old profile and desktop file change together or not at all
+        let mut transaction = Transaction::new();
 
-        let old_profile_path = desktop_file_borrow.get_profile_path().unwrap_or_default();
-
-        let new_profile_path = if is_isolated {
-            match desktop_file_borrow.build_profile_path() {
-                Err(error) => {
-                    drop(desktop_file_borrow);
-                    self.reset_desktop_file();
-                    self.on_error("Could not set isolation", Some(&error));
-                    return;
-                }
-                Ok(profile) => profile,
-            }
-        } else {
-            PathBuf::default()
-        };
+        if let Err(error) =
+            desktop_file_borrow.update_profile_in(is_isolated, migration, &mut transaction)
+        {
+            drop(desktop_file_borrow);
+            transaction.rollback();
+            self.reset_desktop_file();
+            self.on_error("Could not set isolation", Some(&error));
+            return;
+        }
 
-        if old_profile_path != new_profile_path && Path::new(&old_profile_path).is_dir() {
-            debug!(
-                path = old_profile_path.display().to_string(),
-                "Deleting profile"
-            );
-            let _ = fs::remove_dir_all(old_profile_path);
+        if *self.is_new.borrow() {
+            transaction.commit();
+            return;
         }
 
-        desktop_file_borrow.set_profile_path(&new_profile_path);
+        match desktop_file_borrow.save_in(&mut transaction) {
+            Ok(saved) => {
+                transaction.commit();
+                *desktop_file_borrow = saved;
+            }
+            Err(error) => {
+                drop(desktop_file_borrow);
+                transaction.rollback();
+                self.reset_desktop_file();
+                self.on_error("Could not set isolation", Some(&anyhow!("{error}")));
+            }
+        }
     }
 
     fn on_error(self: &Rc<Self>, message: &str, error: Option<&anyhow::Error>) {
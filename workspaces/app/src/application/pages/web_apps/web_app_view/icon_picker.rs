@@ -3,7 +3,7 @@ mod icon_fetcher;
 
 use crate::application::App;
 use anyhow::{Context, Result, bail};
-use common::desktop_file::DesktopFile;
+use common::{desktop_file::DesktopFile, transaction::Transaction};
 use gtk::{
     self, Align, Button, ContentFit, FileDialog, FileFilter, FlowBox, Label, Orientation, Picture,
     SelectionMode,
@@ -25,7 +25,7 @@ use std::{
     cell::RefCell,
     cmp::Reverse,
     collections::HashMap,
-    fs, mem,
+    mem,
     rc::Rc,
     time::{Duration, SystemTime},
 };
@@ -331,6 +331,15 @@ impl IconPicker {
             bail!("Failed to get online icons")
         };
 
+        let shortcuts = icon_fetcher.get_shortcuts();
+        {
+            let mut desktop_file_borrow = self.desktop_file.borrow_mut();
+            // Never overwrite actions set by the user
+            if !shortcuts.is_empty() && desktop_file_borrow.get_actions().is_empty() {
+                desktop_file_borrow.set_actions(&shortcuts);
+            }
+        }
+
         let mut self_icons_borrow = self.icons.borrow_mut();
 
         for (url, icon) in icons {
@@ -457,11 +466,7 @@ impl IconPicker {
 
     fn save(self: &Rc<Self>, icon: &Rc<Icon>) -> Result<()> {
         let mut desktop_file_borrow = self.desktop_file.borrow_mut();
-        if let Some(old_icon_path) = desktop_file_borrow.get_icon_path()
-            && old_icon_path.is_file()
-        {
-            fs::remove_file(old_icon_path).context("Failed to remove old icon")?;
-        }
+        let mut transaction = Transaction::new();
 
         let app_id = desktop_file_borrow
             .get_id()
@@ -477,10 +482,22 @@ impl IconPicker {
             save_path.display()
         );
 
-        icon.pixbuf
-            .savev(save_path.clone(), "png", &[])
+        transaction
+            .write_with(&save_path, |temp_path| {
+                icon.pixbuf.savev(temp_path, "png", &[]).map_err(Into::into)
+            })
             .context("Failed to save icon to fs")?;
 
+        if let Some(old_icon_path) = desktop_file_borrow.get_icon_path()
+            && old_icon_path != save_path
+            && old_icon_path.is_file()
+        {
+            transaction
+                .remove(&old_icon_path)
+                .context("Failed to remove old icon")?;
+        }
+
+        transaction.commit();
         desktop_file_borrow.set_icon_path(&save_path);
         drop(desktop_file_borrow);
 
@@ -1,6 +1,6 @@
 use crate::application::{App, pages::web_apps::web_app_view::icon_picker::icon::Icon};
 use anyhow::{Result, bail};
-use common::{fetch::Response, url::UrlExt};
+use common::{desktop_file::action::DesktopAction, fetch::Response, url::UrlExt};
 use gtk::glib::{self};
 use scraper::{Html, Selector};
 use serde::Deserialize;
@@ -16,8 +16,15 @@ struct ManifestIcon {
     src: Option<String>,
 }
 #[derive(Deserialize)]
+struct ManifestShortcut {
+    name: Option<String>,
+    short_name: Option<String>,
+    url: Option<String>,
+}
+#[derive(Deserialize)]
 struct ManifestJson {
     icons: Option<Vec<ManifestIcon>>,
+    shortcuts: Option<Vec<ManifestShortcut>>,
 }
 
 pub struct IconFetcher {
@@ -27,6 +34,7 @@ pub struct IconFetcher {
     icon_urls: HashSet<String>,
     /// `HashMap<manifest_url_string, (manifest_url, base_url)>`
     manifest_urls: HashMap<String, (Url, Url)>,
+    shortcuts: Vec<DesktopAction>,
 }
 impl IconFetcher {
     pub fn new(app: &Rc<App>, url: &str) -> Result<Self> {
@@ -45,6 +53,7 @@ impl IconFetcher {
             base_url,
             icon_urls: HashSet::new(),
             manifest_urls: HashMap::new(),
+            shortcuts: Vec::new(),
         })
     }
 
@@ -73,6 +82,11 @@ impl IconFetcher {
         Ok(icons)
     }
 
+    /// Shortcuts found in the web app manifests, available after `get_online_icons`
+    pub fn get_shortcuts(&self) -> Vec<DesktopAction> {
+        self.shortcuts.clone()
+    }
+
     #[allow(clippy::unused_self)]
     fn get_href_as_absolute_url(&self, href: &str, url: &Url) -> Result<Url> {
         let sanitized_url = url.sanitize();
@@ -154,6 +168,32 @@ impl IconFetcher {
             let Ok(manifest) = serde_json::from_str::<ManifestJson>(&manifest_json) else {
                 continue;
             };
+            for shortcut in manifest.shortcuts.unwrap_or_default() {
+                let Some(name) = shortcut.short_name.or(shortcut.name) else {
+                    continue;
+                };
+                let Some(shortcut_href) = shortcut.url else {
+                    continue;
+                };
+                let Ok(shortcut_url) = self.get_href_as_absolute_url(&shortcut_href, base_path_url)
+                else {
+                    continue;
+                };
+                if self
+                    .shortcuts
+                    .iter()
+                    .any(|action| action.url == shortcut_url.as_str())
+                {
+                    continue;
+                }
+                info!(
+                    shortcut_url = shortcut_url.to_string(),
+                    "Manifest shortcut found"
+                );
+                self.shortcuts
+                    .push(DesktopAction::new(&name, shortcut_url.as_str()));
+            }
+
             let Some(icons) = manifest.icons else {
                 continue;
             };
@@ -1,23 +1,32 @@
 use crate::application::{App, pages::web_apps::web_app_view::WebAppView};
 use anyhow::anyhow;
-use common::desktop_file::{DesktopFile, category::Category};
+use common::{
+    browsers::ProxyMode,
+    desktop_file::{
+        DesktopFile, SettingSupport, action::DesktopAction, category::Category,
+        error::DesktopFileError, exec,
+    },
+};
 use gtk::{
-    InputPurpose, Label, ListItem, SignalListItemFactory, gio,
+    Align, Button, InputPurpose, Label, ListItem, SignalListItemFactory, StringList, Widget, gio,
     glib::{BoxedAnyObject, object::Cast},
-    prelude::{BoxExt, EditableExt, ListItemExt},
+    prelude::{BoxExt, ButtonExt, EditableExt, ListItemExt, WidgetExt},
 };
 use libadwaita::{
-    ComboRow, EntryRow, PreferencesDialog, PreferencesGroup, PreferencesPage,
+    ActionRow, ButtonRow, ComboRow, EntryRow, PreferencesDialog, PreferencesGroup, PreferencesPage,
+    SwitchRow,
     prelude::{
-        AdwDialogExt, ComboRowExt, EntryRowExt, PreferencesDialogExt, PreferencesGroupExt,
-        PreferencesPageExt,
+        ActionRowExt, AdwDialogExt, ComboRowExt, EntryRowExt, PreferencesDialogExt,
+        PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
     },
 };
 use std::{
     cell::{OnceCell, RefCell},
+    collections::BTreeSet,
     rc::Rc,
 };
 use tracing::error;
+use url::Url;
 
 pub struct OptionalSettings {
     init: OnceCell<bool>,
@@ -26,14 +35,81 @@ pub struct OptionalSettings {
     pref_page: PreferencesPage,
     optional_pref_group: PreferencesGroup,
     description_row: EntryRow,
+    generic_name_row: EntryRow,
     category_row: ComboRow,
+    additional_categories_row: EntryRow,
+    keywords_row: EntryRow,
+    actions_pref_group: PreferencesGroup,
+    action_name_row: EntryRow,
+    action_url_row: EntryRow,
+    action_add_row: ButtonRow,
+    action_rows: RefCell<Vec<ActionRow>>,
+    translations_pref_group: PreferencesGroup,
+    translation_locale_row: EntryRow,
+    translation_name_row: EntryRow,
+    translation_description_row: EntryRow,
+    translation_add_row: ButtonRow,
+    translation_rows: RefCell<Vec<ActionRow>>,
+    handlers_pref_group: PreferencesGroup,
+    mime_types_row: EntryRow,
+    handler_url_row: EntryRow,
+    default_handler_row: ButtonRow,
+    flags_pref_group: PreferencesGroup,
+    flag_rows: RefCell<Vec<SwitchRow>>,
+    fallback_pref_group: PreferencesGroup,
+    fallback_browsers_row: EntryRow,
+    advanced_pref_group: PreferencesGroup,
+    extra_args_row: EntryRow,
+    env_vars_row: EntryRow,
+    user_agent_row: EntryRow,
+    proxy_pref_group: PreferencesGroup,
+    proxy_mode_row: ComboRow,
+    proxy_modes: RefCell<Vec<Option<ProxyMode>>>,
+    proxy_server_row: EntryRow,
+    proxy_bypass_row: EntryRow,
+    firefox_prefs_pref_group: PreferencesGroup,
+    firefox_pref_name_row: EntryRow,
+    firefox_pref_value_row: EntryRow,
+    firefox_pref_add_row: ButtonRow,
+    firefox_pref_rows: RefCell<Vec<ActionRow>>,
 }
 impl OptionalSettings {
     pub fn new(app: &Rc<App>, desktop_file: &Rc<RefCell<DesktopFile>>) -> Rc<Self> {
         let pref_page = PreferencesPage::new();
         let optional_pref_group = Self::build_optional_pref_group();
         let description_row = Self::build_description_row(desktop_file);
+        let generic_name_row = Self::build_generic_name_row(desktop_file);
         let category_row = Self::build_category_row(desktop_file);
+        let additional_categories_row = Self::build_additional_categories_row(desktop_file);
+        let keywords_row = Self::build_keywords_row(desktop_file);
+        let actions_pref_group = Self::build_actions_pref_group();
+        let action_name_row = Self::build_action_name_row();
+        let action_url_row = Self::build_action_url_row();
+        let action_add_row = Self::build_action_add_row();
+        let translations_pref_group = Self::build_translations_pref_group();
+        let translation_locale_row = Self::build_translation_locale_row();
+        let translation_name_row = Self::build_translation_name_row();
+        let translation_description_row = Self::build_translation_description_row();
+        let translation_add_row = Self::build_translation_add_row();
+        let handlers_pref_group = Self::build_handlers_pref_group();
+        let mime_types_row = Self::build_mime_types_row(desktop_file);
+        let handler_url_row = Self::build_handler_url_row(desktop_file);
+        let default_handler_row = Self::build_default_handler_row();
+        let flags_pref_group = Self::build_flags_pref_group();
+        let fallback_pref_group = Self::build_fallback_pref_group();
+        let fallback_browsers_row = Self::build_fallback_browsers_row(desktop_file);
+        let advanced_pref_group = Self::build_advanced_pref_group();
+        let extra_args_row = Self::build_extra_args_row(desktop_file);
+        let env_vars_row = Self::build_env_vars_row(desktop_file);
+        let user_agent_row = Self::build_user_agent_row(desktop_file);
+        let proxy_pref_group = Self::build_proxy_pref_group();
+        let proxy_mode_row = Self::build_proxy_mode_row();
+        let proxy_server_row = Self::build_proxy_server_row(desktop_file);
+        let proxy_bypass_row = Self::build_proxy_bypass_row(desktop_file);
+        let firefox_prefs_pref_group = Self::build_firefox_prefs_pref_group();
+        let firefox_pref_name_row = Self::build_firefox_pref_name_row();
+        let firefox_pref_value_row = Self::build_firefox_pref_value_row();
+        let firefox_pref_add_row = Self::build_firefox_pref_add_row();
 
         Rc::new(Self {
             init: OnceCell::from(false),
@@ -42,7 +118,43 @@ impl OptionalSettings {
             pref_page,
             optional_pref_group,
             description_row,
+            generic_name_row,
             category_row,
+            additional_categories_row,
+            keywords_row,
+            actions_pref_group,
+            action_name_row,
+            action_url_row,
+            action_add_row,
+            action_rows: RefCell::new(Vec::new()),
+            translations_pref_group,
+            translation_locale_row,
+            translation_name_row,
+            translation_description_row,
+            translation_add_row,
+            translation_rows: RefCell::new(Vec::new()),
+            handlers_pref_group,
+            mime_types_row,
+            handler_url_row,
+            default_handler_row,
+            flags_pref_group,
+            flag_rows: RefCell::new(Vec::new()),
+            fallback_pref_group,
+            fallback_browsers_row,
+            advanced_pref_group,
+            extra_args_row,
+            env_vars_row,
+            user_agent_row,
+            proxy_pref_group,
+            proxy_mode_row,
+            proxy_modes: RefCell::new(Vec::new()),
+            proxy_server_row,
+            proxy_bypass_row,
+            firefox_prefs_pref_group,
+            firefox_pref_name_row,
+            firefox_pref_value_row,
+            firefox_pref_add_row,
+            firefox_pref_rows: RefCell::new(Vec::new()),
         })
     }
 
@@ -54,18 +166,86 @@ impl OptionalSettings {
         }
 
         self.pref_page.add(&self.optional_pref_group);
+        self.pref_page.add(&self.translations_pref_group);
+        self.pref_page.add(&self.actions_pref_group);
+        self.pref_page.add(&self.handlers_pref_group);
+        self.pref_page.add(&self.flags_pref_group);
+        self.pref_page.add(&self.fallback_pref_group);
+        self.pref_page.add(&self.advanced_pref_group);
+        self.pref_page.add(&self.proxy_pref_group);
+        self.pref_page.add(&self.firefox_prefs_pref_group);
 
         self.optional_pref_group.add(&self.description_row);
+        self.optional_pref_group.add(&self.generic_name_row);
         self.optional_pref_group.add(&self.category_row);
+        self.optional_pref_group
+            .add(&self.additional_categories_row);
+        self.optional_pref_group.add(&self.keywords_row);
+
+        self.translations_pref_group
+            .add(&self.translation_locale_row);
+        self.translations_pref_group.add(&self.translation_name_row);
+        self.translations_pref_group
+            .add(&self.translation_description_row);
+        self.translations_pref_group.add(&self.translation_add_row);
+
+        self.actions_pref_group.add(&self.action_name_row);
+        self.actions_pref_group.add(&self.action_url_row);
+        self.actions_pref_group.add(&self.action_add_row);
+
+        self.handlers_pref_group.add(&self.mime_types_row);
+        self.handlers_pref_group.add(&self.handler_url_row);
+        self.handlers_pref_group.add(&self.default_handler_row);
+
+        self.fallback_pref_group.add(&self.fallback_browsers_row);
+
+        self.advanced_pref_group.add(&self.extra_args_row);
+        self.advanced_pref_group.add(&self.env_vars_row);
+        self.advanced_pref_group.add(&self.user_agent_row);
+
+        self.proxy_pref_group.add(&self.proxy_mode_row);
+        self.proxy_pref_group.add(&self.proxy_server_row);
+        self.proxy_pref_group.add(&self.proxy_bypass_row);
+
+        self.firefox_prefs_pref_group
+            .add(&self.firefox_pref_name_row);
+        self.firefox_prefs_pref_group
+            .add(&self.firefox_pref_value_row);
+        self.firefox_prefs_pref_group
+            .add(&self.firefox_pref_add_row);
 
         self.connect_description_row(web_app_view);
+        self.connect_generic_name_row(web_app_view);
         self.connect_category_row(web_app_view);
+        self.connect_additional_categories_row(web_app_view);
+        self.connect_keywords_row(web_app_view);
+        self.connect_translation_add_row(web_app_view);
+        self.connect_action_add_row(web_app_view);
+        self.connect_mime_types_row(web_app_view);
+        self.connect_handler_url_row(web_app_view);
+        self.connect_default_handler_row(web_app_view);
+        self.connect_fallback_browsers_row(web_app_view);
+        self.connect_extra_args_row(web_app_view);
+        self.connect_env_vars_row(web_app_view);
+        self.connect_user_agent_row(web_app_view);
+        self.connect_proxy_mode_row(web_app_view);
+        self.connect_proxy_server_row(web_app_view);
+        self.connect_proxy_bypass_row(web_app_view);
+        self.connect_firefox_pref_add_row(web_app_view);
 
         let _ = self.init.set(true);
     }
 
     pub fn show_dialog(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) -> PreferencesDialog {
         self.init(web_app_view);
+        // Actions can be added from the web app manifest after init
+        self.reset_translation_rows(web_app_view);
+        self.reset_action_rows(web_app_view);
+        // The browser can change after init
+        self.reset_flag_rows(web_app_view);
+        self.reset_user_agent_row();
+        self.reset_proxy_rows();
+        self.reset_firefox_pref_rows(web_app_view);
 
         let dialog = PreferencesDialog::builder()
             .title(t!("web_apps.web_app_view.optional.dialog.title"))
@@ -86,141 +266,595 @@ impl OptionalSettings {
             .build()
     }
 
-    fn build_description_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
-        let description = desktop_file.borrow().get_description().unwrap_or_default();
+    fn build_translations_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.translations_group.title"
+            ))
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.translations_group.subtitle"
+            ))
+            .build()
+    }
 
+    fn build_translation_locale_row() -> EntryRow {
         EntryRow::builder()
             .title(t!(
-                "web_apps.web_app_view.optional.dialog.menu_group.description.title"
+                "web_apps.web_app_view.optional.dialog.translations_group.locale.title"
             ))
-            .text(description)
-            .show_apply_button(true)
             .input_purpose(InputPurpose::FreeForm)
             .build()
     }
 
-    fn build_category_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> ComboRow {
-        let all_categories = Category::get_all();
+    fn build_translation_name_row() -> EntryRow {
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.translations_group.name.title"
+            ))
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
 
-        // Some weird factory setup where the list calls factory methods...
-        // First create all data structures, then set data from ListStore.
-        // Why is this so unnecessary complicated? ¯\_(ツ)_/¯
-        let list = gio::ListStore::new::<BoxedAnyObject>();
-        for category in all_categories {
-            let boxed = BoxedAnyObject::new(category);
-            list.append(&boxed);
-        }
-        let factory = SignalListItemFactory::new();
-        factory.connect_bind(|_, list_item| {
-            let Some(list_item) = list_item.downcast_ref::<ListItem>() else {
-                error!(?list_item, "Failed to downcast list item");
-                return;
-            };
-            let Some(category_item_boxed) = list_item
-                .item()
-                .and_then(|item| item.downcast::<BoxedAnyObject>().ok())
-            else {
-                error!(?list_item, "Failed to downcast boxed list item");
-                return;
-            };
+    fn build_translation_description_row() -> EntryRow {
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.translations_group.description.title"
+            ))
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
 
-            let category = category_item_boxed.borrow::<Category>();
-            let box_container = gtk::Box::new(gtk::Orientation::Horizontal, 6);
-            let icon = category.get_icon();
+    fn build_translation_add_row() -> ButtonRow {
+        ButtonRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.translations_group.add"
+            ))
+            .start_icon_name("list-add-symbolic")
+            .build()
+    }
 
-            box_container.append(&icon);
-            box_container.append(&Label::new(Some(&Self::category_to_string_ui(*category))));
+    fn build_translation_row(
+        locale: &str,
+        name: Option<&String>,
+        description: Option<&String>,
+    ) -> (ActionRow, Button) {
+        let remove_button = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(t!(
+                "web_apps.web_app_view.optional.dialog.translations_group.remove"
+            ))
+            .css_classes(["flat"])
+            .valign(Align::Center)
+            .build();
 
-            list_item.set_child(Some(&box_container));
-        });
+        let title = match name {
+            Some(name) => format!("{locale}: {name}"),
+            None => locale.to_string(),
+        };
+        let translation_row = ActionRow::builder()
+            .title(title)
+            .subtitle(description.cloned().unwrap_or_default())
+            .use_markup(false)
+            .build();
+        translation_row.add_suffix(&remove_button);
 
-        let combo_row = ComboRow::builder()
+        (translation_row, remove_button)
+    }
+
+    fn build_actions_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
             .title(t!(
-                "web_apps.web_app_view.optional.dialog.menu_group.category.title"
+                "web_apps.web_app_view.optional.dialog.actions_group.title"
             ))
-            .subtitle(t!(
-                "web_apps.web_app_view.optional.dialog.menu_group.category.subtitle"
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.actions_group.subtitle"
             ))
-            .model(&list)
-            .factory(&factory)
+            .build()
+    }
+
+    fn build_action_name_row() -> EntryRow {
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.actions_group.name.title"
+            ))
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_action_url_row() -> EntryRow {
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.actions_group.url.title"
+            ))
+            .input_purpose(InputPurpose::Url)
+            .build()
+    }
+
+    fn build_action_add_row() -> ButtonRow {
+        ButtonRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.actions_group.add"
+            ))
+            .start_icon_name("list-add-symbolic")
+            .build()
+    }
+
+    fn build_action_row(action: &DesktopAction) -> (ActionRow, Button) {
+        let remove_button = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(t!(
+                "web_apps.web_app_view.optional.dialog.actions_group.remove"
+            ))
+            .css_classes(["flat"])
+            .valign(Align::Center)
             .build();
 
-        if let Some(current_category) = desktop_file.borrow().get_category()
-            && let Some(index) = all_categories
-                .iter()
-                .position(|category| current_category == category.to_string())
-            && let Ok(index) = index.try_into()
-        {
-            combo_row.set_selected(index);
-        } else if let Some(index) = all_categories
+        let action_row = ActionRow::builder()
+            .title(&action.name)
+            .subtitle(&action.url)
+            .use_markup(false)
+            .build();
+        action_row.add_suffix(&remove_button);
+
+        (action_row, remove_button)
+    }
+
+    fn build_flags_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.flags_group.title"
+            ))
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.flags_group.subtitle"
+            ))
+            .build()
+    }
+
+    fn build_fallback_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.fallback_group.title"
+            ))
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.fallback_group.subtitle"
+            ))
+            .build()
+    }
+
+    fn build_fallback_browsers_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let fallback_browsers = desktop_file.borrow().get_fallback_browsers();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.fallback_group.browsers.title"
+            ))
+            .text(fallback_browsers.join("; "))
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_advanced_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.advanced_group.title"
+            ))
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.advanced_group.subtitle"
+            ))
+            .build()
+    }
+
+    fn build_extra_args_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let extra_args = desktop_file.borrow().get_extra_args();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.advanced_group.extra_args.title"
+            ))
+            .text(exec::build(&[exec::Part::Args(extra_args)]).unwrap_or_default())
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_env_vars_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let assignments = desktop_file
+            .borrow()
+            .get_env_vars()
             .iter()
-            .position(|category| Category::Network == *category)
-            && let Ok(index) = index.try_into()
-        {
-            combo_row.set_selected(index);
-        }
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
 
-        combo_row
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.advanced_group.env_vars.title"
+            ))
+            .text(exec::build(&[exec::Part::Args(assignments)]).unwrap_or_default())
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
     }
 
-    pub fn category_to_string_ui(category: Category) -> String {
-        match category {
-            Category::AudioVideo => t!(
-                "web_apps.web_app_view.optional.dialog.menu_group.category.categories.audio_video"
-            )
-            .to_string(),
-            Category::Audio => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.audio")
-                    .to_string()
-            }
-            Category::Video => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.video")
-                    .to_string()
-            }
-            Category::Development => t!(
-                "web_apps.web_app_view.optional.dialog.menu_group.category.categories.development"
-            )
-            .to_string(),
-            Category::Education => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.education")
-                    .to_string()
-            }
-            Category::Game => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.game")
-                    .to_string()
-            }
-            Category::Graphics => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.graphics")
-                    .to_string()
-            }
-            Category::Network => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.network")
-                    .to_string()
-            }
-            Category::Office => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.office")
-                    .to_string()
-            }
-            Category::Science => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.science")
-                    .to_string()
+    fn build_user_agent_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let user_agent = desktop_file.borrow().get_user_agent();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.advanced_group.user_agent.title"
+            ))
+            .text(user_agent.unwrap_or_default())
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_proxy_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.proxy_group.title"
+            ))
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.proxy_group.subtitle"
+            ))
+            .build()
+    }
+
+    /// The modes depend on the browser, `reset_proxy_rows` sets them
+    fn build_proxy_mode_row() -> ComboRow {
+        ComboRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.proxy_group.mode.title"
+            ))
+            .build()
+    }
+
+    fn build_proxy_server_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let proxy_server = desktop_file.borrow().get_proxy_server();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.proxy_group.server.title"
+            ))
+            .text(proxy_server.unwrap_or_default())
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::Url)
+            .build()
+    }
+
+    fn build_proxy_bypass_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let proxy_bypass = desktop_file.borrow().get_proxy_bypass();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.proxy_group.bypass.title"
+            ))
+            .text(proxy_bypass.join("; "))
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn proxy_mode_to_string_ui(proxy_mode: Option<ProxyMode>) -> String {
+        match proxy_mode {
+            None => t!("web_apps.web_app_view.optional.dialog.proxy_group.mode.default"),
+            Some(ProxyMode::None) => {
+                t!("web_apps.web_app_view.optional.dialog.proxy_group.mode.none")
             }
-            Category::Settings => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.settings")
-                    .to_string()
+            Some(ProxyMode::System) => {
+                t!("web_apps.web_app_view.optional.dialog.proxy_group.mode.system")
             }
-            Category::System => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.system")
-                    .to_string()
+            Some(ProxyMode::Http) => {
+                t!("web_apps.web_app_view.optional.dialog.proxy_group.mode.http")
             }
-            Category::Utility => {
-                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.utility")
-                    .to_string()
+            Some(ProxyMode::Socks) => {
+                t!("web_apps.web_app_view.optional.dialog.proxy_group.mode.socks")
             }
         }
+        .to_string()
     }
 
-    fn connect_description_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+    fn build_firefox_prefs_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.title"
+            ))
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.subtitle"
+            ))
+            .build()
+    }
+
+    fn build_firefox_pref_name_row() -> EntryRow {
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.name.title"
+            ))
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_firefox_pref_value_row() -> EntryRow {
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.value.title"
+            ))
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_firefox_pref_add_row() -> ButtonRow {
+        ButtonRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.add"
+            ))
+            .start_icon_name("list-add-symbolic")
+            .build()
+    }
+
+    fn build_firefox_pref_row(name: &str, value: &str) -> (ActionRow, Button) {
+        let remove_button = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.remove"
+            ))
+            .css_classes(["flat"])
+            .valign(Align::Center)
+            .build();
+
+        let firefox_pref_row = ActionRow::builder()
+            .title(name)
+            .subtitle(value)
+            .use_markup(false)
+            .build();
+        firefox_pref_row.add_suffix(&remove_button);
+
+        (firefox_pref_row, remove_button)
+    }
+
+    fn build_handlers_pref_group() -> PreferencesGroup {
+        PreferencesGroup::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.handlers_group.title"
+            ))
+            .description(t!(
+                "web_apps.web_app_view.optional.dialog.handlers_group.subtitle"
+            ))
+            .build()
+    }
+
+    fn build_mime_types_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let mime_types = desktop_file.borrow().get_mime_types().join("; ");
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.handlers_group.mime_types.title"
+            ))
+            .text(mime_types)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_handler_url_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let handler_url = desktop_file.borrow().get_handler_url().unwrap_or_default();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.handlers_group.handler_url.title"
+            ))
+            .text(handler_url)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::Url)
+            .build()
+    }
+
+    fn build_default_handler_row() -> ButtonRow {
+        ButtonRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.handlers_group.default.title"
+            ))
+            .start_icon_name("emblem-default-symbolic")
+            .build()
+    }
+
+    fn build_description_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let description = desktop_file.borrow().get_description().unwrap_or_default();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.description.title"
+            ))
+            .text(description)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_generic_name_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let generic_name = desktop_file.borrow().get_generic_name().unwrap_or_default();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.generic_name.title"
+            ))
+            .text(generic_name)
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_additional_categories_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let additional_categories = Self::get_additional_categories(&desktop_file.borrow());
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.additional_categories.title"
+            ))
+            .text(additional_categories.join("; "))
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    fn build_keywords_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> EntryRow {
+        let keywords = desktop_file.borrow().get_keywords();
+
+        EntryRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.keywords.title"
+            ))
+            .text(keywords.join("; "))
+            .show_apply_button(true)
+            .input_purpose(InputPurpose::FreeForm)
+            .build()
+    }
+
+    /// All categories except the main category picked in the combo row
+    fn get_additional_categories(desktop_file: &DesktopFile) -> Vec<String> {
+        let main_categories = desktop_file
+            .get_main_category()
+            .map(|category| category.to_string())
+            .unwrap_or_default();
+        let main_categories = main_categories.split(';').collect::<Vec<_>>();
+
+        desktop_file
+            .get_categories()
+            .into_iter()
+            .filter(|name| !main_categories.contains(&name.as_str()))
+            .collect()
+    }
+
+    fn parse_list(text: &str) -> Vec<String> {
+        text.split([';', ','])
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(std::string::ToString::to_string)
+            .collect()
+    }
+
+    fn build_category_row(desktop_file: &Rc<RefCell<DesktopFile>>) -> ComboRow {
+        let all_categories = Category::get_all();
+
+        // Some weird factory setup where the list calls factory methods...
+        // First create all data structures, then set data from ListStore.
+        // Why is this so unnecessary complicated? ¯\_(ツ)_/¯
+        let list = gio::ListStore::new::<BoxedAnyObject>();
+        for category in all_categories {
+            let boxed = BoxedAnyObject::new(category);
+            list.append(&boxed);
+        }
+        let factory = SignalListItemFactory::new();
+        factory.connect_bind(|_, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<ListItem>() else {
+                error!(?list_item, "Failed to downcast list item");
+                return;
+            };
+            let Some(category_item_boxed) = list_item
+                .item()
+                .and_then(|item| item.downcast::<BoxedAnyObject>().ok())
+            else {
+                error!(?list_item, "Failed to downcast boxed list item");
+                return;
+            };
+
+            let category = category_item_boxed.borrow::<Category>();
+            let box_container = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+            let icon = category.get_icon();
+
+            box_container.append(&icon);
+            box_container.append(&Label::new(Some(&Self::category_to_string_ui(*category))));
+
+            list_item.set_child(Some(&box_container));
+        });
+
+        let combo_row = ComboRow::builder()
+            .title(t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.category.title"
+            ))
+            .subtitle(t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.category.subtitle"
+            ))
+            .model(&list)
+            .factory(&factory)
+            .build();
+
+        if let Some(current_category) = desktop_file.borrow().get_main_category()
+            && let Some(index) = all_categories
+                .iter()
+                .position(|category| current_category == *category)
+            && let Ok(index) = index.try_into()
+        {
+            combo_row.set_selected(index);
+        } else if let Some(index) = all_categories
+            .iter()
+            .position(|category| Category::Network == *category)
+            && let Ok(index) = index.try_into()
+        {
+            combo_row.set_selected(index);
+        }
+
+        combo_row
+    }
+
+    pub fn category_to_string_ui(category: Category) -> String {
+        match category {
+            Category::AudioVideo => t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.category.categories.audio_video"
+            )
+            .to_string(),
+            Category::Audio => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.audio")
+                    .to_string()
+            }
+            Category::Video => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.video")
+                    .to_string()
+            }
+            Category::Development => t!(
+                "web_apps.web_app_view.optional.dialog.menu_group.category.categories.development"
+            )
+            .to_string(),
+            Category::Education => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.education")
+                    .to_string()
+            }
+            Category::Game => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.game")
+                    .to_string()
+            }
+            Category::Graphics => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.graphics")
+                    .to_string()
+            }
+            Category::Network => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.network")
+                    .to_string()
+            }
+            Category::Office => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.office")
+                    .to_string()
+            }
+            Category::Science => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.science")
+                    .to_string()
+            }
+            Category::Settings => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.settings")
+                    .to_string()
+            }
+            Category::System => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.system")
+                    .to_string()
+            }
+            Category::Utility => {
+                t!("web_apps.web_app_view.optional.dialog.menu_group.category.categories.utility")
+                    .to_string()
+            }
+        }
+    }
+
+    fn connect_description_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
         let self_clone = self.clone();
         let web_app_view_clone = web_app_view.clone();
 
@@ -255,4 +889,775 @@ impl OptionalSettings {
                 web_app_view_clone.on_desktop_file_change();
             });
     }
+
+    fn connect_action_add_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.action_add_row.connect_activated(move |_| {
+            let name = self_clone.action_name_row.text().trim().to_string();
+            let url = self_clone.action_url_row.text().trim().to_string();
+
+            if name.is_empty() || url.is_empty() {
+                web_app_view_clone.on_error(
+                    &t!("web_apps.web_app_view.optional.dialog.actions_group.missing"),
+                    None,
+                );
+                return;
+            }
+            if let Err(error) = Url::parse(&url) {
+                self_clone.action_url_row.add_css_class("error");
+                web_app_view_clone.on_error(
+                    &t!("web_apps.web_app_view.optional.dialog.actions_group.invalid_url"),
+                    Some(&anyhow::Error::from(error)),
+                );
+                return;
+            }
+            self_clone.action_url_row.remove_css_class("error");
+
+            {
+                let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                let mut actions = desktop_file_borrow.get_actions();
+                actions.push(DesktopAction::new(&name, &url));
+                desktop_file_borrow.set_actions(&actions);
+            }
+
+            self_clone.action_name_row.set_text("");
+            self_clone.action_url_row.set_text("");
+            web_app_view_clone.on_desktop_file_change();
+            self_clone.reset_action_rows(&web_app_view_clone);
+        });
+    }
+
+    fn reset_action_rows(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        for action_row in self.action_rows.borrow_mut().drain(..) {
+            self.actions_pref_group.remove(&action_row);
+        }
+
+        let actions = self.desktop_file.borrow().get_actions();
+        let mut action_rows = Vec::new();
+
+        for (index, action) in actions.iter().enumerate() {
+            let (action_row, remove_button) = Self::build_action_row(action);
+
+            let self_clone = self.clone();
+            let web_app_view_clone = web_app_view.clone();
+            remove_button.connect_clicked(move |_| {
+                {
+                    let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                    let mut actions = desktop_file_borrow.get_actions();
+                    if index < actions.len() {
+                        actions.remove(index);
+                    }
+                    desktop_file_borrow.set_actions(&actions);
+                }
+                web_app_view_clone.on_desktop_file_change();
+                self_clone.reset_action_rows(&web_app_view_clone);
+            });
+
+            self.actions_pref_group.add(&action_row);
+            action_rows.push(action_row);
+        }
+        Self::move_rows_to_end(
+            &self.actions_pref_group,
+            &[
+                self.action_name_row.upcast_ref(),
+                self.action_url_row.upcast_ref(),
+                self.action_add_row.upcast_ref(),
+            ],
+        );
+
+        *self.action_rows.borrow_mut() = action_rows;
+    }
+
+    /// Keeps the rows to add an item below the list, a `PreferencesGroup` can only append
+    fn move_rows_to_end(pref_group: &PreferencesGroup, rows: &[&Widget]) {
+        for row in rows {
+            pref_group.remove(*row);
+            pref_group.add(*row);
+        }
+    }
+
+    fn reset_flag_rows(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        for flag_row in self.flag_rows.borrow_mut().drain(..) {
+            self.flags_pref_group.remove(&flag_row);
+        }
+
+        let desktop_file_borrow = self.desktop_file.borrow();
+        let flags = desktop_file_borrow
+            .get_browser()
+            .map(|browser| browser.flags.clone())
+            .unwrap_or_default();
+        let enabled_flags = desktop_file_borrow.get_enabled_flags();
+        drop(desktop_file_borrow);
+
+        self.flags_pref_group.set_visible(!flags.is_empty());
+        let mut flag_rows = Vec::new();
+
+        for flag in flags {
+            let flag_row = SwitchRow::builder()
+                .title(flag.get_label(&self.app.locale.current, &self.app.locale.default))
+                .active(enabled_flags.contains(&flag.name))
+                .build();
+
+            let self_clone = self.clone();
+            let web_app_view_clone = web_app_view.clone();
+            flag_row.connect_active_notify(move |flag_row| {
+                {
+                    let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                    let mut enabled_flags = desktop_file_borrow.get_enabled_flags();
+                    enabled_flags.retain(|name| *name != flag.name);
+                    if flag_row.is_active() {
+                        enabled_flags.push(flag.name.clone());
+                    }
+                    desktop_file_borrow.set_enabled_flags(&enabled_flags);
+                }
+                web_app_view_clone.on_desktop_file_change();
+            });
+
+            self.flags_pref_group.add(&flag_row);
+            flag_rows.push(flag_row);
+        }
+
+        *self.flag_rows.borrow_mut() = flag_rows;
+    }
+
+    fn connect_mime_types_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.mime_types_row.connect_apply(move |entry_row| {
+            let mime_types = DesktopFile::parse_mime_types(&entry_row.text());
+            entry_row.set_text(&mime_types.join("; "));
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_mime_types(&mime_types);
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_handler_url_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.handler_url_row.connect_apply(move |entry_row| {
+            let handler_url = entry_row.text().trim().to_string();
+            let is_valid = handler_url.is_empty()
+                || (handler_url.contains(DesktopFile::HANDLER_URL_PLACEHOLDER)
+                    && Url::parse(&handler_url).is_ok());
+
+            if !is_valid {
+                entry_row.add_css_class("error");
+                web_app_view_clone.on_error(
+                    &t!("web_apps.web_app_view.optional.dialog.handlers_group.handler_url.invalid"),
+                    None,
+                );
+                return;
+            }
+            entry_row.remove_css_class("error");
+
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_handler_url(&handler_url);
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_default_handler_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.default_handler_row
+            .connect_activated(move |button_row| {
+                if let Err(error) = self_clone.desktop_file.borrow().set_as_default_handler() {
+                    web_app_view_clone.on_error(
+                        &t!("web_apps.web_app_view.optional.dialog.handlers_group.default.error"),
+                        Some(&error),
+                    );
+                    return;
+                }
+                button_row.set_title(&t!(
+                    "web_apps.web_app_view.optional.dialog.handlers_group.default.success"
+                ));
+            });
+    }
+
+    fn connect_translation_add_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.translation_add_row.connect_activated(move |_| {
+            let locale = self_clone.translation_locale_row.text().trim().to_string();
+            let name = self_clone.translation_name_row.text().trim().to_string();
+            let description = self_clone
+                .translation_description_row
+                .text()
+                .trim()
+                .to_string();
+
+            if !DesktopFile::is_valid_locale(&locale) {
+                self_clone.translation_locale_row.add_css_class("error");
+                web_app_view_clone.on_error(
+                    &t!("web_apps.web_app_view.optional.dialog.translations_group.invalid_locale"),
+                    None,
+                );
+                return;
+            }
+            self_clone.translation_locale_row.remove_css_class("error");
+
+            if name.is_empty() && description.is_empty() {
+                web_app_view_clone.on_error(
+                    &t!("web_apps.web_app_view.optional.dialog.translations_group.missing"),
+                    None,
+                );
+                return;
+            }
+
+            {
+                let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                let mut names = desktop_file_borrow.get_localized_names();
+                let mut descriptions = desktop_file_borrow.get_localized_descriptions();
+                names.insert(locale.clone(), name);
+                descriptions.insert(locale, description);
+                // Empty values are dropped
+                desktop_file_borrow.set_localized_names(&names);
+                desktop_file_borrow.set_localized_descriptions(&descriptions);
+            }
+
+            self_clone.translation_locale_row.set_text("");
+            self_clone.translation_name_row.set_text("");
+            self_clone.translation_description_row.set_text("");
+            web_app_view_clone.on_desktop_file_change();
+            self_clone.reset_translation_rows(&web_app_view_clone);
+        });
+    }
+
+    fn reset_translation_rows(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        for translation_row in self.translation_rows.borrow_mut().drain(..) {
+            self.translations_pref_group.remove(&translation_row);
+        }
+
+        let names = self.desktop_file.borrow().get_localized_names();
+        let descriptions = self.desktop_file.borrow().get_localized_descriptions();
+        let locales = names
+            .keys()
+            .chain(descriptions.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let mut translation_rows = Vec::new();
+
+        for locale in locales {
+            let (translation_row, remove_button) =
+                Self::build_translation_row(&locale, names.get(&locale), descriptions.get(&locale));
+
+            let self_clone = self.clone();
+            let web_app_view_clone = web_app_view.clone();
+            remove_button.connect_clicked(move |_| {
+                {
+                    let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                    let mut names = desktop_file_borrow.get_localized_names();
+                    let mut descriptions = desktop_file_borrow.get_localized_descriptions();
+                    names.remove(&locale);
+                    descriptions.remove(&locale);
+                    desktop_file_borrow.set_localized_names(&names);
+                    desktop_file_borrow.set_localized_descriptions(&descriptions);
+                }
+                web_app_view_clone.on_desktop_file_change();
+                self_clone.reset_translation_rows(&web_app_view_clone);
+            });
+
+            self.translations_pref_group.add(&translation_row);
+            translation_rows.push(translation_row);
+        }
+        Self::move_rows_to_end(
+            &self.translations_pref_group,
+            &[
+                self.translation_locale_row.upcast_ref(),
+                self.translation_name_row.upcast_ref(),
+                self.translation_description_row.upcast_ref(),
+                self.translation_add_row.upcast_ref(),
+            ],
+        );
+
+        *self.translation_rows.borrow_mut() = translation_rows;
+    }
+
+    fn connect_generic_name_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.generic_name_row.connect_apply(move |entry_row| {
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_generic_name(entry_row.text().trim());
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_additional_categories_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.additional_categories_row
+            .connect_apply(move |entry_row| {
+                let mut additional_categories = Vec::new();
+                for name in Self::parse_list(&entry_row.text()) {
+                    let Some(registered_name) = Category::get_registered_name(&name) else {
+                        entry_row.add_css_class("error");
+                        web_app_view_clone.on_error(
+                            &t!(
+                                "web_apps.web_app_view.optional.dialog.menu_group.additional_categories.invalid",
+                                category = name
+                            ),
+                            None,
+                        );
+                        return;
+                    };
+                    additional_categories.push(registered_name);
+                }
+                entry_row.remove_css_class("error");
+                entry_row.set_text(&additional_categories.join("; "));
+
+                {
+                    let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                    let main_category = desktop_file_borrow
+                        .get_main_category()
+                        .unwrap_or(Category::Network);
+                    let categories = main_category
+                        .to_string()
+                        .split(';')
+                        .map(std::string::ToString::to_string)
+                        .chain(additional_categories)
+                        .collect::<Vec<_>>();
+                    desktop_file_borrow.set_categories(&categories);
+                }
+                web_app_view_clone.on_desktop_file_change();
+            });
+    }
+
+    fn connect_fallback_browsers_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.fallback_browsers_row.connect_apply(move |entry_row| {
+            let browser_ids = Self::parse_list(&entry_row.text());
+            if let Some(unknown_id) = browser_ids
+                .iter()
+                .find(|id| self_clone.app.browser_configs.get_base_by_id(id).is_none())
+            {
+                entry_row.add_css_class("error");
+                web_app_view_clone.on_error(
+                    &t!(
+                        "web_apps.web_app_view.optional.dialog.fallback_group.browsers.invalid",
+                        browser = unknown_id
+                    ),
+                    None,
+                );
+                return;
+            }
+            entry_row.remove_css_class("error");
+            entry_row.set_text(&browser_ids.join("; "));
+
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_fallback_browsers(&browser_ids);
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_extra_args_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.extra_args_row.connect_apply(move |entry_row| {
+            entry_row.remove_css_class("error");
+            entry_row.remove_css_class("warning");
+
+            let extra_args = match DesktopFile::parse_extra_args(&entry_row.text())
+                .map_err(DesktopFileError::from)
+                .and_then(|extra_args| {
+                    self_clone
+                        .desktop_file
+                        .borrow_mut()
+                        .set_extra_args(&extra_args)?;
+                    Ok(extra_args)
+                }) {
+                Ok(extra_args) => extra_args,
+                Err(error) => {
+                    entry_row.add_css_class("error");
+                    web_app_view_clone.on_error(
+                        &t!(
+                            "web_apps.web_app_view.optional.dialog.advanced_group.extra_args.invalid",
+                            error = error.to_string()
+                        ),
+                        None,
+                    );
+                    return;
+                }
+            };
+
+            // Still saved, the browser decides which one wins
+            let conflicting_args = DesktopFile::get_conflicting_args(&extra_args);
+            if !conflicting_args.is_empty() {
+                entry_row.add_css_class("warning");
+                web_app_view_clone.on_error(
+                    &t!(
+                        "web_apps.web_app_view.optional.dialog.advanced_group.extra_args.conflict",
+                        args = conflicting_args.join(" ")
+                    ),
+                    None,
+                );
+            }
+
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_env_vars_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.env_vars_row.connect_apply(move |entry_row| {
+            let result = DesktopFile::parse_env_vars(&entry_row.text())
+                .map_err(DesktopFileError::from)
+                .and_then(|env_vars| self_clone.desktop_file.borrow_mut().set_env_vars(&env_vars));
+            if let Err(error) = result {
+                entry_row.add_css_class("error");
+                web_app_view_clone.on_error(
+                    &t!(
+                        "web_apps.web_app_view.optional.dialog.advanced_group.env_vars.invalid",
+                        error = error.to_string()
+                    ),
+                    None,
+                );
+                return;
+            }
+            entry_row.remove_css_class("error");
+
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_user_agent_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.user_agent_row.connect_apply(move |entry_row| {
+            let user_agent = entry_row.text().trim().to_string();
+            entry_row.set_text(&user_agent);
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_user_agent(&user_agent);
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    /// The browser and isolation can change after init, the tooltip explains why the
+    /// row is disabled
+    fn reset_user_agent_row(&self) {
+        let support = self.desktop_file.borrow().get_user_agent_support();
+        let explanation = match support {
+            SettingSupport::Supported => None,
+            SettingSupport::NeedsIsolation => Some(t!(
+                "web_apps.web_app_view.optional.dialog.advanced_group.user_agent.needs_isolation"
+            )),
+            SettingSupport::Unsupported => Some(t!(
+                "web_apps.web_app_view.optional.dialog.advanced_group.user_agent.unsupported"
+            )),
+        };
+
+        self.user_agent_row.set_sensitive(explanation.is_none());
+        self.user_agent_row.set_tooltip_text(explanation.as_deref());
+    }
+
+    fn connect_proxy_mode_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.proxy_mode_row
+            .connect_selected_notify(move |combo_row| {
+                // Empty while `reset_proxy_rows` replaces the model
+                let Some(proxy_mode) = usize::try_from(combo_row.selected())
+                    .ok()
+                    .and_then(|index| self_clone.proxy_modes.borrow().get(index).copied())
+                else {
+                    return;
+                };
+                if self_clone.desktop_file.borrow().get_proxy_mode() == proxy_mode {
+                    return;
+                }
+
+                self_clone
+                    .desktop_file
+                    .borrow_mut()
+                    .set_proxy_mode(proxy_mode);
+                self_clone.reset_proxy_entry_rows();
+
+                // Saved once the server is applied
+                let needs_server = proxy_mode.is_some_and(ProxyMode::is_manual)
+                    && self_clone
+                        .desktop_file
+                        .borrow()
+                        .get_proxy_server()
+                        .is_none();
+                if needs_server {
+                    self_clone.proxy_server_row.add_css_class("error");
+                    self_clone.proxy_server_row.grab_focus();
+                    web_app_view_clone.on_error(
+                        &t!("web_apps.web_app_view.optional.dialog.proxy_group.server.missing"),
+                        None,
+                    );
+                    return;
+                }
+
+                web_app_view_clone.on_desktop_file_change();
+            });
+    }
+
+    fn connect_proxy_server_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.proxy_server_row.connect_apply(move |entry_row| {
+            let proxy_server = entry_row.text().trim().to_string();
+            if let Err(error) = self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_proxy_server(&proxy_server)
+            {
+                entry_row.add_css_class("error");
+                web_app_view_clone.on_error(
+                    &t!(
+                        "web_apps.web_app_view.optional.dialog.proxy_group.server.invalid",
+                        error = error.to_string()
+                    ),
+                    None,
+                );
+                return;
+            }
+            entry_row.remove_css_class("error");
+            entry_row.set_text(&proxy_server);
+
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    fn connect_proxy_bypass_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.proxy_bypass_row.connect_apply(move |entry_row| {
+            let hosts = Self::parse_list(&entry_row.text());
+            entry_row.set_text(&hosts.join("; "));
+            self_clone
+                .desktop_file
+                .borrow_mut()
+                .set_proxy_bypass(&hosts);
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
+
+    /// The browser and isolation can change after init, so the modes are the ones of the
+    /// current browser and a saved mode it does not support
+    fn reset_proxy_rows(&self) {
+        let (browser_modes, current_mode) = {
+            let desktop_file = self.desktop_file.borrow();
+            let browser_modes = desktop_file
+                .get_browser()
+                .map(|browser| browser.proxy_modes.clone())
+                .unwrap_or_default();
+            (browser_modes, desktop_file.get_proxy_mode())
+        };
+
+        let mut proxy_modes = vec![None];
+        proxy_modes.extend(browser_modes.iter().copied().map(Some));
+        if !proxy_modes.contains(&current_mode) {
+            proxy_modes.push(current_mode);
+        }
+        let names = proxy_modes
+            .iter()
+            .map(|proxy_mode| Self::proxy_mode_to_string_ui(*proxy_mode))
+            .collect::<Vec<_>>();
+
+        self.proxy_modes.borrow_mut().clear();
+        self.proxy_mode_row.set_model(Some(&StringList::new(
+            &names.iter().map(String::as_str).collect::<Vec<_>>(),
+        )));
+        let selected = proxy_modes
+            .iter()
+            .position(|proxy_mode| *proxy_mode == current_mode)
+            .and_then(|index| index.try_into().ok())
+            .unwrap_or_default();
+        *self.proxy_modes.borrow_mut() = proxy_modes;
+        self.proxy_mode_row.set_selected(selected);
+
+        // Isolation is the same for every mode of the browser
+        let support = browser_modes
+            .first()
+            .map_or(SettingSupport::Unsupported, |proxy_mode| {
+                self.desktop_file.borrow().get_proxy_support(*proxy_mode)
+            });
+        let explanation = match support {
+            SettingSupport::Supported => None,
+            SettingSupport::NeedsIsolation => Some(t!(
+                "web_apps.web_app_view.optional.dialog.proxy_group.mode.needs_isolation"
+            )),
+            SettingSupport::Unsupported => Some(t!(
+                "web_apps.web_app_view.optional.dialog.proxy_group.mode.unsupported"
+            )),
+        };
+
+        self.proxy_pref_group.set_sensitive(explanation.is_none());
+        self.proxy_pref_group
+            .set_tooltip_text(explanation.as_deref());
+        self.reset_proxy_entry_rows();
+    }
+
+    /// Only a manual mode has a server and hosts without proxy
+    fn reset_proxy_entry_rows(&self) {
+        let is_manual = self
+            .desktop_file
+            .borrow()
+            .get_proxy_mode()
+            .is_some_and(ProxyMode::is_manual);
+
+        self.proxy_server_row.set_sensitive(is_manual);
+        self.proxy_bypass_row.set_sensitive(is_manual);
+        if !is_manual {
+            self.proxy_server_row.remove_css_class("error");
+        }
+    }
+
+    fn connect_firefox_pref_add_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.firefox_pref_add_row.connect_activated(move |_| {
+            let name = self_clone.firefox_pref_name_row.text().trim().to_string();
+            // An empty value is an empty string
+            let value = self_clone.firefox_pref_value_row.text().trim().to_string();
+
+            if name.is_empty() {
+                web_app_view_clone.on_error(
+                    &t!("web_apps.web_app_view.optional.dialog.firefox_prefs_group.missing"),
+                    None,
+                );
+                return;
+            }
+
+            let result = {
+                let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                let mut prefs = desktop_file_borrow.get_firefox_prefs();
+                // Adding a pref again changes its value
+                match prefs.iter_mut().find(|(pref_name, _)| *pref_name == name) {
+                    Some((_, pref_value)) => pref_value.clone_from(&value),
+                    None => prefs.push((name, value)),
+                }
+                desktop_file_borrow.set_firefox_prefs(&prefs)
+            };
+            if let Err(error) = result {
+                self_clone.firefox_pref_name_row.add_css_class("error");
+                web_app_view_clone.on_error(
+                    &t!(
+                        "web_apps.web_app_view.optional.dialog.firefox_prefs_group.invalid",
+                        error = error.to_string()
+                    ),
+                    None,
+                );
+                return;
+            }
+            self_clone.firefox_pref_name_row.remove_css_class("error");
+
+            self_clone.firefox_pref_name_row.set_text("");
+            self_clone.firefox_pref_value_row.set_text("");
+            web_app_view_clone.on_desktop_file_change();
+            self_clone.reset_firefox_pref_rows(&web_app_view_clone);
+        });
+    }
+
+    /// Shown for Firefox based browsers or saved prefs, the browser and isolation can
+    /// change after init
+    fn reset_firefox_pref_rows(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        for firefox_pref_row in self.firefox_pref_rows.borrow_mut().drain(..) {
+            self.firefox_prefs_pref_group.remove(&firefox_pref_row);
+        }
+
+        let support = self.desktop_file.borrow().get_firefox_prefs_support();
+        let prefs = self.desktop_file.borrow().get_firefox_prefs();
+        // Prefs are kept for another browser, they apply again once the profile can take them
+        let explanation = match support {
+            SettingSupport::Supported => None,
+            SettingSupport::NeedsIsolation => Some(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.needs_isolation"
+            )),
+            SettingSupport::Unsupported => Some(t!(
+                "web_apps.web_app_view.optional.dialog.firefox_prefs_group.unsupported"
+            )),
+        };
+
+        self.firefox_prefs_pref_group
+            .set_visible(support != SettingSupport::Unsupported || !prefs.is_empty());
+        self.firefox_prefs_pref_group
+            .set_tooltip_text(explanation.as_deref());
+
+        let mut firefox_pref_rows = Vec::new();
+
+        for (name, value) in &prefs {
+            let (firefox_pref_row, remove_button) = Self::build_firefox_pref_row(name, value);
+
+            let self_clone = self.clone();
+            let web_app_view_clone = web_app_view.clone();
+            let name = name.clone();
+            remove_button.connect_clicked(move |_| {
+                let result = {
+                    let mut desktop_file_borrow = self_clone.desktop_file.borrow_mut();
+                    let mut prefs = desktop_file_borrow.get_firefox_prefs();
+                    prefs.retain(|(pref_name, _)| *pref_name != name);
+                    desktop_file_borrow.set_firefox_prefs(&prefs)
+                };
+                if let Err(error) = result {
+                    web_app_view_clone.on_error(
+                        &t!(
+                            "web_apps.web_app_view.optional.dialog.firefox_prefs_group.invalid",
+                            error = error.to_string()
+                        ),
+                        None,
+                    );
+                    return;
+                }
+                web_app_view_clone.on_desktop_file_change();
+                self_clone.reset_firefox_pref_rows(&web_app_view_clone);
+            });
+
+            self.firefox_prefs_pref_group.add(&firefox_pref_row);
+            firefox_pref_rows.push(firefox_pref_row);
+        }
+        Self::move_rows_to_end(
+            &self.firefox_prefs_pref_group,
+            &[
+                self.firefox_pref_name_row.upcast_ref(),
+                self.firefox_pref_value_row.upcast_ref(),
+                self.firefox_pref_add_row.upcast_ref(),
+            ],
+        );
+
+        *self.firefox_pref_rows.borrow_mut() = firefox_pref_rows;
+    }
+
+    fn connect_keywords_row(self: &Rc<Self>, web_app_view: &Rc<WebAppView>) {
+        let self_clone = self.clone();
+        let web_app_view_clone = web_app_view.clone();
+
+        self.keywords_row.connect_apply(move |entry_row| {
+            let keywords = Self::parse_list(&entry_row.text());
+            entry_row.set_text(&keywords.join("; "));
+            self_clone.desktop_file.borrow_mut().set_keywords(&keywords);
+            web_app_view_clone.on_desktop_file_change();
+        });
+    }
 }
@@ -0,0 +1,77 @@
+use crate::application::App;
+use common::{browsers::Browser, desktop_file::ProfileMigration};
+use libadwaita::{
+    AlertDialog, ResponseAppearance,
+    prelude::{AdwDialogExt, AlertDialogExt},
+};
+use std::rc::Rc;
+
+pub struct ProfileMigrationDialog;
+impl ProfileMigrationDialog {
+    pub const DIALOG_MOVE: &str = "move";
+    pub const DIALOG_COPY: &str = "copy";
+    pub const DIALOG_DISCARD: &str = "discard";
+    pub const DIALOG_CANCEL: &str = "cancel";
+
+    /// Asks what happens with the profile when the browser changes, `on_choice` gets `None`
+    /// when canceled. A browser with another engine cannot read the profile, then only
+    /// starting with a new profile is offered.
+    pub fn show<F>(app: &Rc<App>, browser: &Browser, can_migrate: bool, on_choice: F)
+    where
+        F: Fn(Option<ProfileMigration>) + 'static,
+    {
+        let browser_name = browser.get_name_with_installation();
+        let dialog = AlertDialog::builder()
+            .heading(t!(
+                "web_apps.web_app_view.profile_migration.title",
+                browser = browser_name
+            ))
+            .width_request(400)
+            .build();
+        dialog.add_response(
+            Self::DIALOG_CANCEL,
+            &t!("web_apps.web_app_view.profile_migration.cancel"),
+        );
+        dialog.set_close_response(Self::DIALOG_CANCEL);
+
+        if can_migrate {
+            dialog.set_body(&t!("web_apps.web_app_view.profile_migration.text"));
+            dialog.add_response(
+                Self::DIALOG_DISCARD,
+                &t!("web_apps.web_app_view.profile_migration.discard"),
+            );
+            dialog.add_response(
+                Self::DIALOG_COPY,
+                &t!("web_apps.web_app_view.profile_migration.copy"),
+            );
+            dialog.add_response(
+                Self::DIALOG_MOVE,
+                &t!("web_apps.web_app_view.profile_migration.move"),
+            );
+            dialog.set_response_appearance(Self::DIALOG_MOVE, ResponseAppearance::Suggested);
+            dialog.set_default_response(Some(Self::DIALOG_MOVE));
+        } else {
+            dialog.set_body(&t!(
+                "web_apps.web_app_view.profile_migration.text_incompatible"
+            ));
+            dialog.add_response(
+                Self::DIALOG_DISCARD,
+                &t!("web_apps.web_app_view.profile_migration.discard_incompatible"),
+            );
+            dialog.set_response_appearance(Self::DIALOG_DISCARD, ResponseAppearance::Destructive);
+            dialog.set_default_response(Some(Self::DIALOG_CANCEL));
+        }
+
+        dialog.connect_response(None, move |_, response| {
+            let migration = match response {
+                Self::DIALOG_MOVE => Some(ProfileMigration::Move),
+                Self::DIALOG_COPY => Some(ProfileMigration::Copy),
+                Self::DIALOG_DISCARD => Some(ProfileMigration::Discard),
+                _ => None,
+            };
+            on_choice(migration);
+        });
+
+        dialog.present(Some(&app.window.adw_window));
+    }
+}
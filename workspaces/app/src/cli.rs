@@ -0,0 +1,1352 @@
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Args, Parser, Subcommand};
+use common::{
+    app_dirs::AppDirs,
+    assets,
+    browser_apps::BrowserApp,
+    browsers::{Base, Browser, BrowserConfigs, ProxyMode},
+    bundle::Bundle,
+    desktop_file::{DesktopFile, ProfileMigration, category::Category, error::DesktopFileError},
+    importers::ForeignApp,
+    orphans::Orphan,
+    transaction::Transaction,
+    user_settings::UserSettings,
+    utils,
+};
+use gtk::{gdk_pixbuf::Pixbuf, glib};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{debug, error};
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    /// Opens the app window when no command is given
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List all web apps
+    List {
+        /// Print as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show a web app
+    Show {
+        /// Id of the web app
+        id: String,
+        /// Print as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Create a new web app
+    Create(CreateArgs),
+    /// Edit an existing web app
+    Edit(EditArgs),
+    /// Duplicate a web app with its own icon and profile, prints the new id
+    Duplicate {
+        /// Id of the web app
+        id: String,
+        /// Name of the copy, defaults to the name with ' (copy)'
+        #[arg(long)]
+        name: Option<String>,
+        /// Copy the isolated profile (logins, site data, extensions)
+        #[arg(long)]
+        copy_profile: bool,
+    },
+    /// Delete a web app including its icon and profile
+    Delete {
+        /// Id of the web app
+        id: String,
+    },
+    /// Launch a web app
+    Launch {
+        /// Id of the web app
+        id: String,
+    },
+    /// Open a handled link or file in a web app, used by the desktop file of handlers
+    Handle {
+        /// Id of the web app
+        id: String,
+        /// Link or file uri (e.g. 'mailto:someone@example.com')
+        uri: Option<String>,
+    },
+    /// Export web apps to a bundle
+    Export {
+        /// Ids of the web apps, exports all web apps when empty
+        ids: Vec<String>,
+        /// Bundle file to write
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Include isolated profiles (logins, site data, extensions)
+        #[arg(long)]
+        profiles: bool,
+    },
+    /// Import web apps from a bundle
+    Import {
+        /// Bundle file to read
+        file: PathBuf,
+        /// Browser id to use instead of the browser in the bundle, see the `browsers` command
+        #[arg(long)]
+        browser: Option<String>,
+    },
+    /// Adopt apps and shortcuts created by a browser, lists them when no id is given
+    Adopt {
+        /// Ids of the browser apps (e.g. the id in 'chrome-<id>-Default.desktop')
+        ids: Vec<String>,
+        /// Url of the website, needed for installed apps of which the url is unknown
+        #[arg(long)]
+        url: Option<String>,
+    },
+    /// Import web apps of other web app managers, lists them when no file is given
+    ImportFrom {
+        /// Desktop file names of the web apps (e.g. 'vscode.dev-WebApp.desktop')
+        files: Vec<String>,
+        /// Browser id for apps of which the browser is not installed, see the `browsers` command
+        #[arg(long)]
+        browser: Option<String>,
+    },
+    /// List profiles and icons that no web app uses anymore
+    Cleanup {
+        /// Delete the listed profiles and icons
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Migrate web apps created by an older version
+    Migrate {
+        /// Only show which migrations would apply
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show or set the browsers for web apps of which the browser is uninstalled
+    Fallback {
+        /// Browser ids in order of preference, separated by ';' (e.g. 'org.chromium.Chromium;chromium'),
+        /// an empty string clears the list
+        browsers: Option<String>,
+    },
+    /// List all installed browsers
+    Browsers {
+        /// Print as json
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Name of the web app
+    #[arg(long)]
+    name: String,
+    /// Website url
+    #[arg(long)]
+    url: String,
+    /// Browser id, see the `browsers` command
+    #[arg(long)]
+    browser: String,
+    /// Path to an image file or an icon name from the icon theme
+    #[arg(long)]
+    icon: String,
+    /// Use an isolated profile
+    #[arg(long)]
+    isolate: bool,
+    /// Always start the app maximized
+    #[arg(long)]
+    maximize: bool,
+    /// Short app description
+    #[arg(long)]
+    description: Option<String>,
+    /// Menu categories, a main category optionally followed by more (e.g. 'Network;Chat')
+    #[arg(long)]
+    category: Option<String>,
+    /// Generic name shown by some launchers (e.g. 'Video Conference')
+    #[arg(long)]
+    generic_name: Option<String>,
+    /// Search keywords, separated by ';' (e.g. 'meeting;chat')
+    #[arg(long)]
+    keywords: Option<String>,
+    /// Handled url schemes and MIME types, separated by ';' (e.g. 'mailto;tel')
+    #[arg(long)]
+    mime_types: Option<String>,
+    /// Url to open for a handled link, '%s' is replaced by the link
+    #[arg(long)]
+    handler_url: Option<String>,
+    /// Set the web app as default app for its url schemes and MIME types
+    #[arg(long)]
+    set_default: bool,
+    /// Enabled browser options, separated by ';', see the `browsers` command
+    #[arg(long)]
+    flags: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EditArgs {
+    /// Id of the web app
+    id: String,
+    /// Name of the web app
+    #[arg(long)]
+    name: Option<String>,
+    /// Website url
+    #[arg(long)]
+    url: Option<String>,
+    /// Browser id, see the `browsers` command
+    #[arg(long)]
+    browser: Option<String>,
+    /// Path to an image file or an icon name from the icon theme
+    #[arg(long)]
+    icon: Option<String>,
+    /// Use an isolated profile
+    #[arg(long)]
+    isolate: Option<bool>,
+    /// Always start the app maximized
+    #[arg(long)]
+    maximize: Option<bool>,
+    /// Short app description
+    #[arg(long)]
+    description: Option<String>,
+    /// Menu categories, a main category optionally followed by more (e.g. 'Network;Chat')
+    #[arg(long)]
+    category: Option<String>,
+    /// Generic name shown by some launchers (e.g. 'Video Conference')
+    #[arg(long)]
+    generic_name: Option<String>,
+    /// Search keywords, separated by ';' (e.g. 'meeting;chat')
+    #[arg(long)]
+    keywords: Option<String>,
+    /// Handled url schemes and MIME types, separated by ';' (e.g. 'mailto;tel')
+    #[arg(long)]
+    mime_types: Option<String>,
+    /// Url to open for a handled link, '%s' is replaced by the link
+    #[arg(long)]
+    handler_url: Option<String>,
+    /// Set the web app as default app for its url schemes and MIME types
+    #[arg(long)]
+    set_default: bool,
+    /// Enabled browser options, separated by ';', see the `browsers` command
+    #[arg(long)]
+    flags: Option<String>,
+    /// What happens with the isolated profile when the browser changes: 'move' (default for
+    /// the same engine), 'copy' or 'discard' (required for another engine)
+    #[arg(long)]
+    migrate_profile: Option<String>,
+    /// Browser ids to switch to when the browser is uninstalled, separated by ';'
+    #[arg(long)]
+    fallback_browsers: Option<String>,
+    /// Extra browser arguments, quoted as in Exec (e.g. '--disable-gpu "--lang=en US"'),
+    /// empty to remove them
+    #[arg(long, allow_hyphen_values = true)]
+    extra_args: Option<String>,
+    /// Environment variables for the browser, NAME=value quoted as in Exec
+    /// (e.g. 'MOZ_ENABLE_WAYLAND=1 "LANG=nl_NL.UTF-8"'), empty to remove them
+    #[arg(long)]
+    env: Option<String>,
+    /// User agent that overrides the one of the browser, empty to remove it. Firefox based
+    /// browsers need an isolated profile.
+    #[arg(long)]
+    user_agent: Option<String>,
+    /// Proxy: 'none', 'system', 'http' or 'socks', empty for the browser default. Firefox
+    /// based browsers need an isolated profile.
+    #[arg(long)]
+    proxy_mode: Option<String>,
+    /// Server of an 'http' or 'socks' proxy, host:port (e.g. 'localhost:8080')
+    #[arg(long)]
+    proxy_server: Option<String>,
+    /// Hosts that connect without the proxy, separated by ';' (e.g. 'localhost;*.lan')
+    #[arg(long)]
+    proxy_bypass: Option<String>,
+    /// Prefs for the isolated profile of a Firefox based browser, name=value quoted as in Exec
+    /// (e.g. 'media.peerconnection.enabled=false media.eme.enabled=true'), empty to remove them
+    #[arg(long)]
+    firefox_prefs: Option<String>,
+}
+
+#[derive(Serialize)]
+struct WebAppJson {
+    id: String,
+    name: String,
+    url: String,
+    browser_id: Option<String>,
+    browser_name: Option<String>,
+    isolated: bool,
+    maximized: bool,
+    profile_path: Option<PathBuf>,
+    icon_path: Option<PathBuf>,
+    description: Option<String>,
+    category: Option<String>,
+    generic_name: Option<String>,
+    keywords: Vec<String>,
+    flags: Vec<String>,
+    extra_args: Vec<String>,
+    env: BTreeMap<String, String>,
+    user_agent: Option<String>,
+    proxy_mode: Option<ProxyMode>,
+    proxy_server: Option<String>,
+    proxy_bypass: Vec<String>,
+    firefox_prefs: BTreeMap<String, String>,
+    mime_types: Vec<String>,
+    handler_url: Option<String>,
+    desktop_file_path: PathBuf,
+}
+impl WebAppJson {
+    fn from_desktop_file(desktop_file: &DesktopFile) -> Self {
+        let browser = desktop_file.get_browser();
+
+        Self {
+            id: desktop_file.get_id().unwrap_or_default(),
+            name: desktop_file.get_name().unwrap_or_default(),
+            url: desktop_file.get_url().unwrap_or_default(),
+            browser_id: browser.as_ref().map(|browser| browser.id.clone()),
+            browser_name: browser
+                .as_ref()
+                .map(|browser| browser.get_name_with_installation()),
+            isolated: desktop_file.get_isolated().unwrap_or(false),
+            maximized: desktop_file.get_maximized().unwrap_or(false),
+            profile_path: desktop_file
+                .get_profile_path()
+                .filter(|path| !path.as_os_str().is_empty()),
+            icon_path: desktop_file.get_icon_path(),
+            description: desktop_file.get_description(),
+            category: desktop_file.get_category(),
+            generic_name: desktop_file.get_generic_name(),
+            keywords: desktop_file.get_keywords(),
+            flags: desktop_file.get_enabled_flags(),
+            extra_args: desktop_file.get_extra_args(),
+            env: desktop_file.get_env_vars().into_iter().collect(),
+            user_agent: desktop_file.get_user_agent(),
+            proxy_mode: desktop_file.get_proxy_mode(),
+            proxy_server: desktop_file.get_proxy_server(),
+            proxy_bypass: desktop_file.get_proxy_bypass(),
+            firefox_prefs: desktop_file.get_firefox_prefs().into_iter().collect(),
+            mime_types: desktop_file.get_mime_types(),
+            handler_url: desktop_file.get_handler_url(),
+            desktop_file_path: desktop_file.get_path(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BrowserJson {
+    id: String,
+    name: String,
+    can_isolate: bool,
+    can_start_maximized: bool,
+    flags: Vec<String>,
+    proxy_modes: Vec<ProxyMode>,
+}
+
+struct CliContext {
+    app_dirs: Rc<AppDirs>,
+    browser_configs: Rc<BrowserConfigs>,
+    user_settings: UserSettings,
+}
+
+pub fn run(command: Command) -> Result<()> {
+    let app_dirs = AppDirs::new()?;
+    assets::init(&app_dirs)?;
+    let browser_configs = BrowserConfigs::new_headless(&app_dirs);
+    browser_configs.init();
+
+    let user_settings = UserSettings::new(&app_dirs);
+
+    let context = CliContext {
+        app_dirs,
+        browser_configs,
+        user_settings,
+    };
+
+    match command {
+        Command::List { json } => list(&context, json),
+        Command::Show { id, json } => show(&context, &id, json),
+        Command::Create(args) => create(&context, args),
+        Command::Edit(args) => edit(&context, args),
+        Command::Duplicate {
+            id,
+            name,
+            copy_profile,
+        } => duplicate(&context, &id, name.as_deref(), copy_profile),
+        Command::Delete { id } => delete(&context, &id),
+        Command::Launch { id } => launch(&context, &id),
+        Command::Handle { id, uri } => handle(&context, &id, uri.as_deref()),
+        Command::Export {
+            ids,
+            output,
+            profiles,
+        } => export(&context, &ids, &output, profiles),
+        Command::Import { file, browser } => import(&context, &file, browser.as_deref()),
+        Command::Adopt { ids, url } => adopt(&context, &ids, url.as_deref()),
+        Command::ImportFrom { files, browser } => import_from(&context, &files, browser.as_deref()),
+        Command::Cleanup { delete } => cleanup(&context, delete),
+        Command::Migrate { dry_run } => migrate(&context, dry_run),
+        Command::Fallback { browsers } => fallback(context, browsers.as_deref()),
+        Command::Browsers { json } => browsers(&context, json),
+    }
+}
+
+fn list(context: &CliContext, json: bool) -> Result<()> {
+    let desktop_files = get_owned_desktop_files(context);
+
+    if json {
+        let web_apps = desktop_files
+            .iter()
+            .map(WebAppJson::from_desktop_file)
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&web_apps)?);
+        return Ok(());
+    }
+
+    for desktop_file in &desktop_files {
+        println!(
+            "{}\t{}\t{}\t{}",
+            desktop_file.get_id().unwrap_or_default(),
+            desktop_file.get_name().unwrap_or_default(),
+            desktop_file.get_url().unwrap_or_default(),
+            desktop_file
+                .get_browser()
+                .map(|browser| browser.get_name_with_installation())
+                .unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+fn show(context: &CliContext, id: &str, json: bool) -> Result<()> {
+    let desktop_file = get_desktop_file_by_id(context, id)?;
+    let web_app = WebAppJson::from_desktop_file(&desktop_file);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&web_app)?);
+        return Ok(());
+    }
+
+    let mut txt = String::new();
+    let _ = writeln!(txt, "Id:           {}", web_app.id);
+    let _ = writeln!(txt, "Name:         {}", web_app.name);
+    let _ = writeln!(txt, "Url:          {}", web_app.url);
+    let _ = writeln!(
+        txt,
+        "Browser:      {}",
+        web_app.browser_name.unwrap_or_default()
+    );
+    let _ = writeln!(txt, "Isolated:     {}", web_app.isolated);
+    let _ = writeln!(txt, "Maximized:    {}", web_app.maximized);
+    let _ = writeln!(
+        txt,
+        "Description:  {}",
+        web_app.description.unwrap_or_default()
+    );
+    let _ = writeln!(
+        txt,
+        "Category:     {}",
+        web_app.category.unwrap_or_default()
+    );
+    if let Some(generic_name) = web_app.generic_name {
+        let _ = writeln!(txt, "Generic name: {generic_name}");
+    }
+    if !web_app.keywords.is_empty() {
+        let _ = writeln!(txt, "Keywords:     {}", web_app.keywords.join(", "));
+    }
+    if !web_app.flags.is_empty() {
+        let _ = writeln!(txt, "Options:      {}", web_app.flags.join(", "));
+    }
+    let _ = writeln!(
+        txt,
+        "Icon:         {}",
+        web_app.icon_path.unwrap_or_default().display()
+    );
+    if let Some(profile_path) = web_app.profile_path {
+        let _ = writeln!(txt, "Profile:      {}", profile_path.display());
+    }
+    if !web_app.mime_types.is_empty() {
+        let _ = writeln!(txt, "Handles:      {}", web_app.mime_types.join(", "));
+        let _ = writeln!(
+            txt,
+            "Handler url:  {}",
+            web_app.handler_url.unwrap_or_default()
+        );
+    }
+    let _ = write!(txt, "Desktop file: {}", web_app.desktop_file_path.display());
+    println!("{txt}");
+
+    Ok(())
+}
+
+fn create(context: &CliContext, args: CreateArgs) -> Result<()> {
+    let mut desktop_file = DesktopFile::new(&context.browser_configs, &context.app_dirs);
+
+    desktop_file.set_name(&args.name);
+    desktop_file.set_url(&args.url);
+    set_browser(context, &mut desktop_file, &args.browser)?;
+    desktop_file.set_maximized(args.maximize);
+    if let Some(description) = &args.description {
+        desktop_file.set_description(description);
+    }
+    if let Some(category) = &args.category {
+        desktop_file.set_categories(&parse_categories(category)?);
+    }
+    if let Some(generic_name) = &args.generic_name {
+        desktop_file.set_generic_name(generic_name);
+    }
+    if let Some(keywords) = &args.keywords {
+        desktop_file.set_keywords(&parse_list(keywords));
+    }
+    if let Some(mime_types) = &args.mime_types {
+        desktop_file.set_mime_types(&DesktopFile::parse_mime_types(mime_types));
+    }
+    if let Some(handler_url) = &args.handler_url {
+        desktop_file.set_handler_url(handler_url);
+    }
+    if let Some(flags) = &args.flags {
+        set_flags(&mut desktop_file, flags)?;
+    }
+    // Icon, profile and desktop file are rolled back when one of them fails
+    let mut transaction = Transaction::new();
+    set_icon(context, &mut desktop_file, &args.icon, &mut transaction)?;
+    set_isolated(
+        &mut desktop_file,
+        args.isolate,
+        ProfileMigration::Discard,
+        &mut transaction,
+    )?;
+    let desktop_file = save(&desktop_file, &mut transaction)?;
+    transaction.commit();
+
+    if args.set_default {
+        desktop_file.set_as_default_handler()?;
+    }
+    println!("{}", desktop_file.get_id().unwrap_or_default());
+
+    Ok(())
+}
+
+fn edit(context: &CliContext, args: EditArgs) -> Result<()> {
+    let mut desktop_file = get_desktop_file_by_id(context, &args.id)?;
+
+    if let Some(name) = &args.name {
+        desktop_file.set_name(name);
+    }
+    if let Some(url) = &args.url {
+        desktop_file.set_url(url);
+    }
+    // Without a browser change the profile only follows a new profile location
+    let mut profile_migration = ProfileMigration::Move;
+    if let Some(browser_id) = &args.browser {
+        if args.isolate != Some(false) {
+            profile_migration = get_profile_migration(
+                context,
+                &desktop_file,
+                browser_id,
+                args.migrate_profile.as_deref(),
+            )?;
+        }
+        set_browser(context, &mut desktop_file, browser_id)?;
+    }
+    if let Some(maximize) = args.maximize {
+        desktop_file.set_maximized(maximize);
+    }
+    if let Some(description) = &args.description {
+        desktop_file.set_description(description);
+    }
+    if let Some(category) = &args.category {
+        desktop_file.set_categories(&parse_categories(category)?);
+    }
+    if let Some(generic_name) = &args.generic_name {
+        desktop_file.set_generic_name(generic_name);
+    }
+    if let Some(keywords) = &args.keywords {
+        desktop_file.set_keywords(&parse_list(keywords));
+    }
+    if let Some(mime_types) = &args.mime_types {
+        desktop_file.set_mime_types(&DesktopFile::parse_mime_types(mime_types));
+    }
+    if let Some(handler_url) = &args.handler_url {
+        desktop_file.set_handler_url(handler_url);
+    }
+    if let Some(flags) = &args.flags {
+        set_flags(&mut desktop_file, flags)?;
+    }
+    if let Some(fallback_browsers) = &args.fallback_browsers {
+        let browser_ids = parse_browser_ids(context, fallback_browsers)?;
+        desktop_file.set_fallback_browsers(&browser_ids);
+    }
+    if let Some(extra_args) = &args.extra_args {
+        set_extra_args(&mut desktop_file, extra_args)?;
+    }
+    if let Some(env) = &args.env {
+        let env_vars =
+            DesktopFile::parse_env_vars(env).map_err(|error| anyhow!("Invalid input: {error}"))?;
+        desktop_file
+            .set_env_vars(&env_vars)
+            .map_err(|error| match error {
+                DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+                DesktopFileError::Other(error) => error.context("Error setting environment"),
+            })?;
+    }
+    if let Some(user_agent) = &args.user_agent {
+        desktop_file.set_user_agent(user_agent);
+    }
+    if let Some(proxy_mode) = &args.proxy_mode {
+        desktop_file.set_proxy_mode(parse_proxy_mode(proxy_mode)?);
+    }
+    if let Some(proxy_server) = &args.proxy_server {
+        desktop_file
+            .set_proxy_server(proxy_server)
+            .map_err(|error| match error {
+                DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+                DesktopFileError::Other(error) => error.context("Error setting proxy server"),
+            })?;
+    }
+    if let Some(proxy_bypass) = &args.proxy_bypass {
+        desktop_file.set_proxy_bypass(&parse_list(proxy_bypass));
+    }
+    if let Some(firefox_prefs) = &args.firefox_prefs {
+        let prefs = DesktopFile::parse_firefox_prefs(firefox_prefs)
+            .map_err(|error| anyhow!("Invalid input: {error}"))?;
+        desktop_file
+            .set_firefox_prefs(&prefs)
+            .map_err(|error| match error {
+                DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+                DesktopFileError::Other(error) => error.context("Error setting Firefox prefs"),
+            })?;
+    }
+    let mut transaction = Transaction::new();
+    if let Some(icon) = &args.icon {
+        set_icon(context, &mut desktop_file, icon, &mut transaction)?;
+    }
+
+    // Browser changes the profile path, otherwise the profile is left alone
+    if args.browser.is_some() || args.isolate.is_some() {
+        let is_isolated = args
+            .isolate
+            .unwrap_or(desktop_file.get_isolated().unwrap_or(false));
+        set_isolated(
+            &mut desktop_file,
+            is_isolated,
+            profile_migration,
+            &mut transaction,
+        )?;
+    }
+    let desktop_file = save(&desktop_file, &mut transaction)?;
+    transaction.commit();
+
+    if args.set_default {
+        desktop_file.set_as_default_handler()?;
+    }
+
+    Ok(())
+}
+
+fn duplicate(context: &CliContext, id: &str, name: Option<&str>, copy_profile: bool) -> Result<()> {
+    let desktop_file = get_desktop_file_by_id(context, id)?;
+    let name = name.map_or_else(
+        || format!("{} (copy)", desktop_file.get_name().unwrap_or_default()),
+        str::to_string,
+    );
+
+    let duplicate = desktop_file
+        .duplicate(&name, copy_profile)
+        .map_err(|error| match error {
+            DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+            DesktopFileError::Other(error) => error.context("Error duplicating web app"),
+        })?;
+    println!("{}", duplicate.get_id().unwrap_or_default());
+
+    Ok(())
+}
+
+fn delete(context: &CliContext, id: &str) -> Result<()> {
+    let desktop_file = get_desktop_file_by_id(context, id)?;
+    desktop_file.delete()
+}
+
+fn launch(context: &CliContext, id: &str) -> Result<()> {
+    let desktop_file = get_desktop_file_by_id(context, id)?;
+    let Some(executable) = desktop_file.get_launch_exec() else {
+        bail!("No executable on web app: {id}")
+    };
+
+    run_executable(&desktop_file, executable)
+}
+
+fn handle(context: &CliContext, id: &str, uri: Option<&str>) -> Result<()> {
+    let desktop_file = get_desktop_file_by_id(context, id)?;
+    let executable = desktop_file
+        .get_handler_exec(uri)
+        .map_err(|error| anyhow!("Failed to open '{}': {error}", uri.unwrap_or_default()))?;
+
+    run_executable(&desktop_file, executable)
+}
+
+fn run_executable(desktop_file: &DesktopFile, mut executable: String) -> Result<()> {
+    if utils::env::is_devcontainer()
+        && desktop_file
+            .get_browser()
+            .is_some_and(|browser| browser.base == Base::Chromium)
+    {
+        let _ = write!(executable, " --no-sandbox");
+    }
+
+    debug!("Running web app: '{executable}'");
+    utils::command::run_command_background(&executable)
+}
+
+fn export(context: &CliContext, ids: &[String], output: &Path, profiles: bool) -> Result<()> {
+    let desktop_files = get_owned_desktop_files(context);
+    let mut export_desktop_files = Vec::new();
+
+    if ids.is_empty() {
+        export_desktop_files = desktop_files;
+    } else {
+        for id in ids {
+            let desktop_file = desktop_files
+                .iter()
+                .find(|desktop_file| desktop_file.get_id().is_some_and(|app_id| app_id == *id))
+                .context(format!("No web app found with id: {id}"))?;
+            export_desktop_files.push(desktop_file.clone());
+        }
+    }
+
+    if export_desktop_files.is_empty() {
+        bail!("No web apps to export")
+    }
+
+    Bundle::export(&export_desktop_files, output, profiles)?;
+    println!("{}", output.display());
+
+    Ok(())
+}
+
+fn adopt(context: &CliContext, ids: &[String], url: Option<&str>) -> Result<()> {
+    let browser_apps = BrowserApp::scan(&context.browser_configs, &context.app_dirs);
+
+    if ids.is_empty() {
+        for browser_app in &browser_apps {
+            println!(
+                "{}\t{}\t{}\t{}",
+                browser_app.app_id,
+                browser_app.name,
+                browser_app.url.as_deref().unwrap_or("-"),
+                browser_app
+                    .browser
+                    .as_ref()
+                    .map(|browser| browser.get_name_with_installation())
+                    .unwrap_or("-".to_string())
+            );
+        }
+        return Ok(());
+    }
+    if url.is_some() && ids.len() > 1 {
+        bail!("A url can only be given when adopting a single app")
+    }
+
+    for id in ids {
+        let browser_app = browser_apps
+            .iter()
+            .find(|browser_app| browser_app.app_id == *id)
+            .context(format!("No browser app found with id: {id}"))?;
+
+        let desktop_file = browser_app
+            .adopt(url, &context.browser_configs, &context.app_dirs)
+            .map_err(|error| match error {
+                DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+                DesktopFileError::Other(error) => error.context("Error adopting browser app"),
+            })?;
+        println!(
+            "{}\t{}",
+            desktop_file.get_id().unwrap_or_default(),
+            desktop_file.get_name().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+fn import_from(context: &CliContext, files: &[String], browser_id: Option<&str>) -> Result<()> {
+    let foreign_apps = ForeignApp::scan(&context.browser_configs, &context.app_dirs);
+
+    if files.is_empty() {
+        for foreign_app in &foreign_apps {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                foreign_app
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy(),
+                foreign_app.name,
+                foreign_app.url,
+                foreign_app.source,
+                foreign_app
+                    .browser
+                    .as_ref()
+                    .map(|browser| browser.get_name_with_installation())
+                    .unwrap_or("-".to_string())
+            );
+        }
+        return Ok(());
+    }
+
+    let fallback_browser = match browser_id {
+        Some(browser_id) => Some(
+            context
+                .browser_configs
+                .get_by_id(browser_id)
+                .filter(|browser| browser.is_installed())
+                .context(format!("No installed browser found with id: {browser_id}"))?,
+        ),
+        None => None,
+    };
+
+    for file in files {
+        let foreign_app = foreign_apps
+            .iter()
+            .find(|foreign_app| {
+                foreign_app
+                    .path
+                    .file_name()
+                    .is_some_and(|file_name| file_name.to_string_lossy() == *file)
+            })
+            .context(format!("No web app of another manager found: {file}"))?;
+
+        let desktop_file = foreign_app
+            .import(
+                fallback_browser.as_ref(),
+                &context.browser_configs,
+                &context.app_dirs,
+            )
+            .map_err(|error| match error {
+                DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+                DesktopFileError::Other(error) => error.context("Error importing web app"),
+            })?;
+        println!(
+            "{}\t{}",
+            desktop_file.get_id().unwrap_or_default(),
+            desktop_file.get_name().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+fn cleanup(context: &CliContext, delete: bool) -> Result<()> {
+    let orphans = Orphan::scan(&context.app_dirs).context("Error scanning for orphans")?;
+
+    for orphan in &orphans {
+        println!(
+            "{}\t{}\t{}",
+            orphan.kind,
+            glib::format_size(orphan.size),
+            orphan.path.display()
+        );
+        if delete {
+            orphan.delete()?;
+        }
+    }
+
+    let total_size = orphans.iter().map(|orphan| orphan.size).sum::<u64>();
+    if delete {
+        println!(
+            "Removed {} ({})",
+            orphans.len(),
+            glib::format_size(total_size)
+        );
+    } else if !orphans.is_empty() {
+        println!(
+            "{} unused ({}), remove with: cleanup --delete",
+            orphans.len(),
+            glib::format_size(total_size)
+        );
+    }
+
+    Ok(())
+}
+
+fn import(context: &CliContext, file: &Path, browser_id: Option<&str>) -> Result<()> {
+    let browser = match browser_id {
+        Some(browser_id) => Some(
+            context
+                .browser_configs
+                .get_by_id(browser_id)
+                .filter(|browser| browser.is_installed())
+                .context(format!("No installed browser found with id: {browser_id}"))?,
+        ),
+        None => None,
+    };
+    let existing_ids = get_owned_desktop_files(context)
+        .iter()
+        .filter_map(DesktopFile::get_id)
+        .collect::<HashSet<_>>();
+
+    let imported = Bundle::import(
+        file,
+        browser.as_ref(),
+        &existing_ids,
+        &context.browser_configs,
+        &context.app_dirs,
+    )?;
+
+    for desktop_file in &imported {
+        println!(
+            "{}\t{}",
+            desktop_file.get_id().unwrap_or_default(),
+            desktop_file.get_name().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+fn migrate(context: &CliContext, dry_run: bool) -> Result<()> {
+    let mut is_error = false;
+
+    // Read them as they are, the loading of the other commands already migrates
+    for path in DesktopFile::get_owned_paths(&context.app_dirs) {
+        let mut desktop_file =
+            match DesktopFile::from_path(&path, &context.browser_configs, &context.app_dirs) {
+                Ok(desktop_file) => desktop_file,
+                Err(error) => {
+                    error!("Failed to read '{}': {error:?}", path.display());
+                    is_error = true;
+                    continue;
+                }
+            };
+        let id = desktop_file.get_id().unwrap_or_default();
+        let report = match desktop_file.migrate(dry_run) {
+            Ok(report) => report,
+            Err(error) => {
+                error!("Failed to migrate web app '{id}': {error}");
+                is_error = true;
+                continue;
+            }
+        };
+        if !report.is_update() {
+            continue;
+        }
+
+        println!(
+            "{id}\t{}\t{} -> {}\t{}",
+            desktop_file.get_name().unwrap_or_default(),
+            report.from,
+            report.to,
+            report.migrations.join(", ")
+        );
+    }
+
+    if is_error {
+        bail!("Not all web apps could be migrated")
+    }
+
+    Ok(())
+}
+
+fn browsers(context: &CliContext, json: bool) -> Result<()> {
+    let browsers = context
+        .browser_configs
+        .get_all_browsers()
+        .iter()
+        .filter(|browser| browser.is_installed())
+        .map(|browser| BrowserJson {
+            id: browser.id.clone(),
+            name: browser.get_name_with_installation(),
+            can_isolate: browser.can_isolate,
+            can_start_maximized: browser.can_start_maximized,
+            flags: browser.flags.iter().map(|flag| flag.name.clone()).collect(),
+            proxy_modes: browser.proxy_modes.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&browsers)?);
+        return Ok(());
+    }
+
+    for browser in browsers {
+        println!(
+            "{}\t{}\t{}",
+            browser.id,
+            browser.name,
+            browser.flags.join(";")
+        );
+    }
+
+    Ok(())
+}
+
+fn get_owned_desktop_files(context: &CliContext) -> Vec<DesktopFile> {
+    DesktopFile::get_owned(
+        &context.browser_configs,
+        &context.app_dirs,
+        &context.user_settings.settings.fallback_browsers,
+    )
+    .into_iter()
+    .map(|owned| {
+        if let Some(browser) = &owned.fallback_browser {
+            notify_browser_fallback(&owned.desktop_file, browser);
+        }
+        owned.desktop_file
+    })
+    .collect()
+}
+
+fn fallback(mut context: CliContext, browsers: Option<&str>) -> Result<()> {
+    if let Some(browsers) = browsers {
+        let browser_ids = parse_browser_ids(&context, browsers)?;
+        context.user_settings.set_fallback_browsers(&browser_ids);
+        context.user_settings.save()?;
+    }
+
+    for browser_id in &context.user_settings.settings.fallback_browsers {
+        let name = context
+            .browser_configs
+            .get_by_id(browser_id)
+            .map_or("Not installed".to_string(), |browser| {
+                browser.get_name_with_installation()
+            });
+        println!("{browser_id}\t{name}");
+    }
+
+    Ok(())
+}
+
+/// Tells about the switch to a fallback browser on the desktop as well, because a launch often
+/// does not come from a terminal
+fn notify_browser_fallback(desktop_file: &DesktopFile, browser: &Browser) {
+    let message = format!(
+        "{} now opens in {} because its browser is no longer installed",
+        desktop_file.get_name().unwrap_or_default(),
+        browser.get_name_with_installation()
+    );
+    eprintln!("{message}");
+
+    let command = format!(
+        "notify-send {} {}",
+        utils::command::quote_arg("Web app switched browser"),
+        utils::command::quote_arg(&message)
+    );
+    if let Err(error) = utils::command::run_command_background(&command) {
+        debug!("Failed to send notification: {error:?}");
+    }
+}
+
+fn get_desktop_file_by_id(context: &CliContext, id: &str) -> Result<DesktopFile> {
+    get_owned_desktop_files(context)
+        .into_iter()
+        .find(|desktop_file| desktop_file.get_id().is_some_and(|app_id| app_id == id))
+        .context(format!("No web app found with id: {id}"))
+}
+
+fn set_browser(
+    context: &CliContext,
+    desktop_file: &mut DesktopFile,
+    browser_id: &str,
+) -> Result<()> {
+    let browser = context
+        .browser_configs
+        .get_by_id(browser_id)
+        .filter(|browser| browser.is_installed())
+        .context(format!("No installed browser found with id: {browser_id}"))?;
+
+    desktop_file.set_browser(&browser);
+    Ok(())
+}
+
+fn set_flags(desktop_file: &mut DesktopFile, flags: &str) -> Result<()> {
+    let browser = desktop_file
+        .get_browser()
+        .context("Set a browser before setting options")?;
+    let flags = parse_list(flags);
+
+    if let Some(unknown_flag) = flags
+        .iter()
+        .find(|name| !browser.flags.iter().any(|flag| flag.name == **name))
+    {
+        bail!(
+            "Unknown option '{unknown_flag}' for browser: {}",
+            browser.get_name()
+        )
+    }
+
+    desktop_file.set_enabled_flags(&flags);
+    Ok(())
+}
+
+/// Empty is the browser default
+fn parse_proxy_mode(proxy_mode: &str) -> Result<Option<ProxyMode>> {
+    let proxy_mode = proxy_mode.trim();
+    if proxy_mode.is_empty() {
+        return Ok(None);
+    }
+
+    ProxyMode::from_string(proxy_mode).map(Some).ok_or_else(|| {
+        anyhow!("Unknown proxy mode '{proxy_mode}', expected 'none', 'system', 'http' or 'socks'")
+    })
+}
+
+fn set_extra_args(desktop_file: &mut DesktopFile, extra_args: &str) -> Result<()> {
+    let extra_args = DesktopFile::parse_extra_args(extra_args)
+        .map_err(|error| anyhow!("Invalid input: {error}"))?;
+
+    desktop_file
+        .set_extra_args(&extra_args)
+        .map_err(|error| match error {
+            DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+            DesktopFileError::Other(error) => error.context("Error setting extra arguments"),
+        })?;
+
+    let conflicting_args = DesktopFile::get_conflicting_args(&extra_args);
+    if !conflicting_args.is_empty() {
+        eprintln!(
+            "Warning: conflicts with arguments of the browser template: {}",
+            conflicting_args.join(" ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Same behavior as changing the isolate switch in the app
+fn set_isolated(
+    desktop_file: &mut DesktopFile,
+    is_isolated: bool,
+    migration: ProfileMigration,
+    transaction: &mut Transaction,
+) -> Result<()> {
+    desktop_file.set_isolated(is_isolated);
+    desktop_file.update_profile_in(is_isolated, migration, transaction)
+}
+
+/// How the profile follows a browser change, a profile is never lost without asking
+fn get_profile_migration(
+    context: &CliContext,
+    desktop_file: &DesktopFile,
+    browser_id: &str,
+    migrate_profile: Option<&str>,
+) -> Result<ProfileMigration> {
+    let migration = match migrate_profile {
+        None => None,
+        Some("move") => Some(ProfileMigration::Move),
+        Some("copy") => Some(ProfileMigration::Copy),
+        Some("discard") => Some(ProfileMigration::Discard),
+        Some(other) => {
+            bail!("Unknown profile migration '{other}', use 'move', 'copy' or 'discard'")
+        }
+    };
+
+    let Some(browser) = context.browser_configs.get_by_id(browser_id) else {
+        return Ok(ProfileMigration::Discard);
+    };
+    let is_same_browser = desktop_file
+        .get_browser()
+        .is_some_and(|current_browser| current_browser.id == browser.id);
+    if !desktop_file.has_profile() {
+        return Ok(ProfileMigration::Discard);
+    }
+    // Only follows a new profile location
+    if is_same_browser {
+        return Ok(ProfileMigration::Move);
+    }
+
+    if desktop_file.can_migrate_profile_to(&browser) {
+        return Ok(migration.unwrap_or(ProfileMigration::Move));
+    }
+    match migration {
+        Some(ProfileMigration::Discard) => Ok(ProfileMigration::Discard),
+        _ => bail!(
+            "The profile cannot be carried over to a browser with another engine, logins and site data are lost. Use '--migrate-profile discard' to continue"
+        ),
+    }
+}
+
+/// Height to render vector icons at, as the icon picker does
+const ICON_SCALE_HEIGHT: i32 = 512;
+
+fn set_icon(
+    context: &CliContext,
+    desktop_file: &mut DesktopFile,
+    icon: &str,
+    transaction: &mut Transaction,
+) -> Result<()> {
+    let icon_path = Path::new(icon);
+
+    if !icon_path.is_file() {
+        debug!("Using icon name from icon theme: {icon}");
+        desktop_file.set_icon_path(icon_path);
+        return Ok(());
+    }
+
+    // Like the icon picker, scale vector images and store every icon as png
+    let is_scalable = Pixbuf::file_info(icon_path)
+        .is_some_and(|(pixbuf_format, _, _)| pixbuf_format.is_scalable());
+    let pixbuf = if is_scalable {
+        Pixbuf::from_file_at_scale(icon_path, -1, ICON_SCALE_HEIGHT, true)
+    } else {
+        Pixbuf::from_file(icon_path)
+    }
+    .map_err(|error| anyhow!("Could not load image into a Pixbuf: '{error:?}'"))?;
+
+    let app_id = desktop_file.get_id().context("No file id on DesktopFile")?;
+    let file_name = sanitize_filename::sanitize(format!("{app_id}.png"));
+    let save_path = context.app_dirs.app_data_icons.join(&file_name);
+
+    debug!(
+        "Saving icon '{}' to fs: {}",
+        &file_name,
+        save_path.display()
+    );
+    transaction
+        .write_with(&save_path, |temp_path| {
+            pixbuf.savev(temp_path, "png", &[]).map_err(Into::into)
+        })
+        .context("Failed to save icon to fs")?;
+
+    if let Some(old_icon_path) = desktop_file.get_icon_path()
+        && old_icon_path != save_path
+        && old_icon_path.is_file()
+        && old_icon_path.starts_with(&context.app_dirs.app_data_icons)
+    {
+        transaction
+            .remove(&old_icon_path)
+            .context("Failed to remove old icon")?;
+    }
+
+    desktop_file.set_icon_path(&save_path);
+    Ok(())
+}
+
+/// The saved desktop file, to use once the transaction is committed
+fn save(desktop_file: &DesktopFile, transaction: &mut Transaction) -> Result<DesktopFile> {
+    desktop_file
+        .save_in(transaction)
+        .map_err(|error| match error {
+            DesktopFileError::ValidationError(error) => anyhow!("Invalid input: {error}"),
+            DesktopFileError::Other(error) => error.context("Error saving web app"),
+        })
+}
+
+/// A main category first, followed by more main or additional categories
+fn parse_categories(categories: &str) -> Result<Vec<String>> {
+    let mut names = parse_list(categories).into_iter();
+    let main_category = names.next().context("No category given")?;
+    let main_category = Category::from_name(&main_category)
+        .context(format!("Unknown category: {main_category}"))?;
+
+    let mut parsed_categories = main_category
+        .to_string()
+        .split(';')
+        .map(std::string::ToString::to_string)
+        .collect::<Vec<_>>();
+    for name in names {
+        let registered_name =
+            Category::get_registered_name(&name).context(format!("Unknown category: {name}"))?;
+        parsed_categories.push(registered_name);
+    }
+
+    Ok(parsed_categories)
+}
+
+/// Known browser ids, also of browsers that are not installed
+fn parse_browser_ids(context: &CliContext, browser_ids: &str) -> Result<Vec<String>> {
+    let browser_ids = parse_list(browser_ids);
+
+    if let Some(unknown_id) = browser_ids
+        .iter()
+        .find(|id| context.browser_configs.get_base_by_id(id).is_none())
+    {
+        bail!("Unknown browser id '{unknown_id}', see the `browsers` command")
+    }
+
+    Ok(browser_ids)
+}
+
+fn parse_list(list: &str) -> Vec<String> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(std::string::ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{
+        app_dirs::BaseDirs,
+        browsers::{Installation, StaticDetector},
+        config,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, CliContext) {
+        config::init();
+        let home = tempfile::tempdir().unwrap();
+        let app_dirs = AppDirs::from_base_dirs(BaseDirs::from_home(home.path())).unwrap();
+        assets::init(&app_dirs).unwrap();
+        let browser_configs = BrowserConfigs::new_with_detector(
+            &app_dirs,
+            Box::new(StaticDetector {
+                installations: vec![Installation::System("firefox".to_string())],
+            }),
+        );
+        browser_configs.init();
+        let user_settings = UserSettings::new(&app_dirs);
+
+        let context = CliContext {
+            app_dirs,
+            browser_configs,
+            user_settings,
+        };
+        (home, context)
+    }
+
+    fn parse(args: &[&str]) -> Command {
+        Cli::try_parse_from(std::iter::once("web-app-hub").chain(args.iter().copied()))
+            .unwrap()
+            .command
+            .unwrap()
+    }
+
+    #[test]
+    fn rename_keeps_moved_profile() {
+        let (home, context) = setup();
+        let Command::Create(args) = parse(&[
+            "create",
+            "--name=Example",
+            "--url=https://example.com",
+            "--browser=firefox",
+            "--icon=web-browser",
+            "--isolate",
+        ]) else {
+            unreachable!()
+        };
+        create(&context, args).unwrap();
+
+        // A profile of before a change of the profile location
+        let mut desktop_file = get_owned_desktop_files(&context).remove(0);
+        let id = desktop_file.get_id().unwrap();
+        let old_profile_path = home.path().join("old-profile");
+        fs::rename(desktop_file.get_profile_path().unwrap(), &old_profile_path).unwrap();
+        desktop_file.set_profile_path(&old_profile_path);
+        desktop_file.save().unwrap();
+
+        let Command::Edit(args) = parse(&["edit", &id, "--name=Renamed"]) else {
+            unreachable!()
+        };
+        edit(&context, args).unwrap();
+
+        let desktop_file = get_desktop_file_by_id(&context, &id).unwrap();
+        assert_eq!(desktop_file.get_name().as_deref(), Some("Renamed"));
+        assert_eq!(
+            desktop_file.get_profile_path(),
+            Some(old_profile_path.clone())
+        );
+        assert!(old_profile_path.is_dir());
+    }
+}
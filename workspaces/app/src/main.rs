@@ -1,6 +1,9 @@
 mod application;
+mod cli;
 
 use application::App;
+use clap::Parser;
+use cli::Cli;
 use common::{
     config::{self},
     utils::{self, OnceLockExt},
@@ -14,7 +17,7 @@ use tracing_subscriber::{FmtSubscriber, util::SubscriberInitExt};
 extern crate rust_i18n;
 i18n!("translations", fallback = "en");
 
-fn init_logging() {
+fn init_logging(is_cli: bool) {
     let mut log_level = if cfg!(debug_assertions) {
         Level::DEBUG
     } else {
@@ -29,9 +32,14 @@ fn init_logging() {
 
     let logger = FmtSubscriber::builder()
         .with_max_level(Level::INFO)
-        .with_env_filter(filter)
-        .finish();
-    logger.init();
+        .with_env_filter(filter);
+
+    // Keep stdout clean for cli output
+    if is_cli {
+        logger.with_writer(std::io::stderr).finish().init();
+    } else {
+        logger.finish().init();
+    }
 }
 
 fn init_locale() {
@@ -71,16 +79,25 @@ fn init_locale() {
 
 fn main() {
     if cfg!(debug_assertions) {
-        println!("======== Running debug build ========");
+        eprintln!("======== Running debug build ========");
     }
 
     config::init();
-    init_logging();
+    let cli = Cli::parse();
+    init_logging(cli.command.is_some());
     info!("Version: {}", config::VERSION.get_value());
     init_locale();
 
     config::log_all_values_debug();
 
+    if let Some(command) = cli.command {
+        if let Err(error) = cli::run(command) {
+            eprintln!("Error: {error:?}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let adw_application = libadwaita::Application::builder()
         .application_id(config::APP_ID.get_value())
         .build();
@@ -1,9 +1,8 @@
 use crate::{
     config::{self},
-    utils::OnceLockExt,
+    utils::{OnceLockExt, env},
 };
 use anyhow::{Context, Result};
-use gtk::glib;
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -11,6 +10,38 @@ use std::{
 };
 use tracing::debug;
 
+/// The base dirs all app dirs are derived from
+pub struct BaseDirs {
+    pub home: PathBuf,
+    pub data: PathBuf,
+    pub config: PathBuf,
+    pub cache: PathBuf,
+    pub system_data: Vec<PathBuf>,
+}
+impl BaseDirs {
+    /// The XDG dirs of the current user
+    pub fn from_env() -> Self {
+        Self {
+            home: env::get_home_dir(),
+            data: env::get_xdg_dir("XDG_DATA_HOME", ".local/share"),
+            config: env::get_xdg_dir("XDG_CONFIG_HOME", ".config"),
+            cache: env::get_xdg_dir("XDG_CACHE_HOME", ".cache"),
+            system_data: env::get_xdg_data_dirs(),
+        }
+    }
+
+    /// The default XDG layout inside a home dir, e.g. a temporary dir
+    pub fn from_home(home: &Path) -> Self {
+        Self {
+            home: home.to_path_buf(),
+            data: home.join(".local").join("share"),
+            config: home.join(".config"),
+            cache: home.join(".cache"),
+            system_data: Vec::new(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct AppDirs {
     pub user_home: PathBuf,
@@ -31,16 +62,21 @@ pub struct AppDirs {
 }
 impl AppDirs {
     pub fn new() -> Result<Rc<Self>> {
-        Rc::new(Self::default());
+        Self::from_base_dirs(BaseDirs::from_env())
+    }
 
-        let user_home = glib::home_dir();
-        let user_data = glib::user_data_dir();
-        let user_config = glib::user_config_dir();
-        let user_cache = glib::user_cache_dir();
+    /// App dirs on explicit paths instead of the dirs of the current user
+    pub fn from_base_dirs(base_dirs: BaseDirs) -> Result<Rc<Self>> {
+        let BaseDirs {
+            home: user_home,
+            data: user_data,
+            config: user_config,
+            cache: user_cache,
+            system_data,
+        } = base_dirs;
         let user_applications = Self::build_applications_path(&user_data)?;
         let user_flatpak = Self::build_flatpak_path(&user_home);
 
-        let system_data = glib::system_data_dirs();
         let system_icons = Self::build_system_icon_paths(&system_data);
 
         let app_data = user_data.join(config::APP_NAME_HYPHEN.get_value());
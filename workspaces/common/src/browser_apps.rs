@@ -0,0 +1,211 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::{Browser, BrowserConfigs},
+    desktop_file::{DesktopFile, error::DesktopFileError, exec},
+    transaction::Transaction,
+    utils,
+};
+use anyhow::{Context, Result};
+use freedesktop_desktop_entry::DesktopEntry;
+use regex::Regex;
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{debug, error, info};
+
+/// A web app created by a browser itself, like "Install app" or "Create shortcut" of Chromium
+/// based browsers. These write `chrome-<app_id>-<profile>.desktop` files to the user applications.
+pub struct BrowserApp {
+    pub path: PathBuf,
+    pub name: String,
+    /// Id of the app in the browser, launched with `--app-id`
+    pub app_id: String,
+    /// Only known for shortcuts that launch a url with `--app`
+    pub url: Option<String>,
+    /// `None` when the browser is not installed or has no config
+    pub browser: Option<Rc<Browser>>,
+    pub profile_directory: Option<String>,
+    pub icon_path: Option<PathBuf>,
+    pub wm_class: Option<String>,
+}
+impl BrowserApp {
+    const FILE_NAME_PATTERN: &str = r"^(?:.+\.)?[a-z-]+-([a-p]{32})-[^.]+\.desktop$";
+
+    /// All browser apps in the user applications
+    pub fn scan(browser_configs: &Rc<BrowserConfigs>, app_dirs: &Rc<AppDirs>) -> Vec<Self> {
+        debug!("Scanning for browser apps");
+
+        let Ok(file_name_regex) = Regex::new(Self::FILE_NAME_PATTERN) else {
+            error!("Invalid browser app file name pattern");
+            return Vec::new();
+        };
+        let mut browser_apps = Vec::new();
+
+        for file in
+            utils::files::get_entries_in_dir(&app_dirs.user_applications).unwrap_or_default()
+        {
+            let path = file.path();
+            let file_name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let Some(captures) = file_name_regex.captures(&file_name) else {
+                continue;
+            };
+            let app_id = captures[1].to_string();
+
+            match Self::from_path(&path, &app_id, browser_configs, app_dirs) {
+                Ok(Some(browser_app)) => browser_apps.push(browser_app),
+                Ok(None) => {}
+                Err(error) => {
+                    error!(path = %path.display(), "Failed to read browser app: {error:?}");
+                }
+            }
+        }
+
+        browser_apps.sort_by_key(|browser_app| browser_app.name.to_lowercase());
+        browser_apps
+    }
+
+    fn from_path(
+        path: &Path,
+        app_id: &str,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<Option<Self>> {
+        if DesktopFile::is_owned(path)? {
+            return Ok(None);
+        }
+
+        let desktop_entry = DesktopEntry::from_path(path, None::<&[String]>)?;
+        let Some(exec) = desktop_entry.desktop_entry("Exec") else {
+            return Ok(None);
+        };
+        let args = exec::parse(exec)?;
+
+        let get_option = |name: &str| {
+            let prefix = format!("--{name}=");
+            args.iter()
+                .find_map(|arg| arg.strip_prefix(&prefix))
+                .map(str::to_string)
+        };
+        let url = get_option("app");
+        let app_id = get_option("app-id").unwrap_or(app_id.to_string());
+        // Not an app window, e.g. a plain link to a site
+        if url.is_none() && !args.iter().any(|arg| arg.starts_with("--app-id=")) {
+            return Ok(None);
+        }
+
+        let name = desktop_entry
+            .desktop_entry("Name")
+            .unwrap_or_default()
+            .to_string();
+        let icon_path = desktop_entry
+            .desktop_entry("Icon")
+            .and_then(|icon| Self::find_icon(icon, app_dirs));
+        let wm_class = desktop_entry
+            .desktop_entry("StartupWMClass")
+            .filter(|wm_class| !wm_class.is_empty())
+            .map(str::to_string);
+
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            name,
+            app_id,
+            url,
+            browser: browser_configs.get_by_run_args(&args),
+            profile_directory: get_option("profile-directory"),
+            icon_path,
+            wm_class,
+        }))
+    }
+
+    /// Largest icon of the name in the user icon theme, or the icon path itself
+    pub(crate) fn find_icon(icon: &str, app_dirs: &AppDirs) -> Option<PathBuf> {
+        let icon_path = Path::new(icon);
+        if icon_path.is_absolute() {
+            return icon_path.is_file().then(|| icon_path.to_path_buf());
+        }
+
+        let theme_dir = app_dirs.user_data.join("icons").join("hicolor");
+        let mut sizes = utils::files::get_entries_in_dir(&theme_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|entry| {
+                let dir_name = entry.file_name().to_string_lossy().to_string();
+                let size = dir_name.split('x').next()?.parse::<u32>().ok()?;
+                Some((size, entry.path()))
+            })
+            .collect::<Vec<_>>();
+        sizes.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+
+        sizes
+            .into_iter()
+            .map(|(_, dir)| dir.join("apps").join(format!("{icon}.png")))
+            .chain([theme_dir
+                .join("scalable")
+                .join("apps")
+                .join(format!("{icon}.svg"))])
+            .find(|path| path.is_file())
+    }
+
+    /// Convert into a web app of this app, replacing the desktop file of the browser.
+    /// `url` is needed when the browser app has no url.
+    pub fn adopt(
+        &self,
+        url: Option<&str>,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<DesktopFile, DesktopFileError> {
+        let browser = self
+            .browser
+            .clone()
+            .context(format!("No installed browser for: {}", self.name))?;
+        let url = url
+            .or(self.url.as_deref())
+            .context(format!("No url for: {}", self.name))?;
+        let icon_path = self
+            .icon_path
+            .as_ref()
+            .context(format!("No icon found for: {}", self.name))?;
+
+        let mut desktop_file = DesktopFile::new(browser_configs, app_dirs);
+        let id = desktop_file.get_id().unwrap_or_default();
+
+        desktop_file.set_name(&self.name);
+        desktop_file.set_url(url);
+        desktop_file.set_browser(&browser);
+        // The app lives in the profile of the browser
+        desktop_file.set_isolated(false);
+        desktop_file.set_maximized(false);
+        if self.url.is_none() {
+            desktop_file.set_browser_app_id(&self.app_id);
+        }
+        if let Some(profile_directory) = &self.profile_directory {
+            desktop_file.set_profile_directory(profile_directory);
+        }
+        if let Some(wm_class) = &self.wm_class {
+            desktop_file.set_wm_class(wm_class);
+        }
+
+        // Icon, desktop file and removal of the original are rolled back together
+        let mut transaction = Transaction::new();
+        desktop_file.copy_icon_in(icon_path, &mut transaction)?;
+
+        let desktop_file = desktop_file.save_in(&mut transaction)?;
+        transaction
+            .remove(&self.path)
+            .context("Failed to remove desktop file of the browser")?;
+        transaction.commit();
+
+        info!(
+            name = self.name,
+            id,
+            browser_app_id = self.app_id,
+            "Adopted browser app"
+        );
+        Ok(desktop_file)
+    }
+}
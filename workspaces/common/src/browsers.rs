@@ -2,17 +2,15 @@ use crate::utils::{self, OnceLockExt};
 use crate::{
     app_dirs::AppDirs,
     config::{self},
+    desktop_file::{DesktopFile, template::Template},
 };
 use anyhow::{Context, Result, bail};
 use freedesktop_desktop_entry::DesktopEntry;
+#[cfg(feature = "gtk")]
 use gtk::{IconTheme, Image};
-use std::{
-    cell::OnceCell,
-    collections::{HashMap, HashSet},
-    fs,
-    path::Path,
-    rc::Rc,
-};
+#[cfg(feature = "gtk")]
+use std::collections::HashSet;
+use std::{cell::RefCell, collections::HashMap, fmt::Display, fs, path::Path, rc::Rc};
 use std::{fmt::Write as _, path::PathBuf};
 use tracing::{debug, error, info};
 
@@ -23,7 +21,59 @@ pub enum Installation {
     None,
 }
 
-#[derive(PartialEq)]
+/// Decides if a browser is installed, replace it to detect browsers without running commands
+pub trait InstallationDetector {
+    fn is_installed_flatpak(&self, flatpak: &str) -> bool;
+    fn is_installed_system(&self, system_bin: &str) -> bool;
+}
+
+/// Asks `flatpak` and `which` on the host
+pub struct CommandDetector;
+impl InstallationDetector for CommandDetector {
+    fn is_installed_flatpak(&self, flatpak: &str) -> bool {
+        let command = format!("flatpak info {flatpak}");
+        let result = utils::command::run_command_sync(&command);
+
+        match result {
+            Err(error) => {
+                error!("Could not run command '{command}'. Error: {error:?}");
+                false
+            }
+            Ok(response) => response.success,
+        }
+    }
+
+    fn is_installed_system(&self, system_bin: &str) -> bool {
+        let command = format!("which {system_bin}");
+        let result = utils::command::run_command_sync(&command);
+
+        match result {
+            Err(error) => {
+                error!("Could not run command '{command}'. Error: {error:?}");
+                false
+            }
+            Ok(response) => response.success,
+        }
+    }
+}
+
+/// A fixed set of installed browsers, e.g. for tests
+pub struct StaticDetector {
+    pub installations: Vec<Installation>,
+}
+impl InstallationDetector for StaticDetector {
+    fn is_installed_flatpak(&self, flatpak: &str) -> bool {
+        self.installations
+            .contains(&Installation::Flatpak(flatpak.to_string()))
+    }
+
+    fn is_installed_system(&self, system_bin: &str) -> bool {
+        self.installations
+            .contains(&Installation::System(system_bin.to_string()))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum Base {
     Chromium,
     Firefox,
@@ -38,6 +88,55 @@ impl Base {
         }
     }
 }
+impl Display for Base {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Chromium => write!(f, "chromium"),
+            Self::Firefox => write!(f, "firefox"),
+            Self::None => write!(f, "none"),
+        }
+    }
+}
+
+/// How a web app connects, the browser config declares the modes its browser supports
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyMode {
+    /// A direct connection, also when the system has a proxy
+    None,
+    /// The proxy settings of the system
+    System,
+    /// A manual HTTP proxy
+    Http,
+    /// A manual SOCKS proxy
+    Socks,
+}
+impl ProxyMode {
+    pub fn from_string(string: &str) -> Option<Self> {
+        match string {
+            "none" => Some(Self::None),
+            "system" => Some(Self::System),
+            "http" => Some(Self::Http),
+            "socks" => Some(Self::Socks),
+            _ => None,
+        }
+    }
+
+    /// Needs a server and can have a bypass list
+    pub fn is_manual(self) -> bool {
+        matches!(self, Self::Http | Self::Socks)
+    }
+}
+impl Display for ProxyMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::System => write!(f, "system"),
+            Self::Http => write!(f, "http"),
+            Self::Socks => write!(f, "socks"),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct BrowserYaml {
@@ -52,6 +151,30 @@ pub struct BrowserYaml {
     base: String,
     #[serde(default)]
     issues: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    flags: Vec<BrowserFlag>,
+    #[serde(default)]
+    proxy_modes: Vec<ProxyMode>,
+}
+
+/// A per-app option declared by a browser config, a condition in the desktop file template
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BrowserFlag {
+    pub name: String,
+    /// Label per language
+    #[serde(default)]
+    pub label: HashMap<String, String>,
+    #[serde(default)]
+    pub default: bool,
+}
+impl BrowserFlag {
+    pub fn get_label(&self, locale: &str, default_locale: &str) -> String {
+        self.label
+            .get(locale)
+            .or(self.label.get(default_locale))
+            .cloned()
+            .unwrap_or_else(|| self.name.clone())
+    }
 }
 
 struct BrowserConfig {
@@ -73,22 +196,27 @@ pub struct Browser {
     pub desktop_file_name_prefix: String,
     pub base: Base,
     pub issues: HashMap<String, Vec<String>>,
+    pub flags: Vec<BrowserFlag>,
+    pub proxy_modes: Vec<ProxyMode>,
     pub config_name: String,
     configs: Rc<BrowserConfigs>,
-    icon_theme: Rc<IconTheme>,
+    #[cfg(feature = "gtk")]
+    icon_theme: Option<Rc<IconTheme>>,
+    #[cfg(feature = "gtk")]
     icon_names: HashSet<String>,
     app_dirs: Rc<AppDirs>,
 }
 impl Browser {
+    #[cfg(feature = "gtk")]
     const FALLBACK_IMAGE: &str = "web-browser-symbolic";
 
     fn new(
         browser_config: &BrowserConfig,
         installation: Installation,
         browser_configs: &Rc<BrowserConfigs>,
-        icon_theme: &Rc<IconTheme>,
         app_dirs: &Rc<AppDirs>,
     ) -> Self {
+        #[cfg(feature = "gtk")]
         let icon_names = Self::get_icon_names_from_config(browser_config);
         let name = browser_config.config.name.clone();
         let can_isolate = browser_config.config.can_isolate;
@@ -100,6 +228,8 @@ impl Browser {
         let config_name = browser_config.config_name.clone();
         let base = Base::from_string(&browser_config.config.base);
         let issues = browser_config.config.issues.clone();
+        let flags = browser_config.config.flags.clone();
+        let proxy_modes = browser_config.config.proxy_modes.clone();
 
         let id = match &installation {
             Installation::Flatpak(id) => id.clone(),
@@ -119,10 +249,14 @@ impl Browser {
             desktop_file_name_prefix,
             config_name,
             configs: browser_configs.clone(),
+            #[cfg(feature = "gtk")]
             icon_names,
             base,
             issues,
-            icon_theme: icon_theme.clone(),
+            flags,
+            proxy_modes,
+            #[cfg(feature = "gtk")]
+            icon_theme: browser_configs.icon_theme.clone(),
             app_dirs: app_dirs.clone(),
         }
     }
@@ -164,17 +298,50 @@ impl Browser {
         txt
     }
 
-    pub fn get_run_command(&self) -> Result<String> {
+    /// The command as separate arguments, for an Exec
+    pub fn get_run_args(&self) -> Result<Vec<String>> {
         match &self.installation {
-            Installation::Flatpak(id) => Ok(format!("flatpak run {id}")),
-            Installation::System(executable) => Ok(executable.clone()),
+            Installation::Flatpak(id) => {
+                Ok(vec!["flatpak".to_string(), "run".to_string(), id.clone()])
+            }
+            Installation::System(executable) => Ok(vec![executable.clone()]),
             Installation::None => bail!("Browser is not installed"),
         }
     }
 
+    /// The command with environment variables, `flatpak run` has to pass them into the
+    /// sandbox itself, otherwise they are set with `env`
+    pub fn get_run_args_with_env(&self, env_vars: &[(String, String)]) -> Result<Vec<String>> {
+        let run_args = self.get_run_args()?;
+        if env_vars.is_empty() {
+            return Ok(run_args);
+        }
+        let assignments = env_vars
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"));
+
+        match &self.installation {
+            Installation::Flatpak(id) => Ok(["flatpak".to_string(), "run".to_string()]
+                .into_iter()
+                .chain(assignments.map(|assignment| format!("--env={assignment}")))
+                .chain([id.clone()])
+                .collect()),
+            _ => Ok(["env".to_string()]
+                .into_iter()
+                .chain(assignments)
+                .chain(run_args)
+                .collect()),
+        }
+    }
+
+    #[cfg(feature = "gtk")]
     pub fn get_icon(&self) -> Image {
+        let Some(icon_theme) = &self.icon_theme else {
+            return Image::from_icon_name(Self::FALLBACK_IMAGE);
+        };
+
         for icon in &self.icon_names {
-            if !self.icon_theme.has_icon(icon) {
+            if !icon_theme.has_icon(icon) {
                 continue;
             }
             let image = Image::from_icon_name(icon);
@@ -239,6 +406,7 @@ impl Browser {
         self.configs.get_index(self)
     }
 
+    #[cfg(feature = "gtk")]
     fn get_icon_names_from_config(browser_config: &BrowserConfig) -> HashSet<String> {
         let mut icon_names = HashSet::new();
 
@@ -257,29 +425,64 @@ impl Browser {
 }
 
 pub struct BrowserConfigs {
-    all_browsers: OnceCell<Vec<Rc<Browser>>>,
-    uninstalled_browsers: OnceCell<Vec<Rc<Browser>>>,
-    icon_theme: Rc<IconTheme>,
+    all_browsers: RefCell<Option<Vec<Rc<Browser>>>>,
+    uninstalled_browsers: RefCell<Option<Vec<Rc<Browser>>>>,
+    #[cfg(feature = "gtk")]
+    icon_theme: Option<Rc<IconTheme>>,
     app_dirs: Rc<AppDirs>,
+    detector: Box<dyn InstallationDetector>,
 }
 impl BrowserConfigs {
     pub const NO_BROWSER_NAME: &str = "No browser";
 
+    #[cfg(feature = "gtk")]
     pub fn new(icon_theme: &Rc<IconTheme>, app_dirs: &Rc<AppDirs>) -> Rc<Self> {
         Rc::new(Self {
-            all_browsers: OnceCell::new(),
-            uninstalled_browsers: OnceCell::new(),
-            icon_theme: icon_theme.clone(),
+            all_browsers: RefCell::new(None),
+            uninstalled_browsers: RefCell::new(None),
+            icon_theme: Some(icon_theme.clone()),
             app_dirs: app_dirs.clone(),
+            detector: Box::new(CommandDetector),
         })
     }
 
+    /// Browser configs without an icon theme, for use without a display (e.g. the cli)
+    pub fn new_headless(app_dirs: &Rc<AppDirs>) -> Rc<Self> {
+        Rc::new(Self {
+            all_browsers: RefCell::new(None),
+            uninstalled_browsers: RefCell::new(None),
+            #[cfg(feature = "gtk")]
+            icon_theme: None,
+            app_dirs: app_dirs.clone(),
+            detector: Box::new(CommandDetector),
+        })
+    }
+
+    /// Headless browser configs that don't run commands to find installed browsers
+    pub fn new_with_detector(
+        app_dirs: &Rc<AppDirs>,
+        detector: Box<dyn InstallationDetector>,
+    ) -> Rc<Self> {
+        Rc::new(Self {
+            all_browsers: RefCell::new(None),
+            uninstalled_browsers: RefCell::new(None),
+            #[cfg(feature = "gtk")]
+            icon_theme: None,
+            app_dirs: app_dirs.clone(),
+            detector,
+        })
+    }
+
+    /// Also re-reads the configs when already initialized (e.g. after a config file changed)
     pub fn init(self: &Rc<Self>) {
         self.set_browsers_from_files();
     }
 
-    pub fn get_all_browsers(&self) -> &Vec<Rc<Browser>> {
-        self.all_browsers.get().expect("Browsers are uninitialized")
+    pub fn get_all_browsers(&self) -> Vec<Rc<Browser>> {
+        self.all_browsers
+            .borrow()
+            .clone()
+            .expect("Browsers are uninitialized")
     }
 
     pub fn get_flatpak_browsers(&self) -> Vec<Rc<Browser>> {
@@ -302,9 +505,9 @@ impl BrowserConfigs {
 
     pub fn get_uninstalled_browsers(&self) -> Vec<Rc<Browser>> {
         self.uninstalled_browsers
-            .get()
-            .expect("Uninstalled browsers are uninitialized")
+            .borrow()
             .clone()
+            .expect("Uninstalled browsers are uninitialized")
     }
 
     pub fn get_by_id(&self, id: &str) -> Option<Rc<Browser>> {
@@ -314,6 +517,60 @@ impl BrowserConfigs {
             .cloned()
     }
 
+    /// Engine of a browser id, also when that browser is not installed anymore
+    pub fn get_base_by_id(&self, id: &str) -> Option<Base> {
+        self.get_all_browsers()
+            .iter()
+            .chain(self.uninstalled_browsers.borrow().iter().flatten())
+            .find(|browser| {
+                browser.id == id
+                    || browser.flatpak_id.as_deref() == Some(id)
+                    || browser.executable.as_deref() == Some(id)
+            })
+            .map(|browser| browser.base)
+    }
+
+    /// Installed browser that runs a command (e.g. the Exec of a desktop file),
+    /// by flatpak id or executable name
+    pub fn get_by_run_args(&self, args: &[String]) -> Option<Rc<Browser>> {
+        let program = Path::new(args.first()?)
+            .file_name()?
+            .to_string_lossy()
+            .to_string();
+
+        if program == "flatpak" {
+            let flatpak_id = args
+                .iter()
+                .skip_while(|arg| *arg != "run")
+                .skip(1)
+                .find(|arg| !arg.starts_with('-'))?;
+
+            return self
+                .get_flatpak_browsers()
+                .into_iter()
+                .find(|browser| browser.flatpak_id.as_ref() == Some(flatpak_id));
+        }
+
+        // E.g. `google-chrome` for `google-chrome-stable`, `chromium` for `chromium-browser`
+        let is_same_executable = |executable: &str| {
+            let executable = Path::new(executable)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+
+            executable == program
+                || executable.starts_with(&format!("{program}-"))
+                || program.starts_with(&format!("{executable}-"))
+        };
+
+        self.get_system_browsers().into_iter().find(|browser| {
+            browser
+                .executable
+                .as_deref()
+                .is_some_and(is_same_executable)
+        })
+    }
+
     pub fn get_index(&self, browser: &Browser) -> Option<usize> {
         self.get_all_browsers()
             .iter()
@@ -326,8 +583,18 @@ impl BrowserConfigs {
             return;
         }
 
-        debug!("Adding icon path to icon theme: {}", path.display());
-        self.icon_theme.add_search_path(path);
+        #[cfg(feature = "gtk")]
+        {
+            let Some(icon_theme) = &self.icon_theme else {
+                debug!("No icon theme to add icon path to: {}", path.display());
+                return;
+            };
+
+            debug!("Adding icon path to icon theme: {}", path.display());
+            icon_theme.add_search_path(path);
+        }
+        #[cfg(not(feature = "gtk"))]
+        debug!("No icon theme without gtk: {}", path.display());
     }
 
     fn get_no_browser(self: &Rc<Self>) -> Browser {
@@ -343,9 +610,13 @@ impl BrowserConfigs {
             desktop_file_name_prefix: String::default(),
             config_name: String::default(),
             configs: self.clone(),
+            #[cfg(feature = "gtk")]
             icon_names: HashSet::from(["dialog-warning-symbolic".to_string()]),
             base: Base::None,
             issues: HashMap::new(),
+            flags: Vec::new(),
+            proxy_modes: Vec::new(),
+            #[cfg(feature = "gtk")]
             icon_theme: self.icon_theme.clone(),
             app_dirs: self.app_dirs.clone(),
         }
@@ -360,7 +631,7 @@ impl BrowserConfigs {
             let mut is_installed = false;
 
             if let Some(flatpak) = &browser_config.config.flatpak {
-                if Self::is_installed_flatpak(flatpak) {
+                if self.detector.is_installed_flatpak(flatpak) {
                     info!(
                         "Found flatpak browser '{flatpak}' for config '{}'",
                         browser_config.file_name
@@ -370,7 +641,6 @@ impl BrowserConfigs {
                         &browser_config,
                         Installation::Flatpak(flatpak.clone()),
                         self,
-                        &self.icon_theme,
                         &self.app_dirs,
                     ));
 
@@ -391,7 +661,7 @@ impl BrowserConfigs {
             }
 
             if let Some(system_bin) = &browser_config.config.system_bin {
-                if Self::is_installed_system(system_bin) {
+                if self.detector.is_installed_system(system_bin) {
                     info!(
                         "Found system browser '{system_bin}' for config '{}'",
                         browser_config.file_name
@@ -401,7 +671,6 @@ impl BrowserConfigs {
                         &browser_config,
                         Installation::System(system_bin.clone()),
                         self,
-                        &self.icon_theme,
                         &self.app_dirs,
                     ));
 
@@ -420,7 +689,6 @@ impl BrowserConfigs {
                     &browser_config,
                     Installation::None,
                     self,
-                    &self.icon_theme,
                     &self.app_dirs,
                 ));
                 uninstalled_browsers.push(browser);
@@ -430,34 +698,9 @@ impl BrowserConfigs {
         let no_browser = self.get_no_browser();
         installed_browsers.push(Rc::new(no_browser));
 
-        let _ = self.all_browsers.set(installed_browsers);
-        let _ = self.uninstalled_browsers.set(uninstalled_browsers);
-    }
-
-    fn is_installed_flatpak(flatpak: &str) -> bool {
-        let command = format!("flatpak info {flatpak}");
-        let result = utils::command::run_command_sync(&command);
-
-        match result {
-            Err(error) => {
-                error!("Could not run command '{command}'. Error: {error:?}");
-                false
-            }
-            Ok(response) => response.success,
-        }
-    }
-
-    fn is_installed_system(system_bin: &str) -> bool {
-        let command = format!("which {system_bin}");
-        let result = utils::command::run_command_sync(&command);
-
-        match result {
-            Err(error) => {
-                error!("Could not run command '{command}'. Error: {error:?}");
-                false
-            }
-            Ok(response) => response.success,
-        }
+        self.all_browsers.replace(Some(installed_browsers));
+        self.uninstalled_browsers
+            .replace(Some(uninstalled_browsers));
     }
 
     fn get_icon_search_path_flatpak(flatpak: &str) -> Option<PathBuf> {
@@ -558,6 +801,11 @@ impl BrowserConfigs {
                 }
             };
 
+            if let Err(error) = Self::validate_template(&browser, &desktop_file) {
+                error!("Invalid .desktop file template for: '{file_name}'. Error: '{error:?}'");
+                continue;
+            }
+
             let browser_config = BrowserConfig {
                 config: browser,
                 config_name,
@@ -569,4 +817,32 @@ impl BrowserConfigs {
 
         browser_configs
     }
+
+    /// Catch unknown placeholders on load instead of on saving a web app
+    fn validate_template(browser: &BrowserYaml, desktop_file: &DesktopEntry) -> Result<()> {
+        for flag in &browser.flags {
+            if flag.name.is_empty()
+                || !flag
+                    .name
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || char == '_')
+            {
+                bail!("Invalid flag name '{}'", flag.name)
+            }
+            if DesktopFile::TEMPLATE_VARIABLES.contains(&flag.name.as_str()) {
+                bail!("Flag '{}' is a reserved template variable", flag.name)
+            }
+        }
+
+        let template = Template::parse(&desktop_file.to_string())?;
+        for variable in template.get_variables() {
+            if !DesktopFile::TEMPLATE_VARIABLES.contains(&variable.as_str())
+                && !browser.flags.iter().any(|flag| flag.name == variable)
+            {
+                bail!("Unknown template variable '{variable}'")
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,833 @@
+use crate::{
+    app_dirs::AppDirs,
+    browsers::{Browser, BrowserConfigs},
+    config::{self},
+    desktop_file::DesktopFile,
+    transaction::Transaction,
+    utils::OnceLockExt,
+};
+use anyhow::{Context, Result, bail};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write as _,
+    fs::{self, File},
+    io,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{debug, info, warn};
+
+#[derive(Serialize, Deserialize)]
+pub struct BundleWebApp {
+    pub id: String,
+    pub name: String,
+    pub browser_id: String,
+    pub browser_config_name: String,
+    pub browser_base: String,
+    /// Paths inside the bundle
+    pub desktop_file: String,
+    pub icon: Option<String>,
+    pub profile: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub web_apps: Vec<BundleWebApp>,
+    /// Sha256 of every file in the bundle: `BTreeMap<bundle_path, checksum>`
+    pub checksums: BTreeMap<String, String>,
+}
+
+/// A portable archive (tar.gz) of web apps with their icons and optionally their profiles
+pub struct Bundle;
+impl Bundle {
+    pub const EXTENSION: &str = "tar.gz";
+    const FORMAT_VERSION: u32 = 1;
+    const MANIFEST_FILE: &str = "manifest.yml";
+    const WEB_APPS_DIR: &str = "web-apps";
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    pub fn export(
+        desktop_files: &[DesktopFile],
+        archive_path: &Path,
+        include_profiles: bool,
+    ) -> Result<()> {
+        let mut web_apps = Vec::new();
+        let mut checksums = BTreeMap::new();
+        // (bundle path, file on disk)
+        let mut files: Vec<(String, PathBuf)> = Vec::new();
+        // (bundle path, content)
+        let mut data: Vec<(String, String)> = Vec::new();
+
+        for desktop_file in desktop_files {
+            let id = desktop_file.get_id().context("No id on 'DesktopFile'")?;
+            let name = desktop_file.get_name().unwrap_or_default();
+            let browser = desktop_file
+                .get_browser()
+                .context(format!("No browser on web app: {name}"))?;
+            let app_dir = format!("{}/{id}", Self::WEB_APPS_DIR);
+
+            let desktop_file_bundle_path = format!("{app_dir}/app.desktop");
+            let desktop_file_content = desktop_file.to_string();
+            checksums.insert(
+                desktop_file_bundle_path.clone(),
+                Self::checksum_bytes(desktop_file_content.as_bytes()),
+            );
+            data.push((desktop_file_bundle_path.clone(), desktop_file_content));
+
+            let icon = match desktop_file.get_icon_path() {
+                // Icons are saved as png, others are e.g. from an older version
+                Some(icon_path) if icon_path.is_file() && !Self::is_png(&icon_path)? => {
+                    warn!(name, "Icon is not a png, skipping icon");
+                    None
+                }
+                Some(icon_path) if icon_path.is_file() => {
+                    let bundle_path = format!("{app_dir}/icon.png");
+                    checksums.insert(bundle_path.clone(), Self::checksum_file(&icon_path)?);
+                    files.push((bundle_path.clone(), icon_path));
+                    Some(bundle_path)
+                }
+                _ => None,
+            };
+
+            let profile = match desktop_file.get_profile_path() {
+                Some(profile_path)
+                    if include_profiles
+                        && desktop_file.get_isolated().unwrap_or(false)
+                        && profile_path.is_dir() =>
+                {
+                    let bundle_path = format!("{app_dir}/profile");
+                    for (file_bundle_path, file_path) in
+                        Self::collect_dir_files(&profile_path, &bundle_path)?
+                    {
+                        checksums
+                            .insert(file_bundle_path.clone(), Self::checksum_file(&file_path)?);
+                        files.push((file_bundle_path, file_path));
+                    }
+                    Some(bundle_path)
+                }
+                _ => None,
+            };
+
+            web_apps.push(BundleWebApp {
+                id,
+                name,
+                browser_id: browser.id.clone(),
+                browser_config_name: browser.config_name.clone(),
+                browser_base: browser.base.to_string(),
+                desktop_file: desktop_file_bundle_path,
+                icon,
+                profile,
+            });
+        }
+
+        let manifest = BundleManifest {
+            format_version: Self::FORMAT_VERSION,
+            app_version: config::VERSION.get_value().clone(),
+            web_apps,
+            checksums,
+        };
+        let manifest_yaml =
+            serde_yaml::to_string(&manifest).context("Failed to parse manifest to yaml")?;
+
+        debug!("Writing bundle: {}", archive_path.display());
+        let archive_file = File::create(archive_path).context(format!(
+            "Failed to create bundle: {}",
+            archive_path.display()
+        ))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(archive_file, Compression::default()));
+
+        Self::append_data(&mut builder, Self::MANIFEST_FILE, &manifest_yaml)?;
+        for (bundle_path, content) in &data {
+            Self::append_data(&mut builder, bundle_path, content)?;
+        }
+        for (bundle_path, file_path) in &files {
+            builder
+                .append_path_with_name(file_path, bundle_path)
+                .context(format!(
+                    "Failed to add file to bundle: {}",
+                    file_path.display()
+                ))?;
+        }
+
+        builder
+            .into_inner()
+            .and_then(GzEncoder::finish)
+            .context("Failed to write bundle")?;
+
+        info!(
+            "Exported {} web app(s) to: {}",
+            manifest.web_apps.len(),
+            archive_path.display()
+        );
+        Ok(())
+    }
+
+    pub fn read_manifest(archive_path: &Path) -> Result<BundleManifest> {
+        let archive_file = File::open(archive_path)
+            .context(format!("Failed to open bundle: {}", archive_path.display()))?;
+        let mut archive = tar::Archive::new(GzDecoder::new(archive_file));
+
+        for entry in archive.entries().context("Failed to read bundle")? {
+            let mut entry = entry.context("Failed to read bundle entry")?;
+            if entry.path()?.as_ref() != Path::new(Self::MANIFEST_FILE) {
+                continue;
+            }
+
+            let mut manifest_yaml = String::new();
+            io::Read::read_to_string(&mut entry, &mut manifest_yaml)?;
+            return Self::parse_manifest(&manifest_yaml);
+        }
+
+        bail!("No manifest found in bundle")
+    }
+
+    /// Import all web apps in a bundle, the browser is taken from the bundle if installed.
+    /// Web apps that have an id in `existing_ids` get a new id.
+    /// Nothing is imported when one of the web apps fails.
+    pub fn import(
+        archive_path: &Path,
+        browser: Option<&Rc<Browser>>,
+        existing_ids: &HashSet<String>,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<Vec<DesktopFile>> {
+        let extract_dir = app_dirs.app_cache.join("import");
+        if extract_dir.is_dir() {
+            fs::remove_dir_all(&extract_dir).context("Failed to clean import dir")?;
+        }
+        fs::create_dir_all(&extract_dir).context("Failed to create import dir")?;
+
+        let result = Self::import_from_dir(
+            archive_path,
+            &extract_dir,
+            browser,
+            existing_ids,
+            browser_configs,
+            app_dirs,
+        );
+
+        if let Err(error) = fs::remove_dir_all(&extract_dir) {
+            warn!("Failed to remove import dir: {error:?}");
+        }
+
+        result
+    }
+
+    fn import_from_dir(
+        archive_path: &Path,
+        extract_dir: &Path,
+        browser: Option<&Rc<Browser>>,
+        existing_ids: &HashSet<String>,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<Vec<DesktopFile>> {
+        debug!("Extracting bundle to: {}", extract_dir.display());
+        let archive_file = File::open(archive_path)
+            .context(format!("Failed to open bundle: {}", archive_path.display()))?;
+        tar::Archive::new(GzDecoder::new(archive_file))
+            .unpack(extract_dir)
+            .context("Failed to extract bundle")?;
+
+        let manifest_yaml = fs::read_to_string(extract_dir.join(Self::MANIFEST_FILE))
+            .context("No manifest found in bundle")?;
+        let manifest = Self::parse_manifest(&manifest_yaml)?;
+
+        for (bundle_path, checksum) in &manifest.checksums {
+            let file_path = Self::get_bundle_path(extract_dir, bundle_path)?;
+            if !file_path.is_file() || Self::checksum_file(&file_path)? != *checksum {
+                bail!("Checksum mismatch in bundle: {bundle_path}")
+            }
+        }
+        for web_app in &manifest.web_apps {
+            Self::check_web_app_checksums(web_app, &manifest, extract_dir)?;
+        }
+
+        let mut used_ids = existing_ids.clone();
+        let mut imported = Vec::new();
+        // Files of all web apps are rolled back when one of them fails
+        let mut transaction = Transaction::new();
+
+        for web_app in &manifest.web_apps {
+            let desktop_file = Self::import_web_app(
+                web_app,
+                extract_dir,
+                browser,
+                &used_ids,
+                browser_configs,
+                app_dirs,
+                &mut transaction,
+            )
+            .context(format!("Failed to import web app: {}", web_app.name))?;
+
+            if let Some(id) = desktop_file.get_id() {
+                used_ids.insert(id);
+            }
+            imported.push(desktop_file);
+        }
+        transaction.commit();
+
+        Ok(imported)
+    }
+
+    fn import_web_app(
+        web_app: &BundleWebApp,
+        extract_dir: &Path,
+        browser: Option<&Rc<Browser>>,
+        used_ids: &HashSet<String>,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+        transaction: &mut Transaction,
+    ) -> Result<DesktopFile> {
+        let browser = match browser {
+            Some(browser) => browser.clone(),
+            None => Self::find_browser(web_app, browser_configs)?,
+        };
+
+        let desktop_file_path = Self::get_bundle_path(extract_dir, &web_app.desktop_file)?;
+        let mut desktop_file =
+            DesktopFile::from_path(&desktop_file_path, browser_configs, app_dirs)?;
+
+        let id = if used_ids.contains(&web_app.id) {
+            let new_id = DesktopFile::generate_id();
+            info!(
+                old_id = web_app.id,
+                new_id, "Web app id already exists, using a new id"
+            );
+            new_id
+        } else {
+            web_app.id.clone()
+        };
+        desktop_file.set_id(&id);
+        desktop_file.set_browser(&browser);
+
+        if let Some(icon) = &web_app.icon {
+            let icon_path = Self::get_bundle_path(extract_dir, icon)?;
+            if !Self::is_png(&icon_path)? {
+                bail!("Icon is not a png: {icon}")
+            }
+            let file_name = sanitize_filename::sanitize(format!("{id}.png"));
+            let save_path = app_dirs.app_data_icons.join(file_name);
+            transaction
+                .write_with(&save_path, |temp_path| {
+                    fs::copy(&icon_path, temp_path)
+                        .map(|_| ())
+                        .map_err(Into::into)
+                })
+                .context("Failed to copy icon")?;
+            desktop_file.set_icon_path(&save_path);
+        }
+
+        if !browser.can_start_maximized {
+            desktop_file.set_maximized(false);
+        }
+
+        let is_isolated = desktop_file.get_isolated().unwrap_or(false) && browser.can_isolate;
+        desktop_file.set_isolated(is_isolated);
+
+        if is_isolated {
+            let profile_path = desktop_file.build_profile_path_in(transaction)?;
+
+            if let Some(profile) = &web_app.profile {
+                if web_app.browser_base == browser.base.to_string() {
+                    let bundle_profile_path = Self::get_bundle_path(extract_dir, profile)?;
+                    transaction
+                        .copy_dir(&bundle_profile_path, &profile_path)
+                        .context("Failed to copy profile")?;
+                } else {
+                    warn!(
+                        name = web_app.name,
+                        "Profile is from a different browser engine, skipping profile"
+                    );
+                }
+            }
+
+            desktop_file.set_profile_path(&profile_path);
+        } else {
+            desktop_file.set_profile_path(Path::new(""));
+        }
+
+        // Generates the new Exec and path from the browser
+        let desktop_file = desktop_file.save_in(transaction)?;
+
+        info!(name = web_app.name, id, "Imported web app");
+        Ok(desktop_file)
+    }
+
+    fn find_browser(
+        web_app: &BundleWebApp,
+        browser_configs: &Rc<BrowserConfigs>,
+    ) -> Result<Rc<Browser>> {
+        if let Some(browser) = browser_configs
+            .get_by_id(&web_app.browser_id)
+            .filter(|browser| browser.is_installed())
+        {
+            return Ok(browser);
+        }
+
+        // Same browser with a different installation (e.g. flatpak <-> system)
+        browser_configs
+            .get_all_browsers()
+            .iter()
+            .find(|browser| {
+                browser.is_installed() && browser.config_name == web_app.browser_config_name
+            })
+            .cloned()
+            .context(format!(
+                "Browser '{}' is not installed, please pick another browser",
+                web_app.browser_id
+            ))
+    }
+
+    fn parse_manifest(manifest_yaml: &str) -> Result<BundleManifest> {
+        let manifest: BundleManifest =
+            serde_yaml::from_str(manifest_yaml).context("Failed to parse bundle manifest")?;
+
+        if manifest.format_version > Self::FORMAT_VERSION {
+            bail!(
+                "Bundle format version {} is not supported, please update {}",
+                manifest.format_version,
+                config::APP_NAME.get_value()
+            )
+        }
+
+        Ok(manifest)
+    }
+
+    /// Only allow plain relative paths so a bundle can't point outside the extract dir
+    fn get_bundle_path(extract_dir: &Path, bundle_path: &str) -> Result<PathBuf> {
+        let path = Path::new(bundle_path);
+        if !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("Invalid path in bundle: {bundle_path}")
+        }
+
+        Ok(extract_dir.join(path))
+    }
+
+    /// Every file a web app uses needs a checksum, so no file is used unverified
+    fn check_web_app_checksums(
+        web_app: &BundleWebApp,
+        manifest: &BundleManifest,
+        extract_dir: &Path,
+    ) -> Result<()> {
+        let mut bundle_paths = vec![web_app.desktop_file.clone()];
+        bundle_paths.extend(web_app.icon.clone());
+        if let Some(profile) = &web_app.profile {
+            let profile_path = Self::get_bundle_path(extract_dir, profile)?;
+            if profile_path.is_dir() {
+                bundle_paths.extend(
+                    Self::collect_dir_files(&profile_path, profile)?
+                        .into_iter()
+                        .map(|(bundle_path, _)| bundle_path),
+                );
+            }
+        }
+
+        for bundle_path in bundle_paths {
+            if !manifest.checksums.contains_key(&bundle_path) {
+                bail!("No checksum in bundle for: {bundle_path}")
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_png(path: &Path) -> Result<bool> {
+        let mut file = File::open(path).context(format!("Failed to open: {}", path.display()))?;
+        let mut signature = [0; Self::PNG_SIGNATURE.len()];
+
+        match io::Read::read_exact(&mut file, &mut signature) {
+            Ok(()) => Ok(signature == Self::PNG_SIGNATURE),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn collect_dir_files(dir: &Path, bundle_dir: &str) -> Result<Vec<(String, PathBuf)>> {
+        let mut files = Vec::new();
+
+        for entry in fs::read_dir(dir).context(format!("Failed to read dir: {}", dir.display()))? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let bundle_path = format!("{bundle_dir}/{}", entry.file_name().to_string_lossy());
+
+            // Skips symlinks and sockets (e.g. chromium's `SingletonLock`)
+            if file_type.is_dir() {
+                files.extend(Self::collect_dir_files(&entry.path(), &bundle_path)?);
+            } else if file_type.is_file() {
+                files.push((bundle_path, entry.path()));
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn append_data<W: io::Write>(
+        builder: &mut tar::Builder<W>,
+        bundle_path: &str,
+        content: &str,
+    ) -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        builder
+            .append_data(&mut header, bundle_path, content.as_bytes())
+            .context(format!("Failed to add '{bundle_path}' to bundle"))
+    }
+
+    fn checksum_file(path: &Path) -> Result<String> {
+        let mut file = File::open(path).context(format!("Failed to open: {}", path.display()))?;
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher)?;
+
+        Ok(Self::to_hex(&hasher.finalize()))
+    }
+
+    fn checksum_bytes(bytes: &[u8]) -> String {
+        Self::to_hex(&Sha256::digest(bytes))
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app_dirs::BaseDirs,
+        assets,
+        browsers::{Installation, StaticDetector},
+        desktop_file::ProfileMigration,
+    };
+    use tempfile::TempDir;
+
+    /// A home in a temp dir with the shipped assets and firefox installed
+    fn setup() -> (TempDir, Rc<AppDirs>, Rc<BrowserConfigs>) {
+        config::init();
+        let home = tempfile::tempdir().unwrap();
+        let app_dirs = AppDirs::from_base_dirs(BaseDirs::from_home(home.path())).unwrap();
+        assets::init(&app_dirs).unwrap();
+        let browser_configs = BrowserConfigs::new_with_detector(
+            &app_dirs,
+            Box::new(StaticDetector {
+                installations: vec![Installation::System("firefox".to_string())],
+            }),
+        );
+        browser_configs.init();
+
+        (home, app_dirs, browser_configs)
+    }
+
+    /// A saved isolated web app with an icon and a file in its profile
+    fn create(
+        id: &str,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> DesktopFile {
+        let browser = browser_configs.get_by_id("firefox").unwrap();
+        let mut desktop_file = DesktopFile::new(browser_configs, app_dirs);
+        desktop_file.set_id(id);
+        desktop_file.set_name(&format!("App {id}"));
+        desktop_file.set_url(&format!("https://{id}.example.com"));
+        desktop_file.set_browser(&browser);
+        desktop_file.set_maximized(false);
+
+        let icon_path = app_dirs.app_data_icons.join(format!("{id}.png"));
+        fs::write(&icon_path, Bundle::PNG_SIGNATURE).unwrap();
+        desktop_file.set_icon_path(&icon_path);
+
+        let mut transaction = Transaction::new();
+        desktop_file.set_isolated(true);
+        desktop_file
+            .update_profile_in(true, ProfileMigration::Discard, &mut transaction)
+            .unwrap();
+        let desktop_file = desktop_file.save_in(&mut transaction).unwrap();
+        transaction.commit();
+
+        let profile_path = desktop_file.get_profile_path().unwrap();
+        fs::write(profile_path.join("prefs.js"), format!("// {id}")).unwrap();
+
+        desktop_file
+    }
+
+    fn export(desktop_files: &[DesktopFile], home: &TempDir) -> PathBuf {
+        let archive_path = home.path().join(format!("bundle.{}", Bundle::EXTENSION));
+        Bundle::export(desktop_files, &archive_path, true).unwrap();
+        archive_path
+    }
+
+    fn unpack(archive_path: &Path, dir: &Path) {
+        tar::Archive::new(GzDecoder::new(File::open(archive_path).unwrap()))
+            .unpack(dir)
+            .unwrap();
+    }
+
+    fn repack(dir: &Path, archive_path: &Path) {
+        let archive_file = File::create(archive_path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(archive_file, Compression::default()));
+        builder.append_dir_all(".", dir).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn read_manifest_in(dir: &Path) -> BundleManifest {
+        let manifest_yaml = fs::read_to_string(dir.join(Bundle::MANIFEST_FILE)).unwrap();
+        Bundle::parse_manifest(&manifest_yaml).unwrap()
+    }
+
+    fn write_manifest_in(dir: &Path, manifest: &BundleManifest) {
+        let manifest_yaml = serde_yaml::to_string(manifest).unwrap();
+        fs::write(dir.join(Bundle::MANIFEST_FILE), manifest_yaml).unwrap();
+    }
+
+    fn import(
+        archive_path: &Path,
+        existing_ids: &[&str],
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<Vec<DesktopFile>> {
+        let existing_ids = existing_ids.iter().map(ToString::to_string).collect();
+        Bundle::import(archive_path, None, &existing_ids, browser_configs, app_dirs)
+    }
+
+    fn assert_nothing_imported(app_dirs: &Rc<AppDirs>, browser_configs: &Rc<BrowserConfigs>) {
+        let profiles_path = browser_configs
+            .get_by_id("firefox")
+            .unwrap()
+            .get_profile_path()
+            .unwrap();
+
+        assert!(DesktopFile::get_owned_paths(app_dirs).is_empty());
+        assert_eq!(fs::read_dir(&app_dirs.app_data_icons).unwrap().count(), 0);
+        assert!(!profiles_path.is_dir() || fs::read_dir(&profiles_path).unwrap().count() == 0);
+    }
+
+    #[test]
+    fn get_bundle_path_allows_only_relative_paths() {
+        let extract_dir = Path::new("/tmp/import");
+
+        assert_eq!(
+            Bundle::get_bundle_path(extract_dir, "web-apps/abcd1234/app.desktop").unwrap(),
+            extract_dir.join("web-apps/abcd1234/app.desktop")
+        );
+        for bundle_path in [
+            "../app.desktop",
+            "web-apps/../../app.desktop",
+            "/etc/passwd",
+            "./app.desktop",
+        ] {
+            assert!(
+                Bundle::get_bundle_path(extract_dir, bundle_path).is_err(),
+                "{bundle_path}"
+            );
+        }
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let (home, app_dirs, browser_configs) = setup();
+        let desktop_files = [
+            create("aaaa1111", &browser_configs, &app_dirs),
+            create("bbbb2222", &browser_configs, &app_dirs),
+        ];
+        let archive_path = export(&desktop_files, &home);
+
+        let manifest = Bundle::read_manifest(&archive_path).unwrap();
+        assert_eq!(manifest.web_apps.len(), 2);
+        assert!(
+            manifest
+                .checksums
+                .contains_key("web-apps/aaaa1111/profile/prefs.js")
+        );
+
+        let (_import_home, import_app_dirs, import_browser_configs) = setup();
+        let imported = import(
+            &archive_path,
+            &[],
+            &import_browser_configs,
+            &import_app_dirs,
+        )
+        .unwrap();
+
+        assert_eq!(imported.len(), 2);
+        for (desktop_file, original) in imported.iter().zip(&desktop_files) {
+            assert_eq!(desktop_file.get_id(), original.get_id());
+            assert_eq!(desktop_file.get_name(), original.get_name());
+            assert_eq!(desktop_file.get_url(), original.get_url());
+            assert!(desktop_file.get_path().is_file());
+            assert!(desktop_file.get_icon_path().unwrap().is_file());
+            assert_eq!(
+                fs::read_to_string(desktop_file.get_profile_path().unwrap().join("prefs.js"))
+                    .unwrap(),
+                format!("// {}", original.get_id().unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn import_remaps_existing_id() {
+        let (home, app_dirs, browser_configs) = setup();
+        let desktop_files = [
+            create("aaaa1111", &browser_configs, &app_dirs),
+            create("bbbb2222", &browser_configs, &app_dirs),
+        ];
+        let archive_path = export(&desktop_files, &home);
+
+        let imported = import(&archive_path, &["aaaa1111"], &browser_configs, &app_dirs).unwrap();
+
+        let new_id = imported[0].get_id().unwrap();
+        assert_ne!(new_id, "aaaa1111");
+        assert_ne!(new_id, "bbbb2222");
+        assert!(imported[0].get_path().to_string_lossy().contains(&new_id));
+        assert_eq!(
+            imported[0].get_icon_path().unwrap(),
+            app_dirs.app_data_icons.join(format!("{new_id}.png"))
+        );
+        assert!(imported[0].get_profile_path().unwrap().ends_with(&new_id));
+        // Not in `existing_ids`, but in use by the first import after this
+        assert_eq!(imported[1].get_id().as_deref(), Some("bbbb2222"));
+        // The existing web app is untouched
+        assert!(desktop_files[0].get_path().is_file());
+        assert_eq!(DesktopFile::get_owned_paths(&app_dirs).len(), 3);
+    }
+
+    #[test]
+    fn import_rejects_tampered_file() {
+        let (home, app_dirs, browser_configs) = setup();
+        let desktop_file = create("aaaa1111", &browser_configs, &app_dirs);
+        let archive_path = export(&[desktop_file], &home);
+
+        let bundle_dir = tempfile::tempdir().unwrap();
+        unpack(&archive_path, bundle_dir.path());
+        let desktop_file_path = bundle_dir.path().join("web-apps/aaaa1111/app.desktop");
+        let content = fs::read_to_string(&desktop_file_path).unwrap();
+        fs::write(
+            &desktop_file_path,
+            content.replace("example.com", "evil.com"),
+        )
+        .unwrap();
+        repack(bundle_dir.path(), &archive_path);
+
+        let (_import_home, import_app_dirs, import_browser_configs) = setup();
+        let error = import(
+            &archive_path,
+            &[],
+            &import_browser_configs,
+            &import_app_dirs,
+        )
+        .err()
+        .unwrap();
+
+        assert!(error.to_string().contains("Checksum mismatch"), "{error:?}");
+        assert_nothing_imported(&import_app_dirs, &import_browser_configs);
+    }
+
+    #[test]
+    fn import_rejects_file_without_checksum() {
+        let (home, app_dirs, browser_configs) = setup();
+        let desktop_file = create("aaaa1111", &browser_configs, &app_dirs);
+        let archive_path = export(&[desktop_file], &home);
+
+        let bundle_dir = tempfile::tempdir().unwrap();
+        unpack(&archive_path, bundle_dir.path());
+        let mut manifest = read_manifest_in(bundle_dir.path());
+        manifest.checksums.remove("web-apps/aaaa1111/icon.png");
+        write_manifest_in(bundle_dir.path(), &manifest);
+        repack(bundle_dir.path(), &archive_path);
+
+        let (_import_home, import_app_dirs, import_browser_configs) = setup();
+        let error = import(
+            &archive_path,
+            &[],
+            &import_browser_configs,
+            &import_app_dirs,
+        )
+        .err()
+        .unwrap();
+
+        assert!(error.to_string().contains("No checksum"), "{error:?}");
+        assert_nothing_imported(&import_app_dirs, &import_browser_configs);
+    }
+
+    #[test]
+    fn import_rejects_path_outside_bundle() {
+        let (home, app_dirs, browser_configs) = setup();
+        let desktop_file = create("aaaa1111", &browser_configs, &app_dirs);
+        let archive_path = export(&[desktop_file], &home);
+
+        let bundle_dir = tempfile::tempdir().unwrap();
+        unpack(&archive_path, bundle_dir.path());
+        let mut manifest = read_manifest_in(bundle_dir.path());
+        let checksum = manifest.checksums["web-apps/aaaa1111/app.desktop"].clone();
+        let outside_path = "../outside.desktop".to_string();
+        manifest.web_apps[0].desktop_file.clone_from(&outside_path);
+        manifest.checksums.insert(outside_path, checksum);
+        write_manifest_in(bundle_dir.path(), &manifest);
+        repack(bundle_dir.path(), &archive_path);
+
+        let (_import_home, import_app_dirs, import_browser_configs) = setup();
+        let error = import(
+            &archive_path,
+            &[],
+            &import_browser_configs,
+            &import_app_dirs,
+        )
+        .err()
+        .unwrap();
+
+        assert!(error.to_string().contains("Invalid path"), "{error:?}");
+        assert_nothing_imported(&import_app_dirs, &import_browser_configs);
+    }
+
+    #[test]
+    fn failed_import_rolls_back_all_web_apps() {
+        let (home, app_dirs, browser_configs) = setup();
+        let desktop_files = [
+            create("aaaa1111", &browser_configs, &app_dirs),
+            create("bbbb2222", &browser_configs, &app_dirs),
+        ];
+        let archive_path = export(&desktop_files, &home);
+
+        // A valid checksum, but the second web app fails on its icon
+        let bundle_dir = tempfile::tempdir().unwrap();
+        unpack(&archive_path, bundle_dir.path());
+        let icon_bundle_path = "web-apps/bbbb2222/icon.png";
+        fs::write(bundle_dir.path().join(icon_bundle_path), "Not a png").unwrap();
+        let mut manifest = read_manifest_in(bundle_dir.path());
+        manifest.checksums.insert(
+            icon_bundle_path.to_string(),
+            Bundle::checksum_bytes(b"Not a png"),
+        );
+        write_manifest_in(bundle_dir.path(), &manifest);
+        repack(bundle_dir.path(), &archive_path);
+
+        let (_import_home, import_app_dirs, import_browser_configs) = setup();
+        let error = import(
+            &archive_path,
+            &[],
+            &import_browser_configs,
+            &import_app_dirs,
+        )
+        .err()
+        .unwrap();
+
+        assert!(
+            format!("{error:?}").contains("Icon is not a png"),
+            "{error:?}"
+        );
+        assert_nothing_imported(&import_app_dirs, &import_browser_configs);
+    }
+}
@@ -1,31 +1,48 @@
+pub mod action;
 pub mod category;
 pub mod error;
-mod key;
+pub mod exec;
+pub mod key;
+pub mod migration;
+pub mod template;
+pub mod user_js;
 mod utils;
 
 use crate::{
     app_dirs::AppDirs,
-    browsers::{Base, Browser, BrowserConfigs},
+    browsers::{Base, Browser, BrowserConfigs, ProxyMode},
     config::{self},
+    transaction::Transaction,
     utils::OnceLockExt,
 };
-use anyhow::{Context, Result, anyhow, bail};
+use action::DesktopAction;
+use anyhow::{Context, Result, bail};
 use category::Category;
-use error::{DesktopFileError, ValidationError};
+use error::{DesktopFileError, Severity, ValidationError, ValidationReport};
 use freedesktop_desktop_entry::DesktopEntry;
+#[cfg(feature = "gtk")]
 use gtk::{Image, prelude::WidgetExt};
 use key::Key;
+use migration::MigrationReport;
 use rand::{Rng, distributions::Alphanumeric};
 use regex::Regex;
 use semver::Version;
 use std::{
+    collections::{BTreeMap, HashSet},
+    env,
     fs::{self},
     path::{Path, PathBuf},
     rc::Rc,
+    sync::LazyLock,
 };
-use tracing::{debug, error, info};
+use template::Template;
+use tracing::{debug, error, info, warn};
 use url::Url;
-use utils::{map_to_bool_option, map_to_path_option, map_to_string_option};
+use user_js::{Pref, PrefValue};
+use utils::{
+    add_group_entry, get_group_entry, get_locale_map, map_to_bool_option, map_to_list,
+    map_to_path_option, map_to_string_option, remove_entry, remove_groups, set_locale_map,
+};
 
 pub struct DesktopFileEntries {
     name: String,
@@ -39,6 +56,59 @@ pub struct DesktopFileEntries {
     maximize: bool,
     icon_path: PathBuf,
     profile_path: PathBuf,
+    actions: Vec<DesktopAction>,
+    flags: Vec<String>,
+    extra_args: Vec<String>,
+    env_vars: Vec<(String, String)>,
+    user_agent: Option<String>,
+    proxy: Option<Proxy>,
+    browser_app: BrowserApp,
+}
+
+/// Proxy of a web app whose browser supports the mode
+struct Proxy {
+    mode: ProxyMode,
+    server: Option<(String, u16)>,
+    bypass: Vec<String>,
+}
+
+/// An app installed by the browser itself (e.g. a PWA of Chrome) that this web app launches
+#[derive(Default)]
+struct BrowserApp {
+    app_id: Option<String>,
+    profile_directory: Option<String>,
+    wm_class: Option<String>,
+}
+
+/// What happens with the profile of an isolated web app when its profile path changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileMigration {
+    /// Carry the profile over and remove the old one
+    Move,
+    /// Carry the profile over and keep the old one, e.g. to switch back later
+    Copy,
+    /// Start with a new profile and remove the old one
+    Discard,
+    /// Start with a new profile and keep the old one
+    Keep,
+}
+
+/// If a setting of a web app works with its browser. Firefox takes most settings from
+/// `user.js`, which only an isolated profile has, see `get_profile_prefs`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingSupport {
+    Supported,
+    NeedsIsolation,
+    Unsupported,
+}
+
+/// A web app as loaded by `DesktopFile::get_owned`
+pub struct OwnedDesktopFile {
+    pub desktop_file: DesktopFile,
+    /// The browser it switched to because its own browser is uninstalled
+    pub fallback_browser: Option<Rc<Browser>>,
+    /// Migrated from a desktop file of an older version
+    pub is_updated: bool,
 }
 
 #[derive(Clone)]
@@ -48,6 +118,33 @@ pub struct DesktopFile {
     app_dirs: Rc<AppDirs>,
 }
 impl DesktopFile {
+    pub const SCHEME_HANDLER_PREFIX: &str = "x-scheme-handler/";
+    pub const HANDLER_URL_PLACEHOLDER: &str = "%s";
+    /// Variables of the browser desktop file templates, besides the flags of the browser
+    pub const TEMPLATE_VARIABLES: &[&str] = &[
+        "command",
+        "name",
+        "url",
+        "domain",
+        "domain_path",
+        "icon",
+        "app_id",
+        "profile_path",
+        "is_isolated",
+        "is_maximized",
+        "browser_app_id",
+        "profile_directory",
+        "wm_class",
+        "extra_args",
+        "user_agent",
+        "no_proxy_server",
+        "proxy_server",
+        "proxy_bypass_list",
+    ];
+    /// Arguments the browser desktop file templates set themselves, an extra argument
+    /// with the same name conflicts with it
+    pub const TEMPLATE_ARGS: &[&str] = &["--app", "--class", "--user-data-dir", "--profile"];
+
     pub fn is_owned(desktop_file_path: &Path) -> Result<bool> {
         let desktop_entry = DesktopEntry::from_path(desktop_file_path, None::<&[String]>)?;
         let is_owned = desktop_entry
@@ -58,15 +155,121 @@ impl DesktopFile {
         Ok(is_owned)
     }
 
+    /// Paths of the desktop files of web apps in the user applications, as they are on disk
+    pub fn get_owned_paths(app_dirs: &AppDirs) -> Vec<PathBuf> {
+        crate::utils::files::get_entries_in_dir(&app_dirs.user_applications)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|file| file.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "desktop")
+            })
+            .filter(|path| match Self::is_owned(path) {
+                Ok(is_owned) => is_owned,
+                Err(error) => {
+                    error!(path = %path.display(), "Failed to read desktop file: {error:?}");
+                    false
+                }
+            })
+            .collect()
+    }
+
+    /// The web apps sorted by name, ready to use: switched to a fallback browser when their
+    /// browser is uninstalled and migrated when they are of an older version
+    pub fn get_owned(
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+        fallback_browser_ids: &[String],
+    ) -> Vec<OwnedDesktopFile> {
+        debug!("Reading user desktop files");
+
+        let mut owned_desktop_files = Self::get_owned_paths(app_dirs)
+            .iter()
+            .filter_map(|path| {
+                Self::load_owned(path, browser_configs, app_dirs, fallback_browser_ids)
+            })
+            .collect::<Vec<_>>();
+        owned_desktop_files.sort_by_key(|owned| {
+            owned
+                .desktop_file
+                .get_name()
+                .unwrap_or(char::MAX.to_string())
+        });
+
+        owned_desktop_files
+    }
+
+    /// Same as `get_owned` for one desktop file, `None` when it is not of a web app
+    pub fn load_owned(
+        path: &Path,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+        fallback_browser_ids: &[String],
+    ) -> Option<OwnedDesktopFile> {
+        match Self::is_owned(path) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(error) => {
+                error!(path = %path.display(), "Failed to read desktop file: {error:?}");
+                return None;
+            }
+        }
+        let mut desktop_file = match Self::from_path(path, browser_configs, app_dirs) {
+            Ok(desktop_file) => desktop_file,
+            Err(error) => {
+                error!(path = %path.display(), "Failed to read desktop file: {error:?}");
+                return None;
+            }
+        };
+        let file_name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        debug!(file_name = &file_name, "Found desktop file");
+
+        let fallback_browser = desktop_file
+            .apply_browser_fallback(fallback_browser_ids)
+            .unwrap_or_else(|error| {
+                error!(
+                    error = error.to_string(),
+                    desktop_file = &file_name,
+                    "Failed to switch to a fallback browser"
+                );
+                None
+            });
+
+        let is_updated = match desktop_file.update() {
+            Ok(is_updated) => is_updated,
+            Err(error) => {
+                error!(
+                    error = error.to_string(),
+                    desktop_file = &file_name,
+                    "Failed to update 'DesktopFile'"
+                );
+                return None;
+            }
+        };
+        if is_updated {
+            debug!(file_name = &file_name, "Updated desktop file");
+        }
+
+        debug!(file_name = &file_name, "Checking paths");
+        desktop_file.check_paths();
+
+        Some(OwnedDesktopFile {
+            desktop_file,
+            fallback_browser,
+            is_updated,
+        })
+    }
+
     pub fn new(browser_configs: &Rc<BrowserConfigs>, app_dirs: &Rc<AppDirs>) -> Self {
         let mut desktop_entry = DesktopEntry::from_appid(String::new());
 
-        let random_id: String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(8)
-            .map(char::from)
-            .collect();
-        desktop_entry.add_desktop_entry(Key::Id.to_string(), random_id);
+        desktop_entry.add_desktop_entry(Key::Id.to_string(), Self::generate_id());
 
         let version = config::VERSION.get_value().clone();
         desktop_entry.add_desktop_entry(Key::Version.to_string(), version);
@@ -78,6 +281,14 @@ impl DesktopFile {
         }
     }
 
+    pub fn generate_id() -> String {
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect()
+    }
+
     pub fn from_path(
         path: &Path,
         browser_configs: &Rc<BrowserConfigs>,
@@ -145,8 +356,11 @@ impl DesktopFile {
     }
 
     pub fn set_name(&mut self, id: &str) {
+        let locale_map = get_locale_map(&self.desktop_entry, &Key::Name.to_string());
         self.desktop_entry
             .add_desktop_entry(Key::Name.to_string(), id.to_string());
+        // Adding an entry drops the translations
+        set_locale_map(&mut self.desktop_entry, &Key::Name.to_string(), locale_map);
 
         debug!(
             "Set '{}' on desktop file: {}",
@@ -184,6 +398,15 @@ impl DesktopFile {
             .and_then(map_to_string_option)
     }
 
+    /// Exec without field codes as a shell command, for launching without a uri or file
+    pub fn get_launch_exec(&self) -> Option<String> {
+        let exec = self.get_exec()?;
+
+        Self::exec_to_command(&exec)
+            .inspect_err(|error| error!("Invalid Exec on desktop file: {error:?}"))
+            .ok()
+    }
+
     pub fn get_id(&self) -> Option<String> {
         self.desktop_entry
             .desktop_entry(&Key::Id.to_string())
@@ -232,6 +455,15 @@ impl DesktopFile {
     }
 
     pub fn set_browser(&mut self, browser: &Rc<Browser>) {
+        // An app installed by a browser only exists in that browser
+        if self
+            .desktop_entry
+            .desktop_entry(&Key::BrowserId.to_string())
+            .is_some_and(|browser_id| !browser_id.is_empty() && browser_id != browser.id)
+        {
+            self.remove_browser_app();
+        }
+
         self.desktop_entry
             .add_desktop_entry(Key::BrowserId.to_string(), browser.id.clone());
 
@@ -284,6 +516,7 @@ impl DesktopFile {
         );
     }
 
+    #[cfg(feature = "gtk")]
     pub fn get_icon(&self) -> Image {
         let fallback_icon = "image-missing-symbolic";
         let icon_name = self.desktop_entry.icon().unwrap_or_default();
@@ -345,30 +578,138 @@ impl DesktopFile {
             .and_then(map_to_string_option)
     }
 
+    pub fn get_categories(&self) -> Vec<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::Categories.to_string())
+            .map(map_to_list)
+            .unwrap_or_default()
+    }
+
+    pub fn set_categories(&mut self, categories: &[String]) {
+        self.set_list(&Key::Categories, categories);
+    }
+
+    /// Main category for the menu, see `Category::from_categories`
+    pub fn get_main_category(&self) -> Option<Category> {
+        Category::from_categories(&self.get_categories())
+    }
+
+    /// Replaces the main category, other categories are kept
     pub fn set_category(&mut self, category: &Category) {
+        let old_main_category = self
+            .get_main_category()
+            .map(|category| category.to_string())
+            .unwrap_or_default();
+        let old_main_categories = map_to_list(&old_main_category);
+
+        let categories = map_to_list(&category.to_string())
+            .into_iter()
+            .chain(
+                self.get_categories()
+                    .into_iter()
+                    .filter(|name| !old_main_categories.contains(name)),
+            )
+            .collect::<Vec<_>>();
+
+        self.set_categories(&categories);
+    }
+
+    pub fn get_keywords(&self) -> Vec<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::Keywords.to_string())
+            .map(map_to_list)
+            .unwrap_or_default()
+    }
+
+    pub fn set_keywords(&mut self, keywords: &[String]) {
+        let locale_map = get_locale_map(&self.desktop_entry, &Key::Keywords.to_string());
+        self.set_list(&Key::Keywords, keywords);
+        // Adding an entry drops the translations
+        set_locale_map(
+            &mut self.desktop_entry,
+            &Key::Keywords.to_string(),
+            locale_map,
+        );
+    }
+
+    /// Enabled flags of the browser, the defaults of the browser config when never set
+    pub fn get_enabled_flags(&self) -> Vec<String> {
+        let Some(browser) = self.get_browser() else {
+            return Vec::new();
+        };
+        let saved_flags = self.get_saved_flags();
+
+        browser
+            .flags
+            .iter()
+            .filter(|flag| {
+                saved_flags
+                    .as_ref()
+                    .map_or(flag.default, |saved_flags| saved_flags.contains(&flag.name))
+            })
+            .map(|flag| flag.name.clone())
+            .collect()
+    }
+
+    pub fn set_enabled_flags(&mut self, flags: &[String]) {
+        self.set_list(&Key::Flags, flags);
+    }
+
+    fn get_saved_flags(&self) -> Option<Vec<String>> {
+        self.desktop_entry
+            .desktop_entry(&Key::Flags.to_string())
+            .map(map_to_list)
+    }
+
+    pub fn get_generic_name(&self) -> Option<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::GenericName.to_string())
+            .and_then(map_to_string_option)
+    }
+
+    pub fn set_generic_name(&mut self, generic_name: &str) {
+        let locale_map = get_locale_map(&self.desktop_entry, &Key::GenericName.to_string());
         self.desktop_entry
-            .add_desktop_entry(Key::Categories.to_string(), category.to_string());
+            .add_desktop_entry(Key::GenericName.to_string(), generic_name.to_string());
+        // Adding an entry drops the translations
+        set_locale_map(
+            &mut self.desktop_entry,
+            &Key::GenericName.to_string(),
+            locale_map,
+        );
 
         debug!(
             "Set '{}' on desktop file: {}",
-            &Key::Categories.to_string(),
+            &Key::GenericName.to_string(),
             &self
                 .desktop_entry
-                .desktop_entry(&Key::Categories.to_string())
+                .desktop_entry(&Key::GenericName.to_string())
                 .unwrap_or_default()
         );
     }
 
-    fn set_category_str(&mut self, category: &str) {
-        self.desktop_entry
-            .add_desktop_entry(Key::Categories.to_string(), category.to_string());
+    /// Write a `;` separated list without duplicates
+    fn set_list(&mut self, key: &Key, items: &[String]) {
+        let mut value = String::new();
+        let mut seen = HashSet::new();
+
+        for item in items {
+            let item = item.trim();
+            if item.is_empty() || !seen.insert(item) {
+                continue;
+            }
+            value.push_str(item);
+            value.push(';');
+        }
+
+        self.desktop_entry.add_desktop_entry(key.to_string(), value);
 
         debug!(
             "Set '{}' on desktop file: {}",
-            &Key::Categories.to_string(),
+            &key.to_string(),
             &self
                 .desktop_entry
-                .desktop_entry(&Key::Categories.to_string())
+                .desktop_entry(&key.to_string())
                 .unwrap_or_default()
         );
     }
@@ -380,8 +721,15 @@ impl DesktopFile {
     }
 
     pub fn set_description(&mut self, description: &str) {
+        let locale_map = get_locale_map(&self.desktop_entry, &Key::Comment.to_string());
         self.desktop_entry
             .add_desktop_entry(Key::Comment.to_string(), description.to_string());
+        // Adding an entry drops the translations
+        set_locale_map(
+            &mut self.desktop_entry,
+            &Key::Comment.to_string(),
+            locale_map,
+        );
 
         debug!(
             "Set '{}' on desktop file: {}",
@@ -393,331 +741,1635 @@ impl DesktopFile {
         );
     }
 
-    pub fn copy_profile_config_to_profile_path(&self, profile_path: &Path) -> Result<()> {
-        let browser = self.get_browser().context("No browser on 'DesktopFile'")?;
-
-        if !profile_path.is_dir() {
-            debug!(
-                path = profile_path.to_string_lossy().to_string(),
-                "Creating profile path"
-            );
-            fs::create_dir_all(profile_path).context(format!(
-                "Failed to create profile dir: {}",
-                profile_path.display()
-            ))?;
-        }
-
-        let copy_options = fs_extra::dir::CopyOptions {
-            overwrite: true,
-            content_only: true,
-            ..fs_extra::dir::CopyOptions::default()
-        };
-
-        let copy_profile_config = move |config_path: &PathBuf| -> Result<()> {
-            debug!(
-                config_path = config_path.display().to_string(),
-                profile_path = &profile_path.display().to_string(),
-                "Copying profile config"
-            );
-            if config_path.is_dir() {
-                fs_extra::dir::copy(config_path, profile_path, &copy_options)?;
-            }
-            Ok(())
-        };
-
-        let config_path = self
-            .app_dirs
-            .app_config
-            .join("profiles")
-            .join(&browser.config_name);
-        if config_path.is_dir() {
-            return copy_profile_config(&config_path);
-        }
+    /// Translated names as `locale => name`, written as `Name[locale]=`
+    pub fn get_localized_names(&self) -> BTreeMap<String, String> {
+        get_locale_map(&self.desktop_entry, &Key::Name.to_string())
+    }
 
-        match browser.base {
-            Base::Chromium => {
-                let config_path = self.app_dirs.app_config.join("profiles").join("chromium");
-                copy_profile_config(&config_path)
-            }
-            Base::Firefox => {
-                let config_path = self.app_dirs.app_config.join("profiles").join("firefox");
-                copy_profile_config(&config_path)
-            }
-            Base::None => Ok(()),
-        }
+    pub fn set_localized_names(&mut self, names: &BTreeMap<String, String>) {
+        self.set_localized(&Key::Name, names);
     }
 
-    pub fn build_profile_path(&self) -> Result<PathBuf> {
-        let browser = self.get_browser().context("No browser on 'DesktopFile'")?;
-        let is_isolated = self.get_isolated().unwrap_or(false);
+    /// Translated descriptions as `locale => description`, written as `Comment[locale]=`
+    pub fn get_localized_descriptions(&self) -> BTreeMap<String, String> {
+        get_locale_map(&self.desktop_entry, &Key::Comment.to_string())
+    }
 
-        if !is_isolated {
-            bail!("Isolate is not set")
-        }
-        if !browser.can_isolate {
-            bail!("Browser cannot isolate")
-        }
+    pub fn set_localized_descriptions(&mut self, descriptions: &BTreeMap<String, String>) {
+        self.set_localized(&Key::Comment, descriptions);
+    }
 
-        let id = self.get_id().context("No id on 'DesktopFile'")?;
-        let profile_path = browser.get_profile_path()?.join(&id);
+    /// Translated generic names as `locale => generic name`, written as `GenericName[locale]=`
+    pub fn get_localized_generic_names(&self) -> BTreeMap<String, String> {
+        get_locale_map(&self.desktop_entry, &Key::GenericName.to_string())
+    }
 
-        if !profile_path.is_dir() {
-            debug!(
-                path = profile_path.to_string_lossy().to_string(),
-                "Creating profile path"
-            );
-            fs::create_dir_all(&profile_path).context(format!(
-                "Failed to create profile dir: {}",
-                profile_path.display()
-            ))?;
-        }
+    pub fn set_localized_generic_names(&mut self, generic_names: &BTreeMap<String, String>) {
+        self.set_localized(&Key::GenericName, generic_names);
+    }
 
-        debug!("Using profile path: {}", &profile_path.display());
-        self.copy_profile_config_to_profile_path(&profile_path)?;
+    /// Translated keywords as `locale => keywords`, a `;` separated list written as
+    /// `Keywords[locale]=`
+    pub fn get_localized_keywords(&self) -> BTreeMap<String, String> {
+        get_locale_map(&self.desktop_entry, &Key::Keywords.to_string())
+    }
 
-        Ok(profile_path)
+    pub fn set_localized_keywords(&mut self, keywords: &BTreeMap<String, String>) {
+        self.set_localized(&Key::Keywords, keywords);
     }
 
-    pub fn validate(&self) -> Result<(), DesktopFileError> {
-        match self.to_new_from_browser() {
-            Err(error) => {
-                error!(
-                    validation_error = error.to_string(),
-                    "Invalid 'DesktopFile'"
-                );
-                Err(error)
-            }
-            Ok(_) => Ok(()),
-        }
+    /// Locale as in the desktop entry spec: `lang_COUNTRY.ENCODING@MODIFIER`
+    pub fn is_valid_locale(locale: &str) -> bool {
+        static LOCALE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^[a-z]{2,3}(_[A-Z]{2})?(\.[A-Za-z0-9-]+)?(@[A-Za-z0-9]+)?$")
+                .expect("Invalid locale regex")
+        });
+
+        LOCALE_REGEX.is_match(locale)
     }
 
-    pub fn save(&mut self) -> Result<(), DesktopFileError> {
-        let new_desktop_file = self.to_new_from_browser()?;
+    fn set_localized(&mut self, key: &Key, values: &BTreeMap<String, String>) {
+        let locale_map = values
+            .iter()
+            .filter(|(locale, value)| Self::is_valid_locale(locale) && !value.trim().is_empty())
+            .map(|(locale, value)| (locale.clone(), value.trim().to_string()))
+            .collect::<BTreeMap<_, _>>();
 
-        if self.desktop_entry.path.is_file() && !self.desktop_entry.path.is_symlink() {
-            match fs::remove_file(&self.desktop_entry.path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove desktop file before saving new: {error:?}");
-                }
-            }
-        }
+        set_locale_map(&mut self.desktop_entry, &key.to_string(), locale_map);
 
-        let save_path = new_desktop_file.desktop_entry.path.clone();
+        debug!(
+            "Set translations of '{}' on desktop file: {:?}",
+            &key.to_string(),
+            &get_locale_map(&self.desktop_entry, &key.to_string())
+        );
+    }
 
-        debug!("Saving desktop file to: {}", save_path.display());
-        fs::write(&save_path, new_desktop_file.desktop_entry.to_string())
-            .context("Saving desktop file")?;
-        self.desktop_entry = new_desktop_file.desktop_entry;
+    pub fn get_actions(&self) -> Vec<DesktopAction> {
+        let url_key = Key::Url.to_string();
 
-        Ok(())
+        self.desktop_entry
+            .desktop_entry(&Key::Actions.to_string())
+            .map(map_to_list)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| {
+                let mut action = DesktopAction {
+                    id,
+                    name: String::new(),
+                    url: String::new(),
+                };
+                let group_name = action.get_group_name();
+                action.name =
+                    get_group_entry(&self.desktop_entry, &group_name, "Name")?.to_string();
+                action.url =
+                    get_group_entry(&self.desktop_entry, &group_name, &url_key)?.to_string();
+                Some(action)
+            })
+            .collect()
     }
 
-    pub fn delete(&self) -> Result<()> {
-        let mut is_error = false;
+    pub fn set_actions(&mut self, actions: &[DesktopAction]) {
+        let url_key = Key::Url.to_string();
+        let mut ids = HashSet::new();
+        let mut actions_value = String::new();
 
-        if self.desktop_entry.path.is_file() {
-            match fs::remove_file(&self.desktop_entry.path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove desktop file: {error:?}");
-                    is_error = true;
-                }
-            }
-        }
+        remove_groups(&mut self.desktop_entry, DesktopAction::GROUP_PREFIX);
 
-        if let Some(icon_path) = self.get_icon_path()
-            && icon_path.is_file()
-        {
-            match fs::remove_file(icon_path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove icon file: {error:?}");
-                    is_error = true;
-                }
+        for action in actions {
+            let mut action = action.clone();
+            let mut count = 2;
+            while !ids.insert(action.id.clone()) {
+                action.id = format!("{}-{count}", DesktopAction::new(&action.name, "").id);
+                count += 1;
             }
-        }
 
-        if let Some(profile_path) = self.get_profile_path()
-            && Path::new(&profile_path).is_dir()
-        {
-            match fs::remove_dir_all(profile_path) {
-                Ok(()) => {}
-                Err(error) => {
-                    error!("Failed to remove profile: {error:?}");
-                    is_error = true;
-                }
-            }
+            let group_name = action.get_group_name();
+            add_group_entry(&mut self.desktop_entry, &group_name, "Name", &action.name);
+            add_group_entry(&mut self.desktop_entry, &group_name, &url_key, &action.url);
+            actions_value.push_str(&action.id);
+            actions_value.push(';');
         }
 
-        if is_error {
-            bail!("Some files could not be removed, check logs")
+        if actions_value.is_empty() {
+            remove_entry(&mut self.desktop_entry, &Key::Actions.to_string());
+        } else {
+            self.desktop_entry
+                .add_desktop_entry(Key::Actions.to_string(), actions_value);
         }
 
-        info!(
-            "Succesfully removed web app: {}",
-            self.get_name().unwrap_or_default()
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::Actions.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::Actions.to_string())
+                .unwrap_or_default()
         );
-        Ok(())
     }
 
-    /// Run update actions when app has been updated, returns true if actions have been applied
-    #[allow(clippy::collapsible_if)]
-    pub fn update(&mut self) -> Result<bool, DesktopFileError> {
-        let app_version =
-            Version::parse(config::VERSION.get_value()).context("Failed to get app version")?;
-        let desktop_file_version = match self.get_version() {
-            None => {
-                let version = Version::new(0, 0, 0);
-                self.set_version(&version);
-                version
-            }
-            Some(version) => version,
-        };
+    pub fn get_mime_types(&self) -> Vec<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::MimeType.to_string())
+            .map(map_to_list)
+            .unwrap_or_default()
+    }
 
-        if desktop_file_version < app_version {
-            info!(
-                "Older desktop file version detected, {} has been updated",
-                config::APP_NAME.get_value()
-            );
+    pub fn set_mime_types(&mut self, mime_types: &[String]) {
+        self.set_list(&Key::MimeType, mime_types);
+    }
 
-            if self.get_isolated().is_some()
-                && let Some(profile_path) = self.get_profile_path()
-            {
-                debug!(
-                    profile_path = profile_path.to_string_lossy().to_string(),
-                    "Updating profile config"
-                );
+    /// Parse user input like `mailto; tel:; text/calendar`, bare url schemes become
+    /// `x-scheme-handler/<scheme>`
+    pub fn parse_mime_types(value: &str) -> Vec<String> {
+        let mut mime_types = Vec::new();
 
-                self.copy_profile_config_to_profile_path(&profile_path)?;
+        for item in value.split([';', ',', ' ']) {
+            let item = item.trim().trim_end_matches(':').to_lowercase();
+            if item.is_empty() {
+                continue;
+            }
+            let mime_type = if item.contains('/') {
+                item
+            } else {
+                format!("{}{item}", Self::SCHEME_HANDLER_PREFIX)
+            };
+            if !mime_types.contains(&mime_type) {
+                mime_types.push(mime_type);
             }
-        } else {
-            return Ok(false);
         }
 
-        // if desktop_file_version <= Version::new(0, 0, 0) {
-
-        // }
+        mime_types
+    }
 
-        self.set_version(&app_version);
-        self.save()?;
-        Ok(true)
+    pub fn get_handler_url(&self) -> Option<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::HandlerUrl.to_string())
+            .and_then(map_to_string_option)
     }
 
-    /// Check paths, try to fix and print errors
-    pub fn check_paths(&self) {
-        let entries = match self.get_entries() {
-            Ok(entries) => entries,
-            Err(error) => {
-                error!(
-                    name = self.get_name().unwrap_or_default(),
-                    error = match &error {
-                        DesktopFileError::ValidationError(error) => {
-                            format!("Field: {}, Error: {}", error.field, error.message)
-                        }
-                        DesktopFileError::Other(error) => error.to_string(),
-                    },
-                    "Failed to get entries on 'DesktopFile'"
-                );
-                return;
+    pub fn set_handler_url(&mut self, handler_url: &str) {
+        self.desktop_entry
+            .add_desktop_entry(Key::HandlerUrl.to_string(), handler_url.to_string());
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::HandlerUrl.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::HandlerUrl.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    /// Id of the app installed by the browser, launched with `--app-id`
+    pub fn get_browser_app_id(&self) -> Option<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::BrowserAppId.to_string())
+            .and_then(map_to_string_option)
+    }
+
+    pub fn set_browser_app_id(&mut self, browser_app_id: &str) {
+        self.desktop_entry
+            .add_desktop_entry(Key::BrowserAppId.to_string(), browser_app_id.to_string());
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::BrowserAppId.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::BrowserAppId.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    /// Without the app installed by the browser, launched as a normal web app instead
+    fn remove_browser_app(&mut self) {
+        for key in [Key::BrowserAppId, Key::ProfileDirectory, Key::WmClass] {
+            remove_entry(&mut self.desktop_entry, &key.to_string());
+        }
+    }
+
+    /// Profile in the browser's own user data dir (e.g. `Default`)
+    pub fn get_profile_directory(&self) -> Option<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::ProfileDirectory.to_string())
+            .and_then(map_to_string_option)
+    }
+
+    pub fn set_profile_directory(&mut self, profile_directory: &str) {
+        self.desktop_entry.add_desktop_entry(
+            Key::ProfileDirectory.to_string(),
+            profile_directory.to_string(),
+        );
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::ProfileDirectory.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::ProfileDirectory.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    /// Window class that overrides the one of the template
+    pub fn get_wm_class(&self) -> Option<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::WmClass.to_string())
+            .and_then(map_to_string_option)
+    }
+
+    pub fn set_wm_class(&mut self, wm_class: &str) {
+        self.desktop_entry
+            .add_desktop_entry(Key::WmClass.to_string(), wm_class.to_string());
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::WmClass.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::WmClass.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    /// Browser ids to switch to when the browser is uninstalled, in order of preference
+    pub fn get_fallback_browsers(&self) -> Vec<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::FallbackBrowsers.to_string())
+            .map(map_to_list)
+            .unwrap_or_default()
+    }
+
+    pub fn set_fallback_browsers(&mut self, browser_ids: &[String]) {
+        if browser_ids.is_empty() {
+            remove_entry(&mut self.desktop_entry, &Key::FallbackBrowsers.to_string());
+            return;
+        }
+        self.set_list(&Key::FallbackBrowsers, browser_ids);
+    }
+
+    /// Extra arguments for the browser, saved in the quoting of Exec
+    pub fn get_extra_args(&self) -> Vec<String> {
+        self.get_exec_list(&Key::ExtraArgs, Self::validate_extra_arg)
+    }
+
+    pub fn set_extra_args(&mut self, extra_args: &[String]) -> Result<(), DesktopFileError> {
+        for arg in extra_args {
+            Self::validate_extra_arg(arg)?;
+        }
+        self.set_exec_list(&Key::ExtraArgs, extra_args.to_vec())
+    }
+
+    /// Split extra arguments as typed by the user, quoted the same as in Exec
+    /// (e.g. `--disable-gpu "--user-agent=Foo Bar"`)
+    pub fn parse_extra_args(value: &str) -> Result<Vec<String>, ValidationError> {
+        Self::parse_exec_list(Key::ExtraArgs, value, Self::validate_extra_arg)
+    }
+
+    /// Extra arguments that conflict with the ones of the template (e.g. `--app=...`)
+    pub fn get_conflicting_args(extra_args: &[String]) -> Vec<String> {
+        extra_args
+            .iter()
+            .filter(|arg| {
+                let name = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+                Self::TEMPLATE_ARGS.contains(&name)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Every argument reaches the browser as is: no field codes, which the launcher
+    /// replaces, and no control characters
+    fn validate_extra_arg(arg: &str) -> Result<String, ValidationError> {
+        let message = if arg.is_empty() {
+            "Empty argument".to_string()
+        } else if exec::is_field_code(arg) {
+            format!("Field code '{arg}' is not allowed")
+        } else if arg.chars().any(char::is_control) {
+            format!("Control character in '{}'", arg.escape_debug())
+        } else {
+            return Ok(arg.to_string());
+        };
+
+        Err(ValidationError {
+            field: Key::ExtraArgs,
+            message,
+        })
+    }
+
+    /// Environment variables for the browser, saved as `NAME=value` in the quoting of Exec
+    pub fn get_env_vars(&self) -> Vec<(String, String)> {
+        self.get_exec_list(&Key::Env, Self::parse_env_var)
+    }
+
+    pub fn set_env_vars(&mut self, env_vars: &[(String, String)]) -> Result<(), DesktopFileError> {
+        for (name, value) in env_vars {
+            Self::validate_env_var(name, value)?;
+        }
+        self.set_exec_list(&Key::Env, Self::to_assignments(env_vars))
+    }
+
+    /// Split environment variables as typed by the user, `NAME=value` quoted the same
+    /// as in Exec (e.g. `MOZ_ENABLE_WAYLAND=1 "GTK_THEME=Adwaita:dark"`)
+    pub fn parse_env_vars(value: &str) -> Result<Vec<(String, String)>, ValidationError> {
+        Self::parse_exec_list(Key::Env, value, Self::parse_env_var)
+    }
+
+    fn parse_env_var(assignment: &str) -> Result<(String, String), ValidationError> {
+        let (name, value) = Self::split_assignment(Key::Env, assignment, "NAME=value")?;
+        Self::validate_env_var(name, value)?;
+
+        Ok((name.to_string(), value.to_string()))
+    }
+
+    /// A name like the shell accepts and a value without control characters
+    fn validate_env_var(name: &str, value: &str) -> Result<(), ValidationError> {
+        let is_valid_name = name
+            .chars()
+            .next()
+            .is_some_and(|char| char.is_ascii_alphabetic() || char == '_')
+            && name
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '_');
+
+        let message = if !is_valid_name {
+            format!("Invalid variable name '{name}'")
+        } else if value.chars().any(char::is_control) {
+            format!("Control character in the value of '{name}'")
+        } else {
+            return Ok(());
+        };
+
+        Err(ValidationError {
+            field: Key::Env,
+            message,
+        })
+    }
+
+    /// User agent that overrides the one of the browser
+    pub fn get_user_agent(&self) -> Option<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::UserAgent.to_string())
+            .and_then(map_to_string_option)
+    }
+
+    pub fn set_user_agent(&mut self, user_agent: &str) {
+        let user_agent = user_agent.trim();
+        if user_agent.is_empty() {
+            remove_entry(&mut self.desktop_entry, &Key::UserAgent.to_string());
+            return;
+        }
+        self.desktop_entry
+            .add_desktop_entry(Key::UserAgent.to_string(), user_agent.to_string());
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::UserAgent.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::UserAgent.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    pub fn get_user_agent_support(&self) -> SettingSupport {
+        self.get_setting_support(true)
+    }
+
+    /// Without a mode the browser uses its own proxy settings
+    pub fn get_proxy_mode(&self) -> Option<ProxyMode> {
+        self.desktop_entry
+            .desktop_entry(&Key::ProxyMode.to_string())
+            .and_then(ProxyMode::from_string)
+    }
+
+    pub fn set_proxy_mode(&mut self, proxy_mode: Option<ProxyMode>) {
+        let Some(proxy_mode) = proxy_mode else {
+            remove_entry(&mut self.desktop_entry, &Key::ProxyMode.to_string());
+            return;
+        };
+        self.desktop_entry
+            .add_desktop_entry(Key::ProxyMode.to_string(), proxy_mode.to_string());
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::ProxyMode.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::ProxyMode.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    /// Server of a manual proxy mode, `host:port`
+    pub fn get_proxy_server(&self) -> Option<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::ProxyServer.to_string())
+            .and_then(map_to_string_option)
+    }
+
+    pub fn set_proxy_server(&mut self, proxy_server: &str) -> Result<(), DesktopFileError> {
+        let proxy_server = proxy_server.trim();
+        if proxy_server.is_empty() {
+            remove_entry(&mut self.desktop_entry, &Key::ProxyServer.to_string());
+            return Ok(());
+        }
+        Self::parse_proxy_server(proxy_server)?;
+
+        self.desktop_entry
+            .add_desktop_entry(Key::ProxyServer.to_string(), proxy_server.to_string());
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &Key::ProxyServer.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&Key::ProxyServer.to_string())
+                .unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    /// Split `host:port` of a proxy server, an IPv6 host is in brackets (e.g. `[::1]:8080`)
+    pub fn parse_proxy_server(value: &str) -> Result<(String, u16), ValidationError> {
+        let invalid = || ValidationError {
+            field: Key::ProxyServer,
+            message: format!("Expected host:port instead of '{value}'"),
+        };
+
+        // A scheme without default port, so the port is always kept
+        let url = Url::parse(&format!("proxy://{value}")).map_err(|_| invalid())?;
+        let (Some(host), Some(port)) = (url.host_str(), url.port()) else {
+            return Err(invalid());
+        };
+        if !url.username().is_empty()
+            || url.password().is_some()
+            || !url.path().is_empty()
+            || url.query().is_some()
+            || url.fragment().is_some()
+        {
+            return Err(invalid());
+        }
+
+        Ok((host.to_string(), port))
+    }
+
+    /// Hosts that connect without the proxy of a manual mode (e.g. `localhost` or `*.lan`)
+    pub fn get_proxy_bypass(&self) -> Vec<String> {
+        self.desktop_entry
+            .desktop_entry(&Key::ProxyBypass.to_string())
+            .map(map_to_list)
+            .unwrap_or_default()
+    }
+
+    pub fn set_proxy_bypass(&mut self, hosts: &[String]) {
+        if hosts.is_empty() {
+            remove_entry(&mut self.desktop_entry, &Key::ProxyBypass.to_string());
+            return;
+        }
+        self.set_list(&Key::ProxyBypass, hosts);
+    }
+
+    /// The browser config lists the modes of a browser
+    pub fn get_proxy_support(&self, proxy_mode: ProxyMode) -> SettingSupport {
+        let Some(browser) = self.get_browser() else {
+            return SettingSupport::Unsupported;
+        };
+        if !browser.proxy_modes.contains(&proxy_mode) {
+            return SettingSupport::Unsupported;
+        }
+
+        self.get_setting_support(true)
+    }
+
+    /// The proxy when its mode is supported, a manual mode with its server
+    fn get_supported_proxy(&self) -> Option<Proxy> {
+        let mode = self
+            .get_proxy_mode()
+            .filter(|mode| self.get_proxy_support(*mode) == SettingSupport::Supported)?;
+        let server = self
+            .get_proxy_server()
+            .and_then(|server| Self::parse_proxy_server(&server).ok());
+        if mode.is_manual() && server.is_none() {
+            return None;
+        }
+
+        Some(Proxy {
+            mode,
+            server: server.filter(|_| mode.is_manual()),
+            bypass: if mode.is_manual() {
+                self.get_proxy_bypass()
+            } else {
+                Vec::new()
+            },
+        })
+    }
+
+    /// Prefs set by the user, saved as `name=value` in the quoting of Exec. The value is
+    /// kept as typed, see `PrefValue::parse`.
+    pub fn get_firefox_prefs(&self) -> Vec<(String, String)> {
+        self.get_exec_list(&Key::FirefoxPrefs, Self::parse_firefox_pref)
+    }
+
+    pub fn set_firefox_prefs(
+        &mut self,
+        prefs: &[(String, String)],
+    ) -> Result<(), DesktopFileError> {
+        for (name, value) in prefs {
+            Self::validate_firefox_pref(name, value)?;
+        }
+        self.set_exec_list(&Key::FirefoxPrefs, Self::to_assignments(prefs))
+    }
+
+    /// Split prefs as typed by the user, `name=value` quoted the same as in Exec
+    /// (e.g. `media.peerconnection.enabled=false "browser.startup.homepage=https://..."`)
+    pub fn parse_firefox_prefs(value: &str) -> Result<Vec<(String, String)>, ValidationError> {
+        Self::parse_exec_list(Key::FirefoxPrefs, value, Self::parse_firefox_pref)
+    }
+
+    fn parse_firefox_pref(assignment: &str) -> Result<(String, String), ValidationError> {
+        let (name, value) = Self::split_assignment(Key::FirefoxPrefs, assignment, "name=value")?;
+        Self::validate_firefox_pref(name, value)?;
+
+        Ok((name.to_string(), value.to_string()))
+    }
+
+    /// A name without whitespace (e.g. `media.eme.enabled`) and a value without control
+    /// characters
+    fn validate_firefox_pref(name: &str, value: &str) -> Result<(), ValidationError> {
+        let message = if name.is_empty() {
+            "Empty pref name".to_string()
+        } else if name
+            .chars()
+            .any(|char| char.is_whitespace() || char.is_control() || char == '=')
+        {
+            format!("Invalid pref name '{}'", name.escape_debug())
+        } else if value.chars().any(char::is_control) {
+            format!("Control character in the value of '{name}'")
+        } else {
+            return Ok(());
+        };
+
+        Err(ValidationError {
+            field: Key::FirefoxPrefs,
+            message,
+        })
+    }
+
+    pub fn get_firefox_prefs_support(&self) -> SettingSupport {
+        self.get_setting_support(false)
+    }
+
+    /// Support of a setting by the browser of the web app, `is_chromium_flag` when
+    /// Chromium takes it as a flag
+    fn get_setting_support(&self, is_chromium_flag: bool) -> SettingSupport {
+        match self.get_browser().map(|browser| browser.base) {
+            Some(Base::Chromium) if is_chromium_flag => SettingSupport::Supported,
+            Some(Base::Firefox) if self.get_isolated().unwrap_or(false) => {
+                SettingSupport::Supported
+            }
+            Some(Base::Firefox) => SettingSupport::NeedsIsolation,
+            Some(Base::Chromium | Base::None) | None => SettingSupport::Unsupported,
+        }
+    }
+
+    /// Values of a key saved in the quoting of Exec, invalid values are left out
+    fn get_exec_list<T>(
+        &self,
+        key: &Key,
+        parse_value: impl Fn(&str) -> Result<T, ValidationError>,
+    ) -> Vec<T> {
+        self.desktop_entry
+            .desktop_entry(&key.to_string())
+            .and_then(map_to_string_option)
+            .and_then(|values| exec::parse(&values).ok())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|value| parse_value(value).ok())
+            .collect()
+    }
+
+    fn set_exec_list(&mut self, key: &Key, values: Vec<String>) -> Result<(), DesktopFileError> {
+        if values.is_empty() {
+            remove_entry(&mut self.desktop_entry, &key.to_string());
+            return Ok(());
+        }
+
+        let value = exec::build(&[exec::Part::Args(values)])?;
+        self.desktop_entry.add_desktop_entry(key.to_string(), value);
+
+        debug!(
+            "Set '{}' on desktop file: {}",
+            &key.to_string(),
+            &self
+                .desktop_entry
+                .desktop_entry(&key.to_string())
+                .unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    /// Split a list as typed by the user, quoted the same as in Exec
+    fn parse_exec_list<T>(
+        field: Key,
+        value: &str,
+        parse_value: impl Fn(&str) -> Result<T, ValidationError>,
+    ) -> Result<Vec<T>, ValidationError> {
+        exec::parse(value)
+            .map_err(|error| ValidationError {
+                field,
+                message: error.to_string(),
+            })?
+            .iter()
+            .map(|value| parse_value(value))
+            .collect()
+    }
+
+    fn split_assignment<'a>(
+        field: Key,
+        assignment: &'a str,
+        expected: &str,
+    ) -> Result<(&'a str, &'a str), ValidationError> {
+        assignment.split_once('=').ok_or_else(|| ValidationError {
+            field,
+            message: format!("Expected {expected} instead of '{assignment}'"),
+        })
+    }
+
+    fn to_assignments(pairs: &[(String, String)]) -> Vec<String> {
+        pairs
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect()
+    }
+
+    /// Prefs of this web app for Firefox, which reads them from the `user.js` of the
+    /// profile. Only an isolated profile is of the web app alone, so only it gets them.
+    /// A pref set by the user wins over the same pref of another setting.
+    fn get_profile_prefs(&self) -> Vec<Pref> {
+        let mut prefs = Vec::new();
+
+        if let Some(user_agent) = self.get_user_agent() {
+            prefs.push(Pref::new(
+                "general.useragent.override",
+                PrefValue::String(user_agent),
+            ));
+        }
+
+        if let Some(proxy) = self.get_supported_proxy() {
+            // 0 is no proxy, 1 is manual and 5 is the system proxy
+            let proxy_type = match proxy.mode {
+                ProxyMode::None => 0,
+                ProxyMode::System => 5,
+                ProxyMode::Http | ProxyMode::Socks => 1,
+            };
+            prefs.push(Pref::new("network.proxy.type", PrefValue::Int(proxy_type)));
+
+            if let Some((host, port)) = proxy.server {
+                // Firefox wants an IPv6 host without brackets
+                let host = PrefValue::String(host.trim_matches(['[', ']']).to_string());
+                let port = PrefValue::Int(port.into());
+
+                if proxy.mode == ProxyMode::Http {
+                    prefs.push(Pref::new("network.proxy.http", host.clone()));
+                    prefs.push(Pref::new("network.proxy.http_port", port.clone()));
+                    prefs.push(Pref::new("network.proxy.ssl", host));
+                    prefs.push(Pref::new("network.proxy.ssl_port", port));
+                } else {
+                    prefs.push(Pref::new("network.proxy.socks", host));
+                    prefs.push(Pref::new("network.proxy.socks_port", port));
+                    prefs.push(Pref::new("network.proxy.socks_version", PrefValue::Int(5)));
+                    prefs.push(Pref::new(
+                        "network.proxy.socks_remote_dns",
+                        PrefValue::Bool(true),
+                    ));
+                }
+            }
+
+            if !proxy.bypass.is_empty() {
+                prefs.push(Pref::new(
+                    "network.proxy.no_proxies_on",
+                    PrefValue::String(proxy.bypass.join(", ")),
+                ));
+            }
+        }
+
+        for (name, value) in self.get_firefox_prefs() {
+            prefs.retain(|pref| pref.name != name);
+            prefs.push(Pref::new(&name, PrefValue::parse(&value)));
+        }
+
+        prefs
+    }
+
+    /// Write the prefs of this web app in the `user.js` of its profile, the other prefs
+    /// of `user.js` are left alone
+    fn update_profile_prefs_in(&self, transaction: &mut Transaction) -> Result<()> {
+        let Some(profile_path) = self.get_profile_path() else {
+            return Ok(());
+        };
+        let user_js = fs::read_to_string(profile_path.join("user.js")).unwrap_or_default();
+
+        self.write_profile_prefs(&profile_path, &user_js, transaction)
+    }
+
+    /// `previous_user_js` has the prefs that were written before, a pref that is gone
+    /// is also removed from `prefs.js` where Firefox keeps it
+    fn write_profile_prefs(
+        &self,
+        profile_path: &Path,
+        previous_user_js: &str,
+        transaction: &mut Transaction,
+    ) -> Result<()> {
+        let is_firefox_profile = self
+            .get_browser()
+            .is_some_and(|browser| browser.base == Base::Firefox);
+        if !is_firefox_profile || !self.get_isolated().unwrap_or(false) || !profile_path.is_dir() {
+            return Ok(());
+        }
+
+        let prefs = self.get_profile_prefs();
+        let user_js_path = profile_path.join("user.js");
+        let user_js = fs::read_to_string(&user_js_path).unwrap_or_default();
+        let merged_user_js = user_js::merge(&user_js, &prefs);
+
+        if merged_user_js != user_js {
+            debug!(path = %user_js_path.display(), "Writing prefs of web app");
+            transaction
+                .write(&user_js_path, merged_user_js.as_bytes())
+                .context("Failed to write user.js")?;
+        }
+
+        let removed_names = user_js::get_block_names(previous_user_js)
+            .into_iter()
+            .filter(|name| !prefs.iter().any(|pref| pref.name == *name))
+            .collect::<Vec<_>>();
+        let prefs_js_path = profile_path.join("prefs.js");
+
+        if !removed_names.is_empty() && prefs_js_path.is_file() {
+            debug!(names = ?removed_names, "Removing prefs of web app from prefs.js");
+            let prefs_js = fs::read_to_string(&prefs_js_path).context(format!(
+                "Failed to read prefs.js: {}",
+                prefs_js_path.display()
+            ))?;
+            transaction
+                .write(
+                    &prefs_js_path,
+                    user_js::remove_prefs(&prefs_js, &removed_names).as_bytes(),
+                )
+                .context("Failed to write prefs.js")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_handler(&self) -> bool {
+        !self.get_mime_types().is_empty()
+    }
+
+    /// Map a handled uri (e.g. `mailto:someone@example.com`) to the url to open.
+    /// Works like `protocol_handlers` in a web app manifest, `%s` is replaced with the encoded uri.
+    pub fn get_handled_url(&self, uri: &str) -> Result<String> {
+        if let Some(handler_url) = self.get_handler_url() {
+            let encoded_uri = url::form_urlencoded::byte_serialize(uri.as_bytes())
+                .collect::<String>()
+                .replace('+', "%20");
+            return Ok(handler_url.replace(Self::HANDLER_URL_PLACEHOLDER, &encoded_uri));
+        }
+
+        let url = Url::parse(uri).context(format!("Invalid uri: {uri}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("No handler url to open: {uri}")
+        }
+
+        Ok(url.to_string())
+    }
+
+    /// The Exec of a handler runs this app with `%u`, this is the browser command for the uri
+    pub fn get_handler_exec(&self, uri: Option<&str>) -> Result<String, DesktopFileError> {
+        let entries = &self.get_entries()?;
+        let url = match uri.filter(|uri| !uri.is_empty()) {
+            Some(uri) => self.get_handled_url(uri)?,
+            None => entries.url.clone(),
+        };
+        let exec = self.get_exec_from_template(entries, &url, &Key::Exec.to_string())?;
+
+        Ok(Self::exec_to_command(&exec)?)
+    }
+
+    /// A shell command of an Exec, without field codes
+    fn exec_to_command(exec: &str) -> Result<String> {
+        let command = exec::parse(exec)?
+            .iter()
+            .filter(|arg| !exec::is_field_code(arg))
+            .map(|arg| crate::utils::command::quote_arg(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(command)
+    }
+
+    pub fn set_as_default_handler(&self) -> Result<()> {
+        let path = self.get_path();
+        let file_name = path
+            .file_name()
+            .context("Desktop file is not saved")?
+            .to_string_lossy();
+        let mime_types = self.get_mime_types();
+
+        if !path.is_file() {
+            bail!("Desktop file is not saved")
+        }
+        if mime_types.is_empty() {
+            bail!("No MIME types to handle")
+        }
+
+        for mime_type in mime_types {
+            let response = crate::utils::command::run_command_sync(&format!(
+                "xdg-mime default {file_name} {mime_type}"
+            ))?;
+            if !response.success {
+                bail!(
+                    "Failed to set default for '{mime_type}': {}",
+                    response.stderr
+                )
+            }
+            info!("Set '{file_name}' as default for '{mime_type}'");
+        }
+
+        Ok(())
+    }
+
+    /// Copy the profile config of the browser into the profile, replaced files are restored
+    /// when the transaction rolls back
+    pub fn copy_profile_config_to_profile_path(
+        &self,
+        profile_path: &Path,
+        transaction: &mut Transaction,
+    ) -> Result<()> {
+        let browser = self.get_browser().context("No browser on 'DesktopFile'")?;
+
+        if !profile_path.is_dir() {
+            debug!(
+                path = profile_path.to_string_lossy().to_string(),
+                "Creating profile path"
+            );
+            transaction.create_dir_all(profile_path).context(format!(
+                "Failed to create profile dir: {}",
+                profile_path.display()
+            ))?;
+        }
+
+        // Copying the config replaces `user.js`
+        let previous_user_js = fs::read_to_string(profile_path.join("user.js")).unwrap_or_default();
+
+        let profiles_config_path = self.app_dirs.app_config.join("profiles");
+        let config_path = profiles_config_path.join(&browser.config_name);
+        let config_path = if config_path.is_dir() {
+            Some(config_path)
+        } else {
+            match browser.base {
+                Base::Chromium => Some(profiles_config_path.join("chromium")),
+                Base::Firefox => Some(profiles_config_path.join("firefox")),
+                Base::None => None,
+            }
+        };
+
+        if let Some(config_path) = config_path
+            && config_path.is_dir()
+        {
+            debug!(
+                config_path = config_path.display().to_string(),
+                profile_path = &profile_path.display().to_string(),
+                "Copying profile config"
+            );
+            transaction.copy_dir(&config_path, profile_path)?;
+        }
+
+        self.write_profile_prefs(profile_path, &previous_user_js, transaction)
+    }
+
+    pub fn build_profile_path(&self) -> Result<PathBuf> {
+        let mut transaction = Transaction::new();
+        let profile_path = self.build_profile_path_in(&mut transaction)?;
+        transaction.commit();
+
+        Ok(profile_path)
+    }
+
+    /// Same as `build_profile_path`, a new profile dir is removed when the transaction rolls back
+    pub fn build_profile_path_in(&self, transaction: &mut Transaction) -> Result<PathBuf> {
+        let browser = self.get_browser().context("No browser on 'DesktopFile'")?;
+        let is_isolated = self.get_isolated().unwrap_or(false);
+
+        if !is_isolated {
+            bail!("Isolate is not set")
+        }
+        if !browser.can_isolate {
+            bail!("Browser cannot isolate")
+        }
+
+        let id = self.get_id().context("No id on 'DesktopFile'")?;
+        let profile_path = browser.get_profile_path()?.join(&id);
+
+        if !profile_path.is_dir() {
+            debug!(
+                path = profile_path.to_string_lossy().to_string(),
+                "Creating profile path"
+            );
+            transaction.create_dir_all(&profile_path).context(format!(
+                "Failed to create profile dir: {}",
+                profile_path.display()
+            ))?;
+        }
+
+        debug!("Using profile path: {}", &profile_path.display());
+        self.copy_profile_config_to_profile_path(&profile_path, transaction)?;
+
+        Ok(profile_path)
+    }
+
+    pub fn validate(&self) -> Result<(), DesktopFileError> {
+        match self.to_new_from_browser() {
+            Err(error) => {
+                error!(
+                    validation_error = error.to_string(),
+                    "Invalid 'DesktopFile'"
+                );
+                Err(error)
+            }
+            Ok(_) => Ok(()),
+        }
+    }
+
+    pub fn save(&mut self) -> Result<(), DesktopFileError> {
+        let mut transaction = Transaction::new();
+        let saved = self.save_in(&mut transaction)?;
+        transaction.commit();
+        *self = saved;
+
+        Ok(())
+    }
+
+    /// Same as `save`, the previous desktop file is restored when the transaction rolls back.
+    /// Returns the saved desktop file, which replaces this one once the transaction is committed.
+    pub fn save_in(&self, transaction: &mut Transaction) -> Result<Self, DesktopFileError> {
+        let new_desktop_file = self.to_new_from_browser()?;
+        let save_path = new_desktop_file.desktop_entry.path.clone();
+
+        debug!("Saving desktop file to: {}", save_path.display());
+        transaction
+            .write(&save_path, new_desktop_file.to_string().as_bytes())
+            .context("Saving desktop file")?;
+
+        let old_path = &self.desktop_entry.path;
+        if *old_path != save_path && old_path.is_file() && !old_path.is_symlink() {
+            transaction
+                .remove(old_path)
+                .context("Failed to remove old desktop file")?;
+        }
+
+        // Also on an update, when the profile config can have been copied again
+        if let Err(error) = new_desktop_file.update_profile_prefs_in(transaction) {
+            error!("Failed to update prefs of profile: {error:?}");
+        }
+
+        if new_desktop_file.is_handler() {
+            // Makes the app show up as handler for its MIME types
+            let command = format!(
+                "update-desktop-database {}",
+                self.app_dirs.user_applications.display()
+            );
+            transaction.on_commit(move || {
+                if let Err(error) = crate::utils::command::run_command_background(&command) {
+                    error!("Failed to update desktop database: {error:?}");
+                }
+            });
+        }
+
+        Ok(new_desktop_file)
+    }
+
+    /// Saved copy with a new id, icon and profile, e.g. for a second account.
+    /// The profile of the original is copied when `copy_profile` is set.
+    pub fn duplicate(&self, name: &str, copy_profile: bool) -> Result<Self, DesktopFileError> {
+        let mut duplicate = self.clone();
+        let id = Self::generate_id();
+
+        duplicate.set_id(&id);
+        duplicate.set_name(name);
+        // Translations are of the original name
+        duplicate.set_localized_names(&BTreeMap::new());
+        // Saving removes the desktop file on the old path
+        duplicate.set_path(Path::new(""));
+        // The app installed by the browser is the original's, it can't be launched twice
+        duplicate.remove_browser_app();
+
+        // Icon, profile and desktop file are rolled back when one of them fails
+        let mut transaction = Transaction::new();
+
+        // Not shared, deleting one would delete the icon of the other
+        if let Some(icon_path) = self.get_icon_path()
+            && icon_path.is_file()
+        {
+            duplicate.copy_icon_in(&icon_path, &mut transaction)?;
+        }
+
+        if duplicate.get_isolated().unwrap_or(false) {
+            let profile_path = duplicate.build_profile_path_in(&mut transaction)?;
+
+            if copy_profile
+                && let Some(source_profile_path) = self.get_profile_path()
+                && source_profile_path.is_dir()
+            {
+                Self::copy_profile(&source_profile_path, &profile_path, &mut transaction)?;
+            }
+
+            duplicate.set_profile_path(&profile_path);
+        }
+
+        let duplicate = duplicate.save_in(&mut transaction)?;
+        transaction.commit();
+
+        info!(
+            name,
+            id,
+            original = self.get_name().unwrap_or_default(),
+            "Duplicated web app"
+        );
+        Ok(duplicate)
+    }
+
+    /// Copy an icon file to the icons of this app and use it, removed when the transaction rolls back
+    pub fn copy_icon_in(&mut self, icon_path: &Path, transaction: &mut Transaction) -> Result<()> {
+        let id = self.get_id().context("No id on 'DesktopFile'")?;
+        let extension = icon_path
+            .extension()
+            .map_or("png".to_string(), |extension| {
+                extension.to_string_lossy().to_lowercase()
+            });
+        let file_name = sanitize_filename::sanitize(format!("{id}.{extension}"));
+        let save_path = self.app_dirs.app_data_icons.join(file_name);
+
+        transaction
+            .write_with(&save_path, |temp_path| {
+                fs::copy(icon_path, temp_path)
+                    .map(|_| ())
+                    .map_err(Into::into)
+            })
+            .context("Failed to copy icon")?;
+        self.set_icon_path(&save_path);
+
+        Ok(())
+    }
+
+    /// An isolated profile exists, its data is lost when the profile path changes
+    pub fn has_profile(&self) -> bool {
+        self.get_isolated().unwrap_or(false)
+            && self
+                .get_profile_path()
+                .is_some_and(|profile_path| profile_path.is_dir())
+    }
+
+    /// The profile can be carried over to `browser`, only browsers with the same engine
+    /// read each others profile (e.g. Chromium as Flatpak and system package)
+    pub fn can_migrate_profile_to(&self, browser: &Browser) -> bool {
+        self.get_browser().is_some_and(|current_browser| {
+            current_browser.id != browser.id
+                && current_browser.base != Base::None
+                && current_browser.base == browser.base
+                && browser.can_isolate
+        })
+    }
+
+    /// Point the profile path to the current browser, or clear it when not isolated.
+    /// A profile on the old path is carried over or removed according to `migration`.
+    pub fn update_profile_in(
+        &mut self,
+        is_isolated: bool,
+        migration: ProfileMigration,
+        transaction: &mut Transaction,
+    ) -> Result<()> {
+        let old_profile_path = self.get_profile_path().unwrap_or_default();
+        let new_profile_path = if is_isolated {
+            self.build_profile_path_in(transaction)?
+        } else {
+            PathBuf::default()
+        };
+
+        if old_profile_path != new_profile_path && old_profile_path.is_dir() {
+            if is_isolated && matches!(migration, ProfileMigration::Move | ProfileMigration::Copy) {
+                info!(
+                    from = old_profile_path.display().to_string(),
+                    to = new_profile_path.display().to_string(),
+                    "Migrating profile"
+                );
+                Self::copy_profile(&old_profile_path, &new_profile_path, transaction)?;
+                // The config of the new browser wins over the copied one
+                self.copy_profile_config_to_profile_path(&new_profile_path, transaction)?;
+            }
+
+            if matches!(
+                migration,
+                ProfileMigration::Move | ProfileMigration::Discard
+            ) {
+                debug!(
+                    path = old_profile_path.display().to_string(),
+                    "Deleting profile"
+                );
+                transaction
+                    .remove(&old_profile_path)
+                    .context("Failed to remove old profile")?;
+            }
+        }
+
+        self.set_profile_path(&new_profile_path);
+        Ok(())
+    }
+
+    /// Switch a web app of which the browser is uninstalled to the first installed browser of
+    /// its own fallback chain, or of `fallback_browser_ids` when it has none. An isolated web app
+    /// only switches to a browser that can isolate, so its data never ends up in a shared
+    /// profile. The web app is saved with a new Exec, the new browser is returned when it was
+    /// switched.
+    pub fn apply_browser_fallback(
+        &mut self,
+        fallback_browser_ids: &[String],
+    ) -> Result<Option<Rc<Browser>>, DesktopFileError> {
+        let Some(browser_id) = self
+            .desktop_entry
+            .desktop_entry(&Key::BrowserId.to_string())
+            .and_then(map_to_string_option)
+        else {
+            return Ok(None);
+        };
+        if self.get_browser().is_some() {
+            return Ok(None);
+        }
+
+        let own_fallback_browser_ids = self.get_fallback_browsers();
+        let fallback_browser_ids = if own_fallback_browser_ids.is_empty() {
+            fallback_browser_ids
+        } else {
+            &own_fallback_browser_ids
+        };
+        let is_isolated = self.get_isolated().unwrap_or(false);
+        let Some(browser) = fallback_browser_ids.iter().find_map(|fallback_browser_id| {
+            self.browser_configs
+                .get_by_id(fallback_browser_id)
+                .filter(|browser| browser.is_installed() && (browser.can_isolate || !is_isolated))
+        }) else {
+            return Ok(None);
+        };
+
+        // The profile of the uninstalled browser stays, it could be installed again
+        let migration = if self.browser_configs.get_base_by_id(&browser_id) == Some(browser.base) {
+            ProfileMigration::Copy
+        } else {
+            ProfileMigration::Keep
+        };
+
+        let mut transaction = Transaction::new();
+        self.set_browser(&browser);
+        if is_isolated {
+            self.update_profile_in(true, migration, &mut transaction)?;
+        } else {
+            self.set_profile_path(Path::new(""));
+        }
+        let saved = self.save_in(&mut transaction)?;
+        transaction.commit();
+        *self = saved;
+
+        info!(
+            name = self.get_name().unwrap_or_default(),
+            from = browser_id,
+            to = browser.id,
+            "Switched web app to fallback browser"
+        );
+        Ok(Some(browser))
+    }
+
+    /// Copy the contents of a profile, without the locks of a running browser.
+    /// Replaced files are restored when the transaction rolls back.
+    pub fn copy_profile(from: &Path, to: &Path, transaction: &mut Transaction) -> Result<()> {
+        // Locks of a running browser would make the copy look in use
+        const LOCK_FILES: &[&str] = &[
+            "SingletonLock",
+            "SingletonSocket",
+            "SingletonCookie",
+            "lock",
+            ".parentlock",
+        ];
+
+        transaction.copy_dir(from, to).context(format!(
+            "Failed to copy profile '{}' to '{}'",
+            from.display(),
+            to.display()
+        ))?;
+
+        for lock_file in LOCK_FILES {
+            let lock_path = to.join(lock_file);
+            if lock_path.is_symlink() || lock_path.exists() {
+                transaction.remove(&lock_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        let mut is_error = false;
+
+        if self.desktop_entry.path.is_file() {
+            match fs::remove_file(&self.desktop_entry.path) {
+                Ok(()) => {}
+                Err(error) => {
+                    error!("Failed to remove desktop file: {error:?}");
+                    is_error = true;
+                }
+            }
+        }
+
+        if let Some(icon_path) = self.get_icon_path()
+            && icon_path.is_file()
+        {
+            match fs::remove_file(icon_path) {
+                Ok(()) => {}
+                Err(error) => {
+                    error!("Failed to remove icon file: {error:?}");
+                    is_error = true;
+                }
+            }
+        }
+
+        if let Some(profile_path) = self.get_profile_path()
+            && Path::new(&profile_path).is_dir()
+        {
+            match fs::remove_dir_all(profile_path) {
+                Ok(()) => {}
+                Err(error) => {
+                    error!("Failed to remove profile: {error:?}");
+                    is_error = true;
+                }
             }
+        }
+
+        if is_error {
+            bail!("Some files could not be removed, check logs")
+        }
+
+        info!(
+            "Succesfully removed web app: {}",
+            self.get_name().unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    /// Run update actions when app has been updated, returns true if actions have been applied
+    pub fn update(&mut self) -> Result<bool, DesktopFileError> {
+        let report = self.migrate(false)?;
+        Ok(report.is_update())
+    }
+
+    /// Run the migrations for the version of this desktop file, `dry_run` only reports them.
+    /// When a migration fails the desktop file is left untouched.
+    pub fn migrate(&mut self, dry_run: bool) -> Result<MigrationReport, DesktopFileError> {
+        let app_version =
+            Version::parse(config::VERSION.get_value()).context("Failed to get app version")?;
+        let desktop_file_version = self.get_version().unwrap_or(Version::new(0, 0, 0));
+        let mut report = MigrationReport {
+            from: desktop_file_version.clone(),
+            to: app_version.clone(),
+            migrations: Vec::new(),
         };
 
-        if entries.isolate && !entries.profile_path.is_dir() {
-            error!(
-                name = entries.name,
-                "Profile does not exists. Trying to create new profile."
+        if !report.is_update() {
+            return Ok(report);
+        }
+
+        info!(
+            "Older desktop file version detected, {} has been updated",
+            config::APP_NAME.get_value()
+        );
+
+        let pending = migration::get_pending(&desktop_file_version)?;
+        report.migrations = pending.iter().map(|migration| migration.name).collect();
+
+        if dry_run {
+            info!(
+                name = self.get_name().unwrap_or_default(),
+                from = desktop_file_version.to_string(),
+                migrations = ?report.migrations,
+                "Migrations that would apply"
             );
-            let _ = self.build_profile_path();
+            return Ok(report);
         }
 
-        if !entries.icon_path.is_file() {
-            error!(name = entries.name, "Icon file does not exists");
+        // Files of the steps and the desktop file are rolled back when one of them fails
+        let mut transaction = Transaction::new();
+        let mut migrated = self.clone();
+        for migration in pending {
+            info!(
+                name = self.get_name().unwrap_or_default(),
+                from = desktop_file_version.to_string(),
+                versions = migration.versions,
+                "Applying migration: {}",
+                migration.name
+            );
+
+            if let Err(error) = migration.run(&mut migrated, &mut transaction) {
+                error!(
+                    name = self.get_name().unwrap_or_default(),
+                    "Migration '{}' failed, desktop file is left untouched: {error:?}",
+                    migration.name
+                );
+                return Err(error
+                    .context(format!("Migration '{}' failed", migration.name))
+                    .into());
+            }
         }
+
+        migrated.set_version(&app_version);
+        let saved = migrated.save_in(&mut transaction)?;
+        transaction.commit();
+        *self = saved;
+
+        Ok(report)
     }
 
-    fn get_entries(&self) -> Result<DesktopFileEntries, DesktopFileError> {
-        let name = self.get_name().ok_or(ValidationError {
-            field: Key::Name,
-            message: "Missing".to_string(),
-        })?;
-        let app_id = self.get_id().ok_or(ValidationError {
-            field: Key::Id,
-            message: "Missing".to_string(),
-        })?;
-        let version = self.get_version().ok_or(ValidationError {
-            field: Key::Version,
-            message: "Missing".to_string(),
-        })?;
-
-        let url_object = self
-            .get_url()
-            .ok_or(ValidationError {
-                field: Key::Url,
-                message: "Missing".to_string(),
-            })
-            .and_then(|url| {
-                Url::parse(&url).map_err(|_| ValidationError {
-                    field: Key::Url,
-                    message: "Invalid".to_string(),
-                })
-            })?;
-        let url = url_object.to_string();
-        let domain = url_object
-            .domain()
-            .or_else(|| url_object.host_str())
-            .ok_or(ValidationError {
-                field: Key::Url,
-                message: "Invalid domain".to_string(),
-            })?
-            .to_string();
-        let url_path = url_object.path().to_string();
-
-        let browser = self.get_browser().ok_or(ValidationError {
-            field: Key::BrowserId,
-            message: "Missing".to_string(),
-        })?;
-        let isolate = self.get_isolated().ok_or(ValidationError {
-            field: Key::Isolate,
-            message: "Missing".to_string(),
-        })?;
-        let maximize = self.get_maximized().ok_or(ValidationError {
-            field: Key::Maximize,
-            message: "Missing".to_string(),
-        })?;
-        let icon = self.get_icon_path().ok_or(ValidationError {
-            field: Key::Icon,
-            message: "Missing".to_string(),
-        })?;
-        let profile_path = self
-            .get_profile_path()
-            .or_else(|| {
-                if isolate {
-                    None
-                } else {
-                    Some(PathBuf::default())
+    /// Collects every problem instead of stopping at the first one
+    pub fn get_validation_report(&self) -> ValidationReport {
+        self.validate_all().0
+    }
+
+    /// Validate all keys, the entries to render are only complete when there is no error
+    fn validate_all(&self) -> (ValidationReport, Option<DesktopFileEntries>) {
+        let mut report = ValidationReport::default();
+        let entries = self.validate_entries(&mut report);
+        self.validate_optional(&mut report);
+
+        (report, entries)
+    }
+
+    /// Report a missing required key
+    fn require<T>(report: &mut ValidationReport, field: Key, value: Option<T>) -> Option<T> {
+        if value.is_none() {
+            report.add(Severity::Error, field, "Missing");
+        }
+        value
+    }
+
+    fn validate_entries(&self, report: &mut ValidationReport) -> Option<DesktopFileEntries> {
+        let name = Self::require(report, Key::Name, self.get_name());
+        let app_id = Self::require(report, Key::Id, self.get_id());
+        let version = Self::require(report, Key::Version, self.get_version());
+
+        let url = match Self::require(report, Key::Url, self.get_url()).map(|url| Url::parse(&url))
+        {
+            None => None,
+            Some(Err(_)) => {
+                report.add(Severity::Error, Key::Url, "Invalid");
+                None
+            }
+            Some(Ok(url)) => {
+                if url.domain().or_else(|| url.host_str()).is_none() {
+                    report.add(Severity::Error, Key::Url, "Invalid domain");
                 }
-            })
-            .ok_or(ValidationError {
-                field: Key::Profile,
-                message: "Missing".to_string(),
-            })?;
+                Some(url)
+            }
+        };
 
-        Ok(DesktopFileEntries {
-            name,
-            app_id,
-            version,
-            browser,
-            url,
-            url_path,
+        let browser = Self::require(report, Key::BrowserId, self.get_browser());
+        if let Some(browser) = &browser
+            && !browser.is_installed()
+        {
+            report.add(Severity::Warning, Key::BrowserId, "Not installed");
+        }
+
+        let isolate = Self::require(report, Key::Isolate, self.get_isolated());
+        let maximize = Self::require(report, Key::Maximize, self.get_maximized());
+
+        let icon_path = Self::require(report, Key::Icon, self.get_icon_path());
+        if let Some(icon_path) = &icon_path
+            && !icon_path.is_file()
+        {
+            report.add(Severity::Warning, Key::Icon, "File does not exist");
+        }
+
+        let profile_path = if isolate.unwrap_or(false) {
+            let profile_path = Self::require(report, Key::Profile, self.get_profile_path());
+            if let Some(profile_path) = &profile_path
+                && !profile_path.is_dir()
+            {
+                report.add(Severity::Warning, Key::Profile, "Profile does not exist");
+            }
+            profile_path
+        } else {
+            Some(self.get_profile_path().unwrap_or_default())
+        };
+
+        let url = url?;
+        let domain = url.domain().or_else(|| url.host_str())?.to_string();
+
+        Some(DesktopFileEntries {
+            name: name?,
+            app_id: app_id?,
+            version: version?,
+            browser: browser?,
+            url: url.to_string(),
+            url_path: url.path().to_string(),
             domain,
-            isolate,
-            maximize,
-            icon_path: icon,
-            profile_path,
+            isolate: isolate?,
+            maximize: maximize?,
+            icon_path: icon_path?,
+            profile_path: profile_path?,
+            actions: self.get_actions(),
+            flags: self.get_enabled_flags(),
+            extra_args: self.get_extra_args(),
+            env_vars: self.get_env_vars(),
+            user_agent: self.get_user_agent(),
+            proxy: self.get_supported_proxy(),
+            browser_app: BrowserApp {
+                app_id: self.get_browser_app_id(),
+                profile_directory: self.get_profile_directory(),
+                wm_class: self.get_wm_class(),
+            },
         })
     }
 
+    fn validate_optional(&self, report: &mut ValidationReport) {
+        // Adopted and imported apps can have vendor categories, launchers ignore what they
+        // do not know
+        for category in self.get_categories() {
+            if Category::get_registered_name(&category).is_none() {
+                report.add(
+                    Severity::Warning,
+                    Key::Categories,
+                    &format!("Unknown category '{category}'"),
+                );
+            }
+        }
+
+        if let Some(handler_url) = self.get_handler_url()
+            && (!handler_url.contains(Self::HANDLER_URL_PLACEHOLDER)
+                || Url::parse(&handler_url.replace(Self::HANDLER_URL_PLACEHOLDER, "uri")).is_err())
+        {
+            report.add(
+                Severity::Error,
+                Key::HandlerUrl,
+                &format!("Should be a url with '{}'", Self::HANDLER_URL_PLACEHOLDER),
+            );
+        }
+
+        let extra_args = self.validate_exec_list(report, Key::ExtraArgs, Self::validate_extra_arg);
+        for arg in Self::get_conflicting_args(&extra_args) {
+            report.add(
+                Severity::Warning,
+                Key::ExtraArgs,
+                &format!("'{arg}' conflicts with the browser template"),
+            );
+        }
+
+        if let Some(user_agent) = self.get_user_agent() {
+            if user_agent.chars().any(char::is_control) {
+                report.add(Severity::Error, Key::UserAgent, "Control character");
+            }
+            Self::report_support(
+                report,
+                Key::UserAgent,
+                self.get_user_agent_support(),
+                "Not supported by the browser",
+            );
+        }
+
+        if let Some(proxy_mode) = self
+            .desktop_entry
+            .desktop_entry(&Key::ProxyMode.to_string())
+        {
+            match ProxyMode::from_string(proxy_mode) {
+                None => report.add(
+                    Severity::Error,
+                    Key::ProxyMode,
+                    &format!("Unknown mode '{proxy_mode}'"),
+                ),
+                Some(proxy_mode) => {
+                    if proxy_mode.is_manual() && self.get_proxy_server().is_none() {
+                        report.add(Severity::Error, Key::ProxyServer, "Missing");
+                    }
+                    Self::report_support(
+                        report,
+                        Key::ProxyMode,
+                        self.get_proxy_support(proxy_mode),
+                        &format!("Mode '{proxy_mode}' not supported by the browser"),
+                    );
+                }
+            }
+        }
+        if let Some(proxy_server) = self.get_proxy_server()
+            && let Err(error) = Self::parse_proxy_server(&proxy_server)
+        {
+            report.add(Severity::Error, Key::ProxyServer, &error.message);
+        }
+
+        let prefs = self.validate_exec_list(report, Key::FirefoxPrefs, Self::parse_firefox_pref);
+        if !prefs.is_empty() {
+            Self::report_support(
+                report,
+                Key::FirefoxPrefs,
+                self.get_firefox_prefs_support(),
+                "Only for Firefox based browsers",
+            );
+        }
+
+        self.validate_exec_list(report, Key::Env, Self::parse_env_var);
+
+        for action in self.get_actions() {
+            if Url::parse(&action.url).is_err() {
+                report.add(
+                    Severity::Error,
+                    Key::Actions,
+                    &format!("Invalid url for '{}'", action.name),
+                );
+            }
+        }
+    }
+
+    /// Report invalid quoting and every invalid value of a list key, returns the valid values
+    fn validate_exec_list<T>(
+        &self,
+        report: &mut ValidationReport,
+        field: Key,
+        parse_value: impl Fn(&str) -> Result<T, ValidationError>,
+    ) -> Vec<T> {
+        let Some(values) = self.desktop_entry.desktop_entry(&field.to_string()) else {
+            return Vec::new();
+        };
+        let Ok(values) = exec::parse(values) else {
+            report.add(Severity::Error, field, "Invalid quoting");
+            return Vec::new();
+        };
+
+        values
+            .iter()
+            .filter_map(|value| {
+                parse_value(value)
+                    .inspect_err(|error| report.add(Severity::Error, field.clone(), &error.message))
+                    .ok()
+            })
+            .collect()
+    }
+
+    fn report_support(
+        report: &mut ValidationReport,
+        field: Key,
+        support: SettingSupport,
+        unsupported_message: &str,
+    ) {
+        match support {
+            SettingSupport::Supported => {}
+            SettingSupport::NeedsIsolation => {
+                report.add(Severity::Warning, field, "Needs an isolated profile");
+            }
+            SettingSupport::Unsupported => {
+                report.add(Severity::Warning, field, unsupported_message);
+            }
+        }
+    }
+
+    /// Check paths, try to fix and print errors
+    pub fn check_paths(&self) {
+        let name = self.get_name().unwrap_or_default();
+
+        for issue in self.get_validation_report().issues {
+            let field = issue.error.field.to_string();
+            match issue.severity {
+                Severity::Error => error!(
+                    name,
+                    field, "Invalid 'DesktopFile': {}", issue.error.message
+                ),
+                Severity::Warning => warn!(
+                    name,
+                    field, "Problem on 'DesktopFile': {}", issue.error.message
+                ),
+            }
+
+            if issue.error.field == Key::Profile && issue.severity == Severity::Warning {
+                info!(name, "Trying to create new profile");
+                let _ = self.build_profile_path();
+            }
+        }
+    }
+
+    fn get_entries(&self) -> Result<DesktopFileEntries, DesktopFileError> {
+        let (report, entries) = self.validate_all();
+        if let Some(error) = report.get_first_error() {
+            return Err(error.into());
+        }
+
+        Ok(entries.context("Incomplete desktop file without a validation error")?)
+    }
+
     fn get_save_path(&self) -> Result<PathBuf> {
         let applications_dir = &self.app_dirs.user_applications;
         let file_name = format!(
@@ -734,93 +2386,168 @@ impl DesktopFile {
         Ok(desktop_file_path)
     }
 
-    fn replace_conditional(
-        conditional_key: &str,
-        set_value: bool,
-        with_value: Option<&str>,
-        d_str: &mut String,
-    ) -> Result<()> {
-        let optional_replace_value = Regex::new(&format!(r"%\{{{conditional_key}\s*\?\s*([^}}]+)"))
-            .context(format!(
-                "Failed to compile regex for captures with conditional key: {conditional_key}"
-            ))
-            .inspect_err(|error| error!(?error))?
-            .captures(&*d_str)
-            .and_then(|caps| caps.get(1).map(|value| value.as_str().to_string()));
-
-        if let Some(replace_value) = optional_replace_value {
-            let re = Regex::new(&format!(r"%\{{{conditional_key}\s*\?\s*[^}}]+\}}",))
-                .context(format!(
-                    "Failed to compile regex for replacement with conditional key:
-                    {conditional_key}"
-                ))
-                .inspect_err(|error| error!(?error))?;
-
-            let replacement = if set_value && let Some(with_value) = with_value {
-                format!("{replace_value}={with_value}")
-            } else if set_value {
-                replace_value
+    /// Fill the browser desktop file template, `url` differs for actions
+    fn render_template(
+        entries: &DesktopFileEntries,
+        url: &str,
+    ) -> Result<String, DesktopFileError> {
+        let app_name_short = config::APP_NAME_SHORT.get_value();
+        let app_id = format!("{}-{}", app_name_short, entries.app_id);
+
+        let domain_path = &match entries.browser.base {
+            Base::Chromium => {
+                let domain = format!("{}/", entries.domain);
+                let domain_path = format!("{domain}{}", entries.url_path);
+                domain_path.replace('/', "_")
+            }
+            // Not needed for other browser atm
+            _ => {
+                format!("{}{}", entries.domain, entries.url_path)
+            }
+        };
+        let profile_path = entries.profile_path.to_string_lossy();
+
+        let mut context = template::Context::new();
+        context.set_args(
+            "command",
+            &entries.browser.get_run_args_with_env(&entries.env_vars)?,
+        );
+        context.set_text("name", &entries.name);
+        context.set_text("url", url);
+        context.set_text("domain", &entries.domain);
+        context.set_text("domain_path", domain_path);
+        context.set_text("icon", &entries.icon_path.to_string_lossy());
+        context.set_text("app_id", &app_id);
+        context.set_text(
+            "profile_path",
+            if entries.isolate { &profile_path } else { "" },
+        );
+        context.set_flag("is_isolated", entries.isolate.then_some(&*profile_path));
+        context.set_bool("is_maximized", entries.maximize);
+        // Actions open their own url instead of the browser app, unless the Exec of actions
+        // passes the url to the browser app
+        let is_action = url != entries.url;
+        let browser_app_id = entries.browser_app.app_id.as_deref();
+        context.set_flag("browser_app_id", browser_app_id.filter(|_| !is_action));
+        context.set_flag(
+            "profile_directory",
+            entries.browser_app.profile_directory.as_deref(),
+        );
+        context.set_text(
+            "wm_class",
+            entries.browser_app.wm_class.as_deref().unwrap_or_default(),
+        );
+        context.set_args("extra_args", &entries.extra_args);
+        context.set_flag("user_agent", entries.user_agent.as_deref());
+        let proxy_mode = entries.proxy.as_ref().map(|proxy| proxy.mode);
+        let proxy_server = entries.proxy.as_ref().and_then(|proxy| {
+            let (host, port) = proxy.server.as_ref()?;
+            let scheme = if proxy.mode == ProxyMode::Socks {
+                "socks5"
             } else {
-                String::new()
+                "http"
             };
+            Some(format!("{scheme}://{host}:{port}"))
+        });
+        let proxy_bypass_list = entries
+            .proxy
+            .as_ref()
+            .filter(|proxy| !proxy.bypass.is_empty())
+            .map(|proxy| proxy.bypass.join(";"));
+        context.set_bool("no_proxy_server", proxy_mode == Some(ProxyMode::None));
+        context.set_flag("proxy_server", proxy_server.as_deref());
+        context.set_flag("proxy_bypass_list", proxy_bypass_list.as_deref());
+        for flag in &entries.browser.flags {
+            context.set_bool(&flag.name, entries.flags.contains(&flag.name));
+        }
+
+        let exec_keys = [Key::Exec.to_string(), Key::ActionExec.to_string()];
+        let mut d_str = String::new();
+
+        for line in entries.browser.desktop_file.to_string().lines() {
+            let exec_line = exec_keys.iter().find_map(|key| {
+                line.strip_prefix(&format!("{key}="))
+                    .map(|value| (key, value))
+            });
+
+            let rendered_line = match exec_line {
+                // Values in an Exec need quoting per argument
+                Some((key, value)) => {
+                    let is_action_exec = *key == Key::ActionExec.to_string();
+                    context.set_flag(
+                        "browser_app_id",
+                        browser_app_id.filter(|_| !is_action || is_action_exec),
+                    );
+                    let exec = Template::parse(value)
+                        .and_then(|template| template.render_exec(&context))
+                        .map(|exec| format!("{key}={exec}"));
+                    context.set_flag("browser_app_id", browser_app_id.filter(|_| !is_action));
+                    exec
+                }
+                None => Template::parse(line).and_then(|template| template.render(&context)),
+            }
+            .context("Failed to render browser desktop file template")?;
 
-            *d_str = re.replace_all(&*d_str, replacement).to_string();
+            d_str.push_str(&rendered_line);
+            d_str.push('\n');
         }
 
-        Ok(())
+        Ok(d_str)
     }
 
-    fn to_new_from_browser(&self) -> Result<DesktopFile, DesktopFileError> {
-        let entries = &self.get_entries()?;
-        let save_path = self.get_save_path()?;
-        let app_name_short = config::APP_NAME_SHORT.get_value();
-        let app_id = format!("{}-{}", app_name_short, entries.app_id);
+    /// Render the template for `url` and take the Exec of `key`, with fallback to `Exec`
+    fn get_exec_from_template(
+        &self,
+        entries: &DesktopFileEntries,
+        url: &str,
+        key: &str,
+    ) -> Result<String, DesktopFileError> {
+        let d_str = Self::render_template(entries, url)?;
+        // A new web app has no path yet
+        let desktop_entry =
+            DesktopEntry::from_str(self.get_save_path()?, &d_str, None::<&[String]>)
+                .context("Failed to parse browser desktop file")?;
+        let exec = desktop_entry
+            .desktop_entry(key)
+            .or_else(|| desktop_entry.desktop_entry(&Key::Exec.to_string()))
+            .context("No Exec in browser desktop file")?;
+
+        Ok(exec.to_string())
+    }
 
-        let domain_path = match self.get_browser() {
-            None => &entries.domain,
-            Some(browser) => &match browser.base {
-                Base::Chromium => {
-                    let domain = format!("{}/", entries.domain);
-                    let domain_path = format!("{domain}{}", entries.url_path);
-                    domain_path.replace('/', "_")
-                }
-                // Not needed for other browser atm
-                _ => {
-                    format!("{}{}", entries.domain, entries.url_path)
-                }
-            },
-        };
+    fn get_self_run_args() -> Vec<String> {
+        if crate::utils::env::is_flatpak_container() {
+            return vec![
+                "flatpak".to_string(),
+                "run".to_string(),
+                config::APP_ID.get_value().clone(),
+            ];
+        }
 
-        let mut d_str = entries.browser.desktop_file.clone().to_string();
-        d_str = d_str.replace("%{command}", &entries.browser.get_run_command()?);
-        d_str = d_str.replace("%{name}", &entries.name);
-        d_str = d_str.replace("%{url}", &entries.url);
-        d_str = d_str.replace("%{domain}", &entries.domain);
-        d_str = d_str.replace("%{domain_path}", domain_path);
-        d_str = d_str.replace("%{icon}", &entries.icon_path.to_string_lossy());
-        d_str = d_str.replace("%{app_id}", &app_id);
-
-        if Self::replace_conditional(
-            "is_isolated",
-            entries.isolate,
-            Some(&entries.profile_path.to_string_lossy()),
-            &mut d_str,
-        )
-        .is_err()
-        {
-            return Err(DesktopFileError::Other(anyhow!(
-                "Failed to replace conditional 'is_isolated' in desktop file"
-            )));
+        // The installed binary keeps working after an update, unlike the path of a build
+        let bin_name = config::BIN_NAME.get_value();
+        if crate::utils::env::find_in_path(bin_name).is_some() {
+            return vec![bin_name.clone()];
         }
 
-        if Self::replace_conditional("is_maximized", entries.maximize, None, &mut d_str).is_err() {
-            return Err(DesktopFileError::Other(anyhow!(
-                "Failed to replace conditional 'is_maximized' in desktop file"
-            )));
+        match env::current_exe() {
+            Ok(path) if path.is_file() => {
+                debug!("'{bin_name}' is not in PATH, using: {}", path.display());
+                vec![path.to_string_lossy().to_string()]
+            }
+            _ => vec![bin_name.clone()],
         }
+    }
+
+    fn to_new_from_browser(&self) -> Result<DesktopFile, DesktopFileError> {
+        let entries = &self.get_entries()?;
+        let save_path = self.get_save_path()?;
+        let action_exec_key = Key::ActionExec.to_string();
 
+        let d_str = Self::render_template(entries, &entries.url)?;
         let mut new_desktop_file =
             Self::from_string(&save_path, &d_str, &self.browser_configs, &self.app_dirs)?;
+        remove_entry(&mut new_desktop_file.desktop_entry, &action_exec_key);
 
         new_desktop_file.set_is_owned_app();
         new_desktop_file.set_id(&entries.app_id);
@@ -834,10 +2561,76 @@ impl DesktopFile {
         if let Some(description) = self.get_description() {
             new_desktop_file.set_description(&description);
         }
-        if let Some(category) = self.get_category() {
-            new_desktop_file.set_category_str(&category);
-        } else {
+        new_desktop_file.set_localized_names(&self.get_localized_names());
+        new_desktop_file.set_localized_descriptions(&self.get_localized_descriptions());
+        let categories = self.get_categories();
+        if categories.is_empty() {
             new_desktop_file.set_category(&Category::Network);
+        } else {
+            new_desktop_file.set_categories(&categories);
+        }
+        let keywords = self.get_keywords();
+        if !keywords.is_empty() {
+            new_desktop_file.set_keywords(&keywords);
+        }
+        new_desktop_file.set_localized_keywords(&self.get_localized_keywords());
+        if let Some(generic_name) = self.get_generic_name() {
+            new_desktop_file.set_generic_name(&generic_name);
+        }
+        new_desktop_file.set_localized_generic_names(&self.get_localized_generic_names());
+
+        if self.get_saved_flags().is_some() {
+            new_desktop_file.set_enabled_flags(&entries.flags);
+        }
+        if let Some(browser_app_id) = &entries.browser_app.app_id {
+            new_desktop_file.set_browser_app_id(browser_app_id);
+        }
+        if let Some(profile_directory) = &entries.browser_app.profile_directory {
+            new_desktop_file.set_profile_directory(profile_directory);
+        }
+        if let Some(wm_class) = &entries.browser_app.wm_class {
+            new_desktop_file.set_wm_class(wm_class);
+        }
+        new_desktop_file.set_fallback_browsers(&self.get_fallback_browsers());
+        new_desktop_file.set_extra_args(&entries.extra_args)?;
+        new_desktop_file.set_env_vars(&entries.env_vars)?;
+        if let Some(user_agent) = &entries.user_agent {
+            new_desktop_file.set_user_agent(user_agent);
+        }
+        // Also kept when the browser does not support the mode
+        new_desktop_file.set_proxy_mode(self.get_proxy_mode());
+        new_desktop_file.set_proxy_server(&self.get_proxy_server().unwrap_or_default())?;
+        new_desktop_file.set_proxy_bypass(&self.get_proxy_bypass());
+        // Also kept for another browser, e.g. to switch back to Firefox
+        new_desktop_file.set_firefox_prefs(&self.get_firefox_prefs())?;
+
+        new_desktop_file.set_actions(&entries.actions);
+        // Ids could have been deduplicated
+        for action in new_desktop_file.get_actions() {
+            // Templates can have a separate Exec for actions, fallback to the normal Exec
+            let exec = self.get_exec_from_template(entries, &action.url, &action_exec_key)?;
+
+            add_group_entry(
+                &mut new_desktop_file.desktop_entry,
+                &action.get_group_name(),
+                &Key::Exec.to_string(),
+                &exec,
+            );
+        }
+
+        if self.is_handler() {
+            new_desktop_file.set_mime_types(&self.get_mime_types());
+            new_desktop_file.set_handler_url(&self.get_handler_url().unwrap_or_default());
+            // Exec can't map the uri to a url, so let this app do it
+            let exec = exec::build(&[
+                exec::Part::Args(Self::get_self_run_args()),
+                exec::Part::Literal(" handle ".to_string()),
+                exec::Part::Value(entries.app_id.clone()),
+                exec::Part::Literal(" %u".to_string()),
+            ])?;
+            new_desktop_file
+                .desktop_entry
+                .add_desktop_entry(Key::Exec.to_string(), exec);
         }
 
         Ok(new_desktop_file)
@@ -845,6 +2638,355 @@ impl DesktopFile {
 }
 impl std::fmt::Display for DesktopFile {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        self.desktop_entry.fmt(f)
+        write!(f, "{}", utils::to_string(&self.desktop_entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app_dirs::BaseDirs,
+        assets,
+        browsers::{Installation, StaticDetector},
+    };
+    use tempfile::TempDir;
+
+    /// Expected desktop files, regenerate them with `UPDATE_GOLDEN=1 cargo test`
+    const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/golden");
+    const ID: &str = "abcd1234";
+
+    /// A home in a temp dir with the shipped assets, only `installations` are installed
+    fn setup(installations: Vec<Installation>) -> (TempDir, Rc<AppDirs>, Rc<BrowserConfigs>) {
+        config::init();
+        let home = tempfile::tempdir().unwrap();
+        let app_dirs = AppDirs::from_base_dirs(BaseDirs::from_home(home.path())).unwrap();
+        assets::init(&app_dirs).unwrap();
+        let browser_configs = BrowserConfigs::new_with_detector(
+            &app_dirs,
+            Box::new(StaticDetector { installations }),
+        );
+        browser_configs.init();
+
+        (home, app_dirs, browser_configs)
+    }
+
+    /// Every flatpak and system installation of the shipped browser configs
+    fn get_all_installations() -> Vec<Installation> {
+        let (_home, _app_dirs, browser_configs) = setup(Vec::new());
+
+        browser_configs
+            .get_uninstalled_browsers()
+            .iter()
+            .flat_map(|browser| {
+                let flatpak = browser.flatpak_id.clone().map(Installation::Flatpak);
+                let system = browser.executable.clone().map(Installation::System);
+                flatpak.into_iter().chain(system)
+            })
+            .collect()
+    }
+
+    fn get_browser(browser_configs: &Rc<BrowserConfigs>, id: &str) -> Rc<Browser> {
+        browser_configs
+            .get_by_id(id)
+            .filter(|browser| browser.is_installed())
+            .unwrap()
+    }
+
+    /// A saved web app that uses most of the template
+    fn create(
+        browser: &Rc<Browser>,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> DesktopFile {
+        let mut desktop_file = DesktopFile::new(browser_configs, app_dirs);
+        desktop_file.set_id(ID);
+        desktop_file.set_name("Example");
+        desktop_file.set_url("https://example.com/app");
+        desktop_file.set_browser(browser);
+        desktop_file.set_maximized(browser.can_start_maximized);
+        desktop_file.set_description("An example web app");
+        desktop_file.set_category(&Category::Network);
+        desktop_file.set_actions(&[DesktopAction::new(
+            "New message",
+            "https://example.com/app/new",
+        )]);
+        desktop_file
+            .set_extra_args(&["--lang=en US".to_string()])
+            .unwrap();
+        desktop_file
+            .set_env_vars(&[("EXAMPLE".to_string(), "a b".to_string())])
+            .unwrap();
+
+        let icon_path = app_dirs.app_data_icons.join(format!("{ID}.png"));
+        fs::write(&icon_path, b"\x89PNG\r\n\x1a\n").unwrap();
+        desktop_file.set_icon_path(&icon_path);
+
+        let mut transaction = Transaction::new();
+        desktop_file.set_isolated(browser.can_isolate);
+        desktop_file
+            .update_profile_in(
+                browser.can_isolate,
+                ProfileMigration::Discard,
+                &mut transaction,
+            )
+            .unwrap();
+        let desktop_file = desktop_file.save_in(&mut transaction).unwrap();
+        transaction.commit();
+
+        desktop_file
+    }
+
+    /// Without the parts that differ per run
+    fn normalize(desktop_file: &DesktopFile, home: &TempDir) -> String {
+        fs::read_to_string(desktop_file.get_path())
+            .unwrap()
+            .replace(&home.path().to_string_lossy().to_string(), "$HOME")
+            .replace(config::VERSION.get_value(), "$VERSION")
+    }
+
+    fn assert_golden(name: &str, content: &str) {
+        let golden_path = Path::new(GOLDEN_DIR).join(name);
+
+        if env::var("UPDATE_GOLDEN").is_ok() {
+            fs::create_dir_all(GOLDEN_DIR).unwrap();
+            fs::write(&golden_path, content).unwrap();
+            return;
+        }
+
+        let golden = fs::read_to_string(&golden_path)
+            .unwrap_or_else(|_| panic!("No golden file: {}", golden_path.display()));
+        assert_eq!(content, golden, "{name} differs from its golden file");
+    }
+
+    #[test]
+    fn render_every_browser() {
+        let installations = get_all_installations();
+        assert!(!installations.is_empty());
+
+        for installation in installations {
+            let (home, app_dirs, browser_configs) = setup(vec![installation]);
+            let browser = browser_configs
+                .get_all_browsers()
+                .iter()
+                .find(|browser| browser.is_installed())
+                .cloned()
+                .unwrap();
+
+            let desktop_file = create(&browser, &browser_configs, &app_dirs);
+            assert_golden(
+                &format!("{}.desktop", browser.id),
+                &normalize(&desktop_file, &home),
+            );
+
+            if browser.base == Base::Chromium {
+                let mut desktop_file = desktop_file;
+                desktop_file.set_browser_app_id("abcdefghijklmnopabcdefghijklmnop");
+                desktop_file.set_profile_directory("Default");
+                desktop_file.save().unwrap();
+                assert_golden(
+                    &format!("{}-browser-app.desktop", browser.id),
+                    &normalize(&desktop_file, &home),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn create_writes_files() {
+        let (_home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let browser = get_browser(&browser_configs, "firefox");
+
+        let desktop_file = create(&browser, &browser_configs, &app_dirs);
+
+        assert!(desktop_file.get_path().is_file());
+        assert!(
+            desktop_file
+                .get_path()
+                .starts_with(&app_dirs.user_applications)
+        );
+        assert!(desktop_file.get_icon_path().unwrap().is_file());
+        assert!(desktop_file.has_profile());
+
+        let owned = DesktopFile::get_owned(&browser_configs, &app_dirs, &[]);
+        assert_eq!(owned.len(), 1);
+        assert_eq!(owned[0].desktop_file.get_id().as_deref(), Some(ID));
+        assert!(!owned[0].is_updated);
+        assert!(owned[0].fallback_browser.is_none());
+    }
+
+    #[test]
+    fn save_switches_browser() {
+        let (_home, app_dirs, browser_configs) = setup(vec![
+            Installation::System("firefox".to_string()),
+            Installation::System("chromium-browser".to_string()),
+        ]);
+        let firefox = get_browser(&browser_configs, "firefox");
+        let chromium = get_browser(&browser_configs, "chromium-browser");
+        let mut desktop_file = create(&firefox, &browser_configs, &app_dirs);
+        let old_path = desktop_file.get_path();
+        let old_profile_path = desktop_file.get_profile_path().unwrap();
+
+        let mut transaction = Transaction::new();
+        desktop_file.set_browser(&chromium);
+        desktop_file
+            .update_profile_in(true, ProfileMigration::Discard, &mut transaction)
+            .unwrap();
+        let saved = desktop_file.save_in(&mut transaction).unwrap();
+        transaction.commit();
+
+        assert!(!old_path.exists());
+        assert!(!old_profile_path.exists());
+        assert!(saved.get_path().is_file());
+        assert!(saved.has_profile());
+        assert_eq!(
+            DesktopFile::get_owned_paths(&app_dirs),
+            vec![saved.get_path()]
+        );
+        assert!(saved.get_exec().unwrap().contains(" chromium-browser "));
+    }
+
+    #[test]
+    fn failed_save_rolls_back() {
+        let (_home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let browser = get_browser(&browser_configs, "firefox");
+        let mut desktop_file = DesktopFile::new(&browser_configs, &app_dirs);
+        desktop_file.set_name("No url");
+        desktop_file.set_browser(&browser);
+
+        let mut transaction = Transaction::new();
+        desktop_file.set_isolated(true);
+        desktop_file
+            .update_profile_in(true, ProfileMigration::Discard, &mut transaction)
+            .unwrap();
+        let profile_path = desktop_file.get_profile_path().unwrap();
+        assert!(profile_path.is_dir());
+
+        assert!(matches!(
+            desktop_file.save_in(&mut transaction),
+            Err(DesktopFileError::ValidationError(_))
+        ));
+        drop(transaction);
+
+        assert!(!profile_path.exists());
+        assert!(DesktopFile::get_owned_paths(&app_dirs).is_empty());
+    }
+
+    #[test]
+    fn update_migrates_older_version() {
+        let (_home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let browser = get_browser(&browser_configs, "firefox");
+        let mut desktop_file = create(&browser, &browser_configs, &app_dirs);
+        desktop_file.set_version(&Version::new(0, 1, 0));
+        desktop_file.save().unwrap();
+
+        let owned =
+            DesktopFile::load_owned(&desktop_file.get_path(), &browser_configs, &app_dirs, &[])
+                .unwrap();
+        assert!(owned.is_updated);
+
+        let reloaded =
+            DesktopFile::from_path(&desktop_file.get_path(), &browser_configs, &app_dirs).unwrap();
+        assert_eq!(
+            reloaded.get_version().unwrap().to_string(),
+            *config::VERSION.get_value()
+        );
+        assert!(!reloaded.clone().update().unwrap());
+    }
+
+    #[test]
+    fn delete_removes_files() {
+        let (_home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let browser = get_browser(&browser_configs, "firefox");
+        let desktop_file = create(&browser, &browser_configs, &app_dirs);
+
+        desktop_file.delete().unwrap();
+
+        assert!(!desktop_file.get_path().exists());
+        assert!(!desktop_file.get_icon_path().unwrap().exists());
+        assert!(!desktop_file.get_profile_path().unwrap().exists());
+        assert!(DesktopFile::get_owned(&browser_configs, &app_dirs, &[]).is_empty());
+    }
+
+    #[test]
+    fn browser_configs_init_again_reads_changed_configs() {
+        let (_home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let browser = get_browser(&browser_configs, "firefox");
+        let desktop_file = create(&browser, &browser_configs, &app_dirs);
+
+        fs::remove_file(app_dirs.app_config_browser_configs.join("firefox.yml")).unwrap();
+        browser_configs.init();
+
+        assert!(browser_configs.get_by_id("firefox").is_none());
+        assert!(desktop_file.get_browser().is_none());
+    }
+
+    #[test]
+    fn duplicate_removes_browser_app() {
+        let (_home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("chromium-browser".to_string())]);
+        let browser = get_browser(&browser_configs, "chromium-browser");
+        let mut desktop_file = create(&browser, &browser_configs, &app_dirs);
+        desktop_file.set_browser_app_id("abcdefghijklmnop");
+        desktop_file.set_profile_directory("Default");
+        desktop_file.set_wm_class("crx_abcdefghijklmnop");
+        let mut transaction = Transaction::new();
+        let desktop_file = desktop_file.save_in(&mut transaction).unwrap();
+        transaction.commit();
+        assert!(
+            desktop_file
+                .get_exec()
+                .unwrap()
+                .contains("abcdefghijklmnop")
+        );
+
+        let duplicate = desktop_file.duplicate("Copy", false).unwrap();
+
+        assert!(duplicate.get_browser_app_id().is_none());
+        assert!(duplicate.get_profile_directory().is_none());
+        assert!(duplicate.get_wm_class().is_none());
+        assert!(!duplicate.get_exec().unwrap().contains("abcdefghijklmnop"));
+        assert_eq!(
+            desktop_file.get_browser_app_id().as_deref(),
+            Some("abcdefghijklmnop")
+        );
+    }
+
+    #[test]
+    fn save_keeps_translations() {
+        let (_home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let browser = get_browser(&browser_configs, "firefox");
+        let mut desktop_file = create(&browser, &browser_configs, &app_dirs);
+        let translations = |value: &str| {
+            BTreeMap::from([
+                ("de".to_string(), format!("{value} de")),
+                ("pt_BR".to_string(), format!("{value} pt")),
+            ])
+        };
+        desktop_file.set_localized_names(&translations("Name"));
+        desktop_file.set_localized_descriptions(&translations("Comment"));
+        desktop_file.set_generic_name("Generic");
+        desktop_file.set_localized_generic_names(&translations("Generic"));
+        desktop_file.set_keywords(&["a".to_string()]);
+        desktop_file.set_localized_keywords(&translations("a;b;"));
+        // Setting the untranslated values keeps the translations
+        desktop_file.set_keywords(&["a".to_string(), "b".to_string()]);
+        desktop_file.set_generic_name("Other generic");
+        desktop_file.save().unwrap();
+
+        let saved =
+            DesktopFile::from_path(&desktop_file.get_path(), &browser_configs, &app_dirs).unwrap();
+        assert_eq!(saved.get_localized_names(), translations("Name"));
+        assert_eq!(saved.get_localized_descriptions(), translations("Comment"));
+        assert_eq!(saved.get_localized_generic_names(), translations("Generic"));
+        assert_eq!(saved.get_localized_keywords(), translations("a;b;"));
+        assert_eq!(saved.get_keywords(), ["a", "b"]);
+        assert_eq!(saved.get_generic_name().as_deref(), Some("Other generic"));
     }
 }
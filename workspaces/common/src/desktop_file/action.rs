@@ -0,0 +1,42 @@
+/// A `[Desktop Action ...]` group, shown as jump-list entry by most desktops
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopAction {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+}
+impl DesktopAction {
+    pub const GROUP_PREFIX: &str = "Desktop Action ";
+
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            id: Self::build_id(name),
+            name: name.to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    pub fn get_group_name(&self) -> String {
+        format!("{}{}", Self::GROUP_PREFIX, self.id)
+    }
+
+    /// Identifiers may only contain A-Za-z0-9-
+    fn build_id(name: &str) -> String {
+        let id = name
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter(char::is_ascii_alphanumeric)
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+
+        if id.is_empty() {
+            "action".to_string()
+        } else {
+            id
+        }
+    }
+}
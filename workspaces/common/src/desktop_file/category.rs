@@ -1,3 +1,4 @@
+#[cfg(feature = "gtk")]
 use gtk::Image;
 use std::fmt::Display;
 
@@ -38,6 +39,137 @@ impl Display for Category {
     }
 }
 impl Category {
+    /// Additional categories from the registry, these should be combined with a main category
+    pub const ADDITIONAL: &[&str] = &[
+        "Building",
+        "Debugger",
+        "IDE",
+        "GUIDesigner",
+        "Profiling",
+        "RevisionControl",
+        "Translation",
+        "Calendar",
+        "ContactManagement",
+        "Database",
+        "Dictionary",
+        "Chart",
+        "Email",
+        "Finance",
+        "FlowChart",
+        "PDA",
+        "ProjectManagement",
+        "Presentation",
+        "Spreadsheet",
+        "WordProcessor",
+        "2DGraphics",
+        "VectorGraphics",
+        "RasterGraphics",
+        "3DGraphics",
+        "Scanning",
+        "OCR",
+        "Photography",
+        "Publishing",
+        "Viewer",
+        "TextTools",
+        "DesktopSettings",
+        "HardwareSettings",
+        "Printing",
+        "PackageManager",
+        "Dialup",
+        "InstantMessaging",
+        "Chat",
+        "IRCClient",
+        "Feed",
+        "FileTransfer",
+        "HamRadio",
+        "News",
+        "P2P",
+        "RemoteAccess",
+        "Telephony",
+        "TelephonyTools",
+        "VideoConference",
+        "WebBrowser",
+        "WebDevelopment",
+        "Midi",
+        "Mixer",
+        "Sequencer",
+        "Tuner",
+        "TV",
+        "AudioVideoEditing",
+        "Player",
+        "Recorder",
+        "DiscBurning",
+        "ActionGame",
+        "AdventureGame",
+        "ArcadeGame",
+        "BoardGame",
+        "BlocksGame",
+        "CardGame",
+        "KidsGame",
+        "LogicGame",
+        "RolePlaying",
+        "Shooter",
+        "Simulation",
+        "SportsGame",
+        "StrategyGame",
+        "Art",
+        "Construction",
+        "Music",
+        "Languages",
+        "ArtificialIntelligence",
+        "Astronomy",
+        "Biology",
+        "Chemistry",
+        "ComputerScience",
+        "DataVisualization",
+        "Economy",
+        "Electricity",
+        "Geography",
+        "Geology",
+        "Geoscience",
+        "History",
+        "Humanities",
+        "ImageProcessing",
+        "Literature",
+        "Maps",
+        "Math",
+        "NumericalAnalysis",
+        "MedicalSoftware",
+        "Physics",
+        "Robotics",
+        "Spirituality",
+        "Sports",
+        "ParallelComputing",
+        "Amusement",
+        "Archiving",
+        "Compression",
+        "Electronics",
+        "Emulator",
+        "Engineering",
+        "FileTools",
+        "FileManager",
+        "TerminalEmulator",
+        "Filesystem",
+        "Monitor",
+        "Security",
+        "Accessibility",
+        "Calculator",
+        "Clock",
+        "TextEditor",
+        "Documentation",
+        "Adult",
+        "Core",
+        "KDE",
+        "GNOME",
+        "XFCE",
+        "DDE",
+        "GTK",
+        "Qt",
+        "Motif",
+        "Java",
+        "ConsoleOnly",
+    ];
+
     pub fn get_all() -> [Category; 13] {
         let list: [Category; 13] = [
             Self::AudioVideo,
@@ -58,6 +190,57 @@ impl Category {
         list
     }
 
+    /// The registry name, without the parent category
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn get_name(&self) -> String {
+        self.to_string()
+            .split(';')
+            .next_back()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub fn from_name(name: &str) -> Option<Category> {
+        Self::get_all()
+            .into_iter()
+            .find(|category| category.get_name().eq_ignore_ascii_case(name))
+    }
+
+    /// First main category in a list of categories, a sub category (e.g. Audio) wins over its parent
+    pub fn from_categories(categories: &[String]) -> Option<Category> {
+        let main_categories = categories
+            .iter()
+            .filter_map(|name| Self::from_name(name))
+            .collect::<Vec<_>>();
+
+        main_categories
+            .iter()
+            .find(|category| !matches!(category, Self::AudioVideo))
+            .or(main_categories.first())
+            .copied()
+    }
+
+    pub fn is_main(name: &str) -> bool {
+        Self::from_name(name).is_some()
+    }
+
+    /// Registered name of a main or additional category, extensions (`X-`) are kept as is
+    pub fn get_registered_name(name: &str) -> Option<String> {
+        if name.starts_with("X-") {
+            return Some(name.to_string());
+        }
+
+        Self::from_name(name)
+            .map(|category| category.get_name())
+            .or_else(|| {
+                Self::ADDITIONAL
+                    .iter()
+                    .find(|additional| additional.eq_ignore_ascii_case(name))
+                    .map(|additional| (*additional).to_string())
+            })
+    }
+
+    #[cfg(feature = "gtk")]
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn get_icon(&self) -> Image {
         let icon_name = match self {
@@ -40,3 +40,65 @@ impl Display for ValidationError {
     }
 }
 impl std::error::Error for ValidationError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The web app works, but something is off (e.g. a missing icon file)
+    Warning,
+    /// The web app can't be saved
+    Error,
+}
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub error: ValidationError,
+}
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({})", self.error, self.severity)
+    }
+}
+
+/// All problems of a desktop file, instead of only the first one
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+impl ValidationReport {
+    pub fn add(&mut self, severity: Severity, field: Key, message: &str) {
+        self.issues.push(ValidationIssue {
+            severity,
+            error: ValidationError {
+                field,
+                message: message.to_string(),
+            },
+        });
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.get_first_error().is_none()
+    }
+
+    pub fn get_first_error(&self) -> Option<ValidationError> {
+        self.issues
+            .iter()
+            .find(|issue| issue.severity == Severity::Error)
+            .map(|issue| issue.error.clone())
+    }
+
+    pub fn get_issues_for(&self, fields: &[Key]) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| fields.contains(&issue.error.field))
+            .collect()
+    }
+}
@@ -0,0 +1,415 @@
+use anyhow::{Result, bail};
+
+/*
+    The Exec key of the desktop entry spec:
+    <https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html>
+
+    The string escapes (`\s`, `\n`, `\t`, `\r` and `\\`) apply first, then the quoting rules.
+    Arguments with reserved characters are quoted in whole, in a quoted argument
+    `"`, `` ` ``, `$` and `\` are escaped with a backslash. A literal `%` is written as `%%`.
+*/
+
+const RESERVED_CHARS: &[char] = &[
+    ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')',
+    '`',
+];
+const QUOTE_ESCAPED_CHARS: &[char] = &['"', '`', '$', '\\'];
+
+/// A part of an Exec argument, template text is in Exec syntax and values are plain text
+#[derive(Debug)]
+pub enum Part {
+    Literal(String),
+    Value(String),
+    /// Each value is a separate argument
+    Args(Vec<String>),
+}
+
+#[derive(Default)]
+struct Arg {
+    /// Content with `%` escaped, without quotes
+    encoded: String,
+    /// The argument as the launched program gets it
+    decoded: String,
+    is_quoted: bool,
+    has_value: bool,
+}
+impl Arg {
+    fn push_value(&mut self, value: &str) {
+        self.encoded.push_str(&value.replace('%', "%%"));
+        self.decoded.push_str(value);
+        self.has_value = true;
+    }
+
+    fn is_empty(&self) -> bool {
+        self.encoded.is_empty() && !self.is_quoted
+    }
+
+    fn is_field_code(&self) -> bool {
+        is_field_code(&self.decoded) && !self.has_value
+    }
+
+    fn to_exec(&self) -> String {
+        if self.is_field_code()
+            || (!self.encoded.is_empty() && !self.encoded.contains(RESERVED_CHARS))
+        {
+            return self.encoded.clone();
+        }
+
+        let mut quoted = String::from('"');
+        for char in self.encoded.chars() {
+            if QUOTE_ESCAPED_CHARS.contains(&char) {
+                quoted.push('\\');
+            }
+            quoted.push(char);
+        }
+        quoted.push('"');
+
+        quoted
+    }
+}
+
+/// Build an Exec value, fails when parsing it back does not give the intended arguments
+pub fn build(parts: &[Part]) -> Result<String> {
+    let mut args: Vec<Arg> = Vec::new();
+    let mut arg = Arg::default();
+    let mut in_quotes = false;
+
+    for part in parts {
+        match part {
+            Part::Value(value) => arg.push_value(value),
+
+            Part::Args(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        args.push(std::mem::take(&mut arg));
+                    }
+                    arg.push_value(value);
+                }
+            }
+
+            Part::Literal(text) => {
+                let text = unescape(text);
+                let mut chars = text.chars();
+
+                while let Some(char) = chars.next() {
+                    match char {
+                        '%' => {
+                            let code = chars.next().unwrap_or('%');
+                            arg.encoded.push('%');
+                            arg.encoded.push(code);
+                            if code != '%' {
+                                arg.decoded.push('%');
+                            }
+                            arg.decoded.push(code);
+                        }
+                        '\\' if in_quotes => {
+                            let Some(escaped) = chars.next() else {
+                                bail!("Exec ends with an escape character")
+                            };
+                            arg.encoded.push(escaped);
+                            arg.decoded.push(escaped);
+                        }
+                        '"' => {
+                            in_quotes = !in_quotes;
+                            arg.is_quoted = true;
+                        }
+                        _ if char.is_whitespace() && !in_quotes => {
+                            let finished_arg = std::mem::take(&mut arg);
+                            if !finished_arg.is_empty() {
+                                args.push(finished_arg);
+                            }
+                        }
+                        _ => {
+                            arg.encoded.push(char);
+                            arg.decoded.push(char);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if in_quotes {
+        bail!("Unclosed quote in Exec")
+    }
+    if !arg.is_empty() {
+        args.push(arg);
+    }
+    // Only values of the template that turned out empty
+    args.retain(|arg| !arg.decoded.is_empty() || arg.is_quoted);
+
+    let exec = escape(&args.iter().map(Arg::to_exec).collect::<Vec<_>>().join(" "));
+
+    let intended = args.into_iter().map(|arg| arg.decoded).collect::<Vec<_>>();
+    let parsed = parse(&exec)?;
+    if parsed != intended {
+        bail!("Exec '{exec}' parses to {parsed:?} instead of {intended:?}")
+    }
+
+    Ok(exec)
+}
+
+/// Split an Exec value in arguments, field codes like `%u` are kept as an argument
+pub fn parse(exec: &str) -> Result<Vec<String>> {
+    let exec = unescape(exec);
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut is_started = false;
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '%' => {
+                match chars.next() {
+                    Some('%') | None => arg.push('%'),
+                    Some(code) => {
+                        arg.push('%');
+                        arg.push(code);
+                    }
+                }
+                is_started = true;
+            }
+            '\\' if in_quotes => match chars.next() {
+                Some(escaped) if QUOTE_ESCAPED_CHARS.contains(&escaped) => arg.push(escaped),
+                Some(escaped) => bail!("Invalid escape '\\{escaped}' in Exec: {exec}"),
+                None => bail!("Exec ends with an escape character: {exec}"),
+            },
+            '"' => {
+                in_quotes = !in_quotes;
+                is_started = true;
+            }
+            _ if char.is_whitespace() && !in_quotes => {
+                if is_started {
+                    args.push(std::mem::take(&mut arg));
+                }
+                is_started = false;
+            }
+            _ => {
+                arg.push(char);
+                is_started = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        bail!("Unclosed quote in Exec: {exec}")
+    }
+    if is_started {
+        args.push(arg);
+    }
+
+    Ok(args)
+}
+
+pub fn is_field_code(arg: &str) -> bool {
+    matches!(
+        arg,
+        "%f" | "%F" | "%u" | "%U" | "%i" | "%c" | "%k" | "%d" | "%D" | "%n" | "%N" | "%v" | "%m"
+    )
+}
+
+/// String escapes of desktop file values
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::new();
+
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+pub fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') | None => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::command::{quote_arg, split_args};
+
+    const HOSTILE_ARGS: &[&str] = &[
+        "https://example.com/search?q=100%25&a=b",
+        "say \"hi\"",
+        "$HOME",
+        "`id`",
+        "with space",
+        "back\\slash",
+        "new\nline",
+        "/home/user name/.local/share/web apps",
+    ];
+
+    fn literal(text: &str) -> Part {
+        Part::Literal(text.to_string())
+    }
+
+    fn value(text: &str) -> Part {
+        Part::Value(text.to_string())
+    }
+
+    #[test]
+    fn build_round_trips_values() {
+        for arg in HOSTILE_ARGS {
+            let exec = build(&[literal("browser "), value(arg), literal(" %u")]).unwrap();
+
+            assert_eq!(parse(&exec).unwrap(), ["browser", arg, "%u"], "{exec}");
+        }
+    }
+
+    #[test]
+    fn build_round_trips_args() {
+        let args = HOSTILE_ARGS
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let exec = build(&[literal("browser "), Part::Args(args.clone())]).unwrap();
+
+        let mut expected = vec!["browser".to_string()];
+        expected.extend(args);
+        assert_eq!(parse(&exec).unwrap(), expected);
+    }
+
+    #[test]
+    fn build_quotes_and_escapes() {
+        assert_eq!(
+            build(&[literal("browser "), value("with space")]).unwrap(),
+            "browser \"with space\""
+        );
+        assert_eq!(
+            build(&[literal("browser "), value("a\"b$c`d\\e")]).unwrap(),
+            "browser \"a\\\\\"b\\\\$c\\\\`d\\\\\\\\e\""
+        );
+        assert_eq!(
+            build(&[literal("browser "), value("100%")]).unwrap(),
+            "browser 100%%"
+        );
+        assert_eq!(
+            build(&[literal("browser "), value("new\nline")]).unwrap(),
+            "browser \"new\\nline\""
+        );
+    }
+
+    #[test]
+    fn build_keeps_field_codes() {
+        assert_eq!(build(&[literal("browser %u")]).unwrap(), "browser %u");
+        assert_eq!(build(&[literal("browser %U")]).unwrap(), "browser %U");
+        assert_eq!(build(&[literal("browser 100%%")]).unwrap(), "browser 100%%");
+        // A value that looks like a field code is not one
+        assert_eq!(
+            parse(&build(&[literal("browser "), value("%u")]).unwrap()).unwrap(),
+            ["browser", "%u"]
+        );
+        assert_eq!(
+            build(&[literal("browser "), value("%u")]).unwrap(),
+            "browser %%u"
+        );
+    }
+
+    #[test]
+    fn build_empty_args() {
+        assert_eq!(
+            build(&[literal("browser \"\" %u")]).unwrap(),
+            "browser \"\" %u"
+        );
+        assert_eq!(
+            build(&[literal("browser "), value(""), literal(" %u")]).unwrap(),
+            "browser %u"
+        );
+        assert_eq!(
+            build(&[literal("browser \""), value(""), literal("\" %u")]).unwrap(),
+            "browser \"\" %u"
+        );
+    }
+
+    #[test]
+    fn build_rejects_unclosed_quotes() {
+        assert!(build(&[literal("browser \"--name")]).is_err());
+    }
+
+    #[test]
+    fn parse_field_codes_and_percent() {
+        assert_eq!(parse("browser %u").unwrap(), ["browser", "%u"]);
+        assert_eq!(parse("browser %U").unwrap(), ["browser", "%U"]);
+        assert_eq!(parse("browser 100%%").unwrap(), ["browser", "100%"]);
+        assert_eq!(parse("browser \"%%u\"").unwrap(), ["browser", "%u"]);
+    }
+
+    #[test]
+    fn parse_quotes() {
+        assert_eq!(
+            parse(r#"browser "with space" "" "a\\"b""#).unwrap(),
+            ["browser", "with space", "", "a\"b"]
+        );
+        assert!(parse("browser \"unclosed").is_err());
+        assert!(parse(r#"browser "\a""#).is_err());
+    }
+
+    #[test]
+    fn escape_round_trips() {
+        for value in [
+            "plain",
+            "back\\slash",
+            "new\nline",
+            "tab\tand\rreturn",
+            "\\s",
+        ] {
+            assert_eq!(unescape(&escape(value)), value);
+        }
+        assert_eq!(escape("a\\b\nc"), "a\\\\b\\nc");
+        assert_eq!(unescape("a\\sb"), "a b");
+    }
+
+    #[test]
+    fn build_runs_as_command() {
+        for arg in HOSTILE_ARGS {
+            let exec = build(&[literal("browser "), value(arg), literal(" %U")]).unwrap();
+
+            // The command that runs the app: field codes dropped, arguments shell quoted
+            let command = parse(&exec)
+                .unwrap()
+                .iter()
+                .filter(|arg| !is_field_code(arg))
+                .map(|arg| quote_arg(arg))
+                .collect::<Vec<_>>()
+                .join(" ");
+            assert_eq!(split_args(&command).unwrap(), ["browser", arg], "{exec}");
+
+            // Without `%`, the unescaped Exec is valid shell quoting as well
+            if !exec.contains('%') {
+                assert_eq!(
+                    split_args(&unescape(&exec)).unwrap(),
+                    ["browser", arg],
+                    "{exec}"
+                );
+            }
+        }
+    }
+}
@@ -20,6 +20,24 @@ pub enum Key {
     StartupWMClass,
     Categories,
     Comment,
+    Actions,
+    ActionExec,
+    MimeType,
+    HandlerUrl,
+    Keywords,
+    GenericName,
+    Flags,
+    BrowserAppId,
+    ProfileDirectory,
+    WmClass,
+    FallbackBrowsers,
+    ExtraArgs,
+    Env,
+    UserAgent,
+    ProxyMode,
+    ProxyServer,
+    ProxyBypass,
+    FirefoxPrefs,
 }
 impl Display for Key {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -40,6 +58,24 @@ impl Display for Key {
             Self::StartupWMClass => write!(f, "StartupWMClass"),
             Self::Categories => write!(f, "Categories"),
             Self::Comment => write!(f, "Comment"),
+            Self::Actions => write!(f, "Actions"),
+            Self::ActionExec => write!(f, "X-{}-ACTION-EXEC", &identifier),
+            Self::MimeType => write!(f, "MimeType"),
+            Self::HandlerUrl => write!(f, "X-{}-HANDLER-URL", &identifier),
+            Self::Keywords => write!(f, "Keywords"),
+            Self::GenericName => write!(f, "GenericName"),
+            Self::Flags => write!(f, "X-{}-FLAGS", &identifier),
+            Self::BrowserAppId => write!(f, "X-{}-BROWSER-APP-ID", &identifier),
+            Self::ProfileDirectory => write!(f, "X-{}-PROFILE-DIRECTORY", &identifier),
+            Self::WmClass => write!(f, "X-{}-WM-CLASS", &identifier),
+            Self::FallbackBrowsers => write!(f, "X-{}-FALLBACK-BROWSERS", &identifier),
+            Self::ExtraArgs => write!(f, "X-{}-EXTRA-ARGS", &identifier),
+            Self::Env => write!(f, "X-{}-ENV", &identifier),
+            Self::UserAgent => write!(f, "X-{}-USER-AGENT", &identifier),
+            Self::ProxyMode => write!(f, "X-{}-PROXY-MODE", &identifier),
+            Self::ProxyServer => write!(f, "X-{}-PROXY-SERVER", &identifier),
+            Self::ProxyBypass => write!(f, "X-{}-PROXY-BYPASS", &identifier),
+            Self::FirefoxPrefs => write!(f, "X-{}-FIREFOX-PREFS", &identifier),
         }
     }
 }
@@ -0,0 +1,75 @@
+use crate::{desktop_file::DesktopFile, transaction::Transaction};
+use anyhow::{Context, Result};
+use semver::{Version, VersionReq};
+use tracing::debug;
+
+/// A migration step for owned desktop files created by an older version of the app
+pub struct Migration {
+    pub name: &'static str,
+    /// Semver requirement on the version of the desktop file, e.g. `<0.6.0`
+    pub versions: &'static str,
+    /// Files are changed through the transaction, it rolls back when a later step fails
+    run: fn(&mut DesktopFile, &mut Transaction) -> Result<()>,
+}
+impl Migration {
+    pub fn applies_to(&self, version: &Version) -> Result<bool> {
+        let version_req = VersionReq::parse(self.versions).context(format!(
+            "Invalid version range on migration '{}'",
+            self.name
+        ))?;
+
+        Ok(version_req.matches(version))
+    }
+
+    pub fn run(&self, desktop_file: &mut DesktopFile, transaction: &mut Transaction) -> Result<()> {
+        (self.run)(desktop_file, transaction)
+    }
+}
+
+/// In order of execution, a step works on the result of the previous steps
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    name: "Copy profile config",
+    versions: "*",
+    run: copy_profile_config,
+}];
+
+pub struct MigrationReport {
+    pub from: Version,
+    pub to: Version,
+    pub migrations: Vec<&'static str>,
+}
+impl MigrationReport {
+    pub fn is_update(&self) -> bool {
+        self.from < self.to
+    }
+}
+
+pub fn get_pending(version: &Version) -> Result<Vec<&'static Migration>> {
+    let mut pending = Vec::new();
+
+    for migration in MIGRATIONS {
+        if migration.applies_to(version)? {
+            pending.push(migration);
+        }
+    }
+
+    Ok(pending)
+}
+
+fn copy_profile_config(
+    desktop_file: &mut DesktopFile,
+    transaction: &mut Transaction,
+) -> Result<()> {
+    if desktop_file.get_isolated().is_some()
+        && let Some(profile_path) = desktop_file.get_profile_path()
+    {
+        debug!(
+            profile_path = profile_path.to_string_lossy().to_string(),
+            "Updating profile config"
+        );
+
+        desktop_file.copy_profile_config_to_profile_path(&profile_path, transaction)?;
+    }
+
+    Ok(())
+}
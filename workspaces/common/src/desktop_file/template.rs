@@ -0,0 +1,561 @@
+use crate::desktop_file::exec::{self, Part};
+use anyhow::{Result, bail};
+use std::collections::{BTreeSet, HashMap};
+
+/*
+    Mini-language of the browser desktop file templates:
+
+    %{name}                 Value of `name`, escaped for a desktop file
+    %{name|raw}             Value without escaping
+    %{name|quote}           Value as a quoted Exec argument
+    %{name ? then}          `then` when `name` is set
+    %{name ? then : else}   `then` when `name` is set, otherwise `else`
+    %{!name ? then}         `then` when `name` is not set
+
+    Branches can contain placeholders. The else `:` needs whitespace on both sides, so a
+    `:` in an argument (e.g. `--proxy=http://host`) stays literal like in templates from
+    before else branches. In branches `%:` and `%}` are a literal `:` and `}`.
+    `%%{` is a literal `%{`, any other `%` (e.g. the Exec field codes `%u`) is kept as is.
+
+    Rendered as Exec, values are escaped and quoted per argument as the spec requires.
+*/
+
+/// A typed value in the context of a template
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// Set when not empty
+    Text(String),
+    /// Only usable as a condition
+    Bool(bool),
+    /// A flag with an optional value, a conditional renders `<then>=<value>`.
+    /// E.g. `%{is_isolated ? --profile}` renders `--profile=<path>`.
+    Flag(Option<String>),
+    /// Separate arguments in an Exec, e.g. `flatpak run <id>`
+    Args(Vec<String>),
+}
+impl Value {
+    fn is_set(&self) -> bool {
+        match self {
+            Self::Text(text) => !text.is_empty(),
+            Self::Bool(value) => *value,
+            Self::Flag(value) => value.is_some(),
+            Self::Args(args) => !args.is_empty(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Context {
+    values: HashMap<String, Value>,
+}
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn set_text(&mut self, name: &str, value: &str) {
+        self.set(name, Value::Text(value.to_string()));
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set(name, Value::Bool(value));
+    }
+
+    pub fn set_flag(&mut self, name: &str, value: Option<&str>) {
+        self.set(name, Value::Flag(value.map(str::to_string)));
+    }
+
+    pub fn set_args(&mut self, name: &str, args: &[String]) {
+        self.set(name, Value::Args(args.to_vec()));
+    }
+
+    fn get(&self, name: &str) -> Result<&Value> {
+        match self.values.get(name) {
+            Some(value) => Ok(value),
+            None => bail!("Unknown template variable '{name}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Filter {
+    Escape,
+    Raw,
+    Quote,
+}
+impl Filter {
+    fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "raw" => Ok(Self::Raw),
+            "quote" => Ok(Self::Quote),
+            _ => bail!("Unknown template filter '{name}'"),
+        }
+    }
+
+    fn apply(self, value: &str) -> String {
+        match self {
+            Self::Escape => exec::escape(value),
+            Self::Raw => value.to_string(),
+            Self::Quote => quote(value),
+        }
+    }
+}
+
+enum Output {
+    Literal(String),
+    Value(String, Filter),
+    Args(Vec<String>, Filter),
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Variable {
+        name: String,
+        filter: Filter,
+    },
+    Conditional {
+        name: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+        };
+        let nodes = parser.parse_nodes(false)?;
+
+        Ok(Self { nodes })
+    }
+
+    /// Fails on variables that are not in the context
+    pub fn render(&self, context: &Context) -> Result<String> {
+        let mut outputs = Vec::new();
+        Self::render_nodes(&self.nodes, context, &mut outputs)?;
+
+        let rendered = outputs
+            .into_iter()
+            .map(|output| match output {
+                Output::Literal(text) => text,
+                Output::Value(value, filter) => filter.apply(&value),
+                Output::Args(args, filter) => args
+                    .iter()
+                    .map(|arg| filter.apply(arg))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            })
+            .collect();
+
+        Ok(rendered)
+    }
+
+    /// Render the value of an Exec key, every value is escaped and quoted as needed.
+    /// Values with the `raw` filter are taken as Exec syntax.
+    pub fn render_exec(&self, context: &Context) -> Result<String> {
+        let mut outputs = Vec::new();
+        Self::render_nodes(&self.nodes, context, &mut outputs)?;
+
+        let parts = outputs
+            .into_iter()
+            .map(|output| match output {
+                Output::Literal(text) | Output::Value(text, Filter::Raw) => Part::Literal(text),
+                Output::Value(value, _) => Part::Value(value),
+                Output::Args(args, Filter::Raw) => Part::Literal(args.join(" ")),
+                Output::Args(args, _) => Part::Args(args),
+            })
+            .collect::<Vec<_>>();
+
+        exec::build(&parts)
+    }
+
+    /// All variable names used in the template
+    pub fn get_variables(&self) -> BTreeSet<String> {
+        let mut variables = BTreeSet::new();
+        Self::collect_variables(&self.nodes, &mut variables);
+
+        variables
+    }
+
+    fn render_nodes(nodes: &[Node], context: &Context, outputs: &mut Vec<Output>) -> Result<()> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => outputs.push(Output::Literal(text.clone())),
+
+                Node::Variable { name, filter } => match context.get(name)? {
+                    Value::Text(text) => outputs.push(Output::Value(text.clone(), *filter)),
+                    Value::Flag(value) => {
+                        outputs.push(Output::Value(value.clone().unwrap_or_default(), *filter));
+                    }
+                    Value::Args(args) => outputs.push(Output::Args(args.clone(), *filter)),
+                    Value::Bool(_) => {
+                        bail!("Template variable '{name}' is a condition and has no value")
+                    }
+                },
+
+                Node::Conditional {
+                    name,
+                    negate,
+                    then,
+                    otherwise,
+                } => {
+                    let value = context.get(name)?;
+
+                    if value.is_set() == *negate {
+                        Self::render_nodes(otherwise, context, outputs)?;
+                        continue;
+                    }
+
+                    Self::render_nodes(then, context, outputs)?;
+                    if !negate && let Value::Flag(Some(flag_value)) = value {
+                        outputs.push(Output::Literal("=".to_string()));
+                        outputs.push(Output::Value(flag_value.clone(), Filter::Escape));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn collect_variables(nodes: &[Node], variables: &mut BTreeSet<String>) {
+        for node in nodes {
+            match node {
+                Node::Text(_) => {}
+                Node::Variable { name, .. } => {
+                    variables.insert(name.clone());
+                }
+                Node::Conditional {
+                    name,
+                    then,
+                    otherwise,
+                    ..
+                } => {
+                    variables.insert(name.clone());
+                    Self::collect_variables(then, variables);
+                    Self::collect_variables(otherwise, variables);
+                }
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+impl Parser {
+    fn parse_nodes(&mut self, in_branch: bool) -> Result<Vec<Node>> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+
+        while let Some(char) = self.peek(0) {
+            if char == '%' && self.peek(1) == Some('%') && self.peek(2) == Some('{') {
+                text.push_str("%{");
+                self.position += 3;
+            } else if in_branch && char == '%' && matches!(self.peek(1), Some(':' | '}')) {
+                text.push(self.peek(1).unwrap_or_default());
+                self.position += 2;
+            } else if char == '%' && self.peek(1) == Some('{') {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                self.position += 2;
+                nodes.push(self.parse_placeholder()?);
+            } else if in_branch && (char == '}' || (char == ':' && self.is_else_separator())) {
+                break;
+            } else {
+                text.push(char);
+                self.position += 1;
+            }
+        }
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+
+        Ok(nodes)
+    }
+
+    fn parse_placeholder(&mut self) -> Result<Node> {
+        let start = self.position;
+        self.skip_whitespace();
+
+        let negate = self.peek(0) == Some('!');
+        if negate {
+            self.position += 1;
+            self.skip_whitespace();
+        }
+
+        let name = self.parse_name();
+        if name.is_empty() {
+            bail!("Missing variable name at position {start}")
+        }
+        self.skip_whitespace();
+
+        let node = match self.peek(0) {
+            Some('?') => {
+                self.position += 1;
+                let then = Self::trim_branch(self.parse_nodes(true)?);
+
+                let otherwise = if self.peek(0) == Some(':') {
+                    self.position += 1;
+                    Self::trim_branch(self.parse_nodes(true)?)
+                } else {
+                    Vec::new()
+                };
+
+                Node::Conditional {
+                    name,
+                    negate,
+                    then,
+                    otherwise,
+                }
+            }
+            _ if negate => bail!("Negation of '{name}' needs a conditional at position {start}"),
+            Some('|') => {
+                self.position += 1;
+                self.skip_whitespace();
+                let filter = Filter::from_name(&self.parse_name())?;
+                self.skip_whitespace();
+
+                Node::Variable { name, filter }
+            }
+            _ => Node::Variable {
+                name,
+                filter: Filter::Escape,
+            },
+        };
+
+        match self.peek(0) {
+            Some('}') => {
+                self.position += 1;
+                Ok(node)
+            }
+            Some(char) => bail!(
+                "Unexpected '{char}' in placeholder at position {}",
+                self.position
+            ),
+            None => bail!("Unclosed placeholder at position {start}"),
+        }
+    }
+
+    fn parse_name(&mut self) -> String {
+        let mut name = String::new();
+
+        while let Some(char) = self.peek(0)
+            && (char.is_ascii_alphanumeric() || char == '_')
+        {
+            name.push(char);
+            self.position += 1;
+        }
+
+        name
+    }
+
+    fn is_else_separator(&self) -> bool {
+        let is_after_whitespace = self
+            .position
+            .checked_sub(1)
+            .and_then(|position| self.chars.get(position))
+            .is_some_and(|char| char.is_whitespace());
+
+        is_after_whitespace && self.peek(1).is_some_and(char::is_whitespace)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek(0).is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn trim_branch(mut nodes: Vec<Node>) -> Vec<Node> {
+        if let Some(Node::Text(text)) = nodes.first_mut() {
+            *text = text.trim_start().to_string();
+        }
+        if let Some(Node::Text(text)) = nodes.last_mut() {
+            *text = text.trim_end().to_string();
+        }
+        nodes.retain(|node| !matches!(node, Node::Text(text) if text.is_empty()));
+
+        nodes
+    }
+}
+
+/// A single quoted argument for Exec, see "The Exec key" of the desktop entry spec
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for char in value.chars() {
+        match char {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(char);
+            }
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+
+    // The string escape rules apply before the quoting rules
+    exec::escape(&quoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> Context {
+        let mut context = Context::new();
+        context.set_text("name", "Web App");
+        context.set_text("empty", "");
+        context.set_text("url", "https://example.com/?q=100%");
+        context.set_bool("yes", true);
+        context.set_bool("no", false);
+        context.set_flag("profile", Some("/home/user name/profile"));
+        context.set_flag("unset_flag", None);
+        context.set_args(
+            "command",
+            &[
+                "flatpak".to_string(),
+                "run".to_string(),
+                "org.app".to_string(),
+            ],
+        );
+        context
+    }
+
+    fn render(source: &str) -> String {
+        Template::parse(source).unwrap().render(&context()).unwrap()
+    }
+
+    fn render_exec(source: &str) -> String {
+        Template::parse(source)
+            .unwrap()
+            .render_exec(&context())
+            .unwrap()
+    }
+
+    #[test]
+    fn variables_and_filters() {
+        assert_eq!(render("Name=%{name}"), "Name=Web App");
+        assert_eq!(render("%{ name }"), "Web App");
+        assert_eq!(render("%{url|raw}"), "https://example.com/?q=100%");
+        assert_eq!(render("%{name|quote}"), "\"Web App\"");
+        assert_eq!(render("%{url|quote}"), "\"https://example.com/?q=100%%\"");
+        assert_eq!(render("%{command}"), "flatpak run org.app");
+    }
+
+    #[test]
+    fn conditionals() {
+        assert_eq!(render("%{yes ? on}"), "on");
+        assert_eq!(render("%{no ? on}"), "");
+        assert_eq!(render("%{no ? on : off}"), "off");
+        assert_eq!(render("%{empty ? on : off}"), "off");
+        assert_eq!(render("%{name ? on : off}"), "on");
+        assert_eq!(render("%{!no ? on}"), "on");
+        assert_eq!(render("%{!yes ? on : off}"), "off");
+    }
+
+    #[test]
+    fn nested_conditionals() {
+        assert_eq!(render("%{yes ? %{no ? a : b} : c}"), "b");
+        assert_eq!(render("%{no ? a : %{!yes ? b : %{name}}}"), "Web App");
+    }
+
+    #[test]
+    fn flags_render_their_value() {
+        assert_eq!(
+            render("%{profile ? --profile}"),
+            "--profile=/home/user name/profile"
+        );
+        assert_eq!(render("%{unset_flag ? --flag : none}"), "none");
+        assert_eq!(render("%{!profile ? --no-profile}"), "");
+        assert_eq!(
+            render_exec("browser %{profile ? --profile} %u"),
+            "browser \"--profile=/home/user name/profile\" %u"
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(render("%{yes ? a%:b}"), "a:b");
+        assert_eq!(render("%{yes ? a %: b}"), "a : b");
+        assert_eq!(render("%{yes ? {a%}}"), "{a}");
+        assert_eq!(render("%%{name}"), "%{name}");
+        assert_eq!(render("browser %u 100%"), "browser %u 100%");
+    }
+
+    #[test]
+    fn literal_colons_in_branches() {
+        // Templates from before else branches have `:` in arguments
+        assert_eq!(
+            render("%{yes ? --proxy=http://localhost:8080}"),
+            "--proxy=http://localhost:8080"
+        );
+        assert_eq!(render("%{no ? --a=b:c : other}"), "other");
+    }
+
+    #[test]
+    fn exec_quotes_values() {
+        assert_eq!(
+            render_exec("%{command} --app=%{url} %u"),
+            "flatpak run org.app \"--app=https://example.com/?q=100%%\" %u"
+        );
+        assert_eq!(
+            render_exec("%{command} %{name} %{empty} %U"),
+            "flatpak run org.app \"Web App\" %U"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let context = context();
+
+        assert!(Template::parse("%{name|unknown}").is_err());
+        assert!(Template::parse("%{name").is_err());
+        assert!(Template::parse("%{yes ? on").is_err());
+        assert!(Template::parse("%{}").is_err());
+        assert!(Template::parse("%{!name}").is_err());
+        assert!(
+            Template::parse("%{unknown}")
+                .unwrap()
+                .render(&context)
+                .is_err()
+        );
+        assert!(
+            Template::parse("%{unknown ? a}")
+                .unwrap()
+                .render(&context)
+                .is_err()
+        );
+        assert!(Template::parse("%{yes}").unwrap().render(&context).is_err());
+    }
+
+    #[test]
+    fn variables() {
+        let template = Template::parse("%{name} %{yes ? %{url} : %{!no ? a}}").unwrap();
+
+        assert_eq!(
+            template.get_variables(),
+            BTreeSet::from(["name", "yes", "url", "no"].map(str::to_string))
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use crate::{config, utils::OnceLockExt};
+use std::fmt::Display;
+
+/*
+    Prefs of a web app in the `user.js` of its isolated Firefox profile.
+
+    They are kept in a block between two marker lines after the prefs of the profile config,
+    so the shipped defaults stay and the block is replaced on every save. Firefox copies
+    `user.js` into `prefs.js` on start, a pref removed from the block stays there until
+    it is removed from `prefs.js` too.
+*/
+
+/// A value of a pref, written as a JavaScript literal
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrefValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+impl PrefValue {
+    /// `true`, `false` and whole numbers keep their type, anything else is a string.
+    /// A value in double quotes is always a string (e.g. `"1"`).
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "true" => Self::Bool(true),
+            "false" => Self::Bool(false),
+            trimmed => {
+                if let Some(quoted) = trimmed
+                    .strip_prefix('"')
+                    .and_then(|rest| rest.strip_suffix('"'))
+                {
+                    return Self::String(unquote(quoted));
+                }
+                trimmed
+                    .parse::<i64>()
+                    .map_or_else(|_| Self::String(trimmed.to_string()), Self::Int)
+            }
+        }
+    }
+}
+impl Display for PrefValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "{}", quote(value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pref {
+    pub name: String,
+    pub value: PrefValue,
+}
+impl Pref {
+    pub fn new(name: &str, value: PrefValue) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!("user_pref({}, {});", quote(&self.name), self.value)
+    }
+}
+
+fn get_begin_marker() -> String {
+    format!(
+        "// {}: prefs of this web app, changes are overwritten",
+        config::APP_NAME.get_value()
+    )
+}
+
+fn get_end_marker() -> String {
+    format!("// {}: end of prefs", config::APP_NAME.get_value())
+}
+
+/// `user_js` with the block of the web app replaced by `prefs`, without a block when empty
+pub fn merge(user_js: &str, prefs: &[Pref]) -> String {
+    let (mut merged, _) = split_block(user_js);
+
+    if prefs.is_empty() {
+        return merged;
+    }
+
+    if !merged.is_empty() && !merged.ends_with('\n') {
+        merged.push('\n');
+    }
+    merged.push_str(&get_begin_marker());
+    merged.push('\n');
+    for pref in prefs {
+        merged.push_str(&pref.to_line());
+        merged.push('\n');
+    }
+    merged.push_str(&get_end_marker());
+    merged.push('\n');
+
+    merged
+}
+
+/// Names of the prefs in the block of the web app
+pub fn get_block_names(user_js: &str) -> Vec<String> {
+    let (_, block) = split_block(user_js);
+    block
+        .iter()
+        .filter_map(|line| get_pref_name(line))
+        .collect()
+}
+
+/// `prefs_js` without the lines that set one of `names`
+pub fn remove_prefs(prefs_js: &str, names: &[String]) -> String {
+    prefs_js
+        .lines()
+        .filter(|line| get_pref_name(line).is_none_or(|name| !names.contains(&name)))
+        .fold(String::new(), |mut removed, line| {
+            removed.push_str(line);
+            removed.push('\n');
+            removed
+        })
+}
+
+/// The lines outside the block and the lines in it
+fn split_block(user_js: &str) -> (String, Vec<&str>) {
+    let begin_marker = get_begin_marker();
+    let end_marker = get_end_marker();
+    let mut outside = String::new();
+    let mut block = Vec::new();
+    let mut in_block = false;
+
+    for line in user_js.lines() {
+        if line.trim() == begin_marker {
+            in_block = true;
+        } else if line.trim() == end_marker {
+            in_block = false;
+        } else if in_block {
+            block.push(line);
+        } else {
+            outside.push_str(line);
+            outside.push('\n');
+        }
+    }
+
+    (outside, block)
+}
+
+/// Name of a `user_pref("name", value);` line
+fn get_pref_name(line: &str) -> Option<String> {
+    let rest = line
+        .trim()
+        .strip_prefix("user_pref(")?
+        .trim_start()
+        .strip_prefix('"')?;
+    let mut name = String::new();
+    let mut chars = rest.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => name.push(chars.next()?),
+            '"' => return Some(name),
+            _ => name.push(char),
+        }
+    }
+
+    None
+}
+
+/// Content of a JavaScript string literal without its quotes, with the escapes of `quote`
+fn unquote(quoted: &str) -> String {
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            value.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some(escaped) => value.push(escaped),
+            None => value.push('\\'),
+        }
+    }
+
+    value
+}
+
+/// A JavaScript string literal
+fn quote(value: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for char in value.chars() {
+        match char {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIPPED_USER_JS: [&str; 2] = [
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../assets/config/profiles/firefox/user.js"
+        )),
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../assets/config/profiles/zen/user.js"
+        )),
+    ];
+
+    fn get_prefs() -> Vec<Pref> {
+        vec![
+            Pref::new(
+                "browser.startup.homepage",
+                PrefValue::parse("https://example.com"),
+            ),
+            Pref::new("app.update.auto", PrefValue::parse("false")),
+            Pref::new("layout.css.devPixelsPerPx", PrefValue::parse("\"1.5\"")),
+        ]
+    }
+
+    #[test]
+    fn parse_keeps_types() {
+        assert_eq!(PrefValue::parse(" true "), PrefValue::Bool(true));
+        assert_eq!(PrefValue::parse("-12"), PrefValue::Int(-12));
+        assert_eq!(
+            PrefValue::parse("\"1\""),
+            PrefValue::String("1".to_string())
+        );
+        assert_eq!(
+            PrefValue::parse("  a value "),
+            PrefValue::String("a value".to_string())
+        );
+    }
+
+    #[test]
+    fn quote_unquote_round_trip() {
+        let value = "a \"quoted\" C:\\path\twith\nescapes\r";
+        let quoted = quote(value);
+
+        assert_eq!(quoted, r#""a \"quoted\" C:\\path\twith\nescapes\r""#);
+        assert_eq!(unquote(&quoted[1..quoted.len() - 1]), value);
+        assert_eq!(
+            PrefValue::parse(&PrefValue::String(value.to_string()).to_string()),
+            PrefValue::String(value.to_string())
+        );
+    }
+
+    #[test]
+    fn get_pref_name_unescapes() {
+        assert_eq!(
+            get_pref_name(r#"  user_pref( "a.b", 1);"#).as_deref(),
+            Some("a.b")
+        );
+        assert_eq!(
+            get_pref_name(r#"user_pref("a\"b\\c", true);"#).as_deref(),
+            Some(r#"a"b\c"#)
+        );
+        assert_eq!(get_pref_name(r#"pref("a.b", 1);"#), None);
+        assert_eq!(get_pref_name(r#"user_pref("a.b, 1);"#), None);
+        assert_eq!(get_pref_name("// user_pref(\"a.b\", 1);"), None);
+
+        let name = "quoted \"name\" \\";
+        let line = Pref::new(name, PrefValue::Int(1)).to_line();
+        assert_eq!(get_pref_name(&line).as_deref(), Some(name));
+    }
+
+    #[test]
+    fn merge_replaces_block() {
+        config::init();
+        let user_js = "user_pref(\"a.b\", 1);";
+        let merged = merge(user_js, &get_prefs());
+        let (outside, block) = split_block(&merged);
+
+        assert_eq!(outside, "user_pref(\"a.b\", 1);\n");
+        assert_eq!(
+            block,
+            [
+                r#"user_pref("browser.startup.homepage", "https://example.com");"#,
+                r#"user_pref("app.update.auto", false);"#,
+                r#"user_pref("layout.css.devPixelsPerPx", "1.5");"#,
+            ]
+        );
+
+        let prefs = [Pref::new("app.update.auto", PrefValue::Bool(true))];
+        let merged = merge(&merged, &prefs);
+        assert_eq!(get_block_names(&merged), ["app.update.auto"]);
+        assert_eq!(merge(&merged, &[]), outside);
+    }
+
+    #[test]
+    fn merge_is_idempotent() {
+        config::init();
+        let prefs = get_prefs();
+
+        for user_js in SHIPPED_USER_JS {
+            let merged = merge(user_js, &prefs);
+            assert_eq!(merge(&merged, &prefs), merged);
+        }
+    }
+
+    #[test]
+    fn merge_keeps_shipped_prefs() {
+        config::init();
+
+        for user_js in SHIPPED_USER_JS {
+            let merged = merge(user_js, &get_prefs());
+            let (outside, _) = split_block(&merged);
+
+            for line in user_js.lines().filter(|line| get_pref_name(line).is_some()) {
+                assert!(
+                    outside.lines().any(|outside_line| outside_line == line),
+                    "{line}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn remove_prefs_removes_only_names() {
+        let prefs_js = "// Header\nuser_pref(\"a.b\", 1);\nuser_pref(\"a.b.c\", 2);\nuser_pref(\"d\", \"a.b\");";
+        let removed = remove_prefs(prefs_js, &["a.b".to_string()]);
+
+        assert_eq!(
+            removed,
+            "// Header\nuser_pref(\"a.b.c\", 2);\nuser_pref(\"d\", \"a.b\");\n"
+        );
+    }
+}
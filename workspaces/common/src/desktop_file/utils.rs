@@ -1,4 +1,10 @@
-use std::path::{Path, PathBuf};
+use freedesktop_desktop_entry::{DesktopEntry, LocaleMap};
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+pub const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
 
 pub fn map_to_string_option(value: &str) -> Option<String> {
     if value.is_empty() {
@@ -23,3 +29,104 @@ pub fn map_to_path_option(value: &str) -> Option<PathBuf> {
         Some(Path::new(value).to_path_buf())
     }
 }
+
+pub fn map_to_list(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(std::string::ToString::to_string)
+        .collect()
+}
+
+pub fn get_group_entry<'a>(
+    desktop_entry: &'a DesktopEntry,
+    group_name: &str,
+    key: &str,
+) -> Option<&'a str> {
+    desktop_entry
+        .groups
+        .0
+        .get(group_name)
+        .and_then(|group| group.0.get(key))
+        .map(|(value, _locale_map)| value.as_str())
+}
+
+pub fn add_group_entry(desktop_entry: &mut DesktopEntry, group_name: &str, key: &str, value: &str) {
+    desktop_entry
+        .groups
+        .0
+        .entry(group_name.to_string())
+        .or_default()
+        .0
+        .insert(key.to_string(), (value.to_string(), LocaleMap::default()));
+}
+
+pub fn remove_groups(desktop_entry: &mut DesktopEntry, group_name_prefix: &str) {
+    desktop_entry
+        .groups
+        .0
+        .retain(|group_name, _group| !group_name.starts_with(group_name_prefix));
+}
+
+pub fn remove_entry(desktop_entry: &mut DesktopEntry, key: &str) {
+    if let Some(group) = desktop_entry.groups.0.get_mut(DESKTOP_ENTRY_GROUP) {
+        group.0.remove(key);
+    }
+}
+
+pub fn get_locale_map(desktop_entry: &DesktopEntry, key: &str) -> LocaleMap {
+    desktop_entry
+        .groups
+        .0
+        .get(DESKTOP_ENTRY_GROUP)
+        .and_then(|group| group.0.get(key))
+        .map(|(_value, locale_map)| locale_map.clone())
+        .unwrap_or_default()
+}
+
+/// Translations of `key`, an empty map does not add the key
+pub fn set_locale_map(desktop_entry: &mut DesktopEntry, key: &str, locale_map: LocaleMap) {
+    let has_key = desktop_entry
+        .groups
+        .0
+        .get(DESKTOP_ENTRY_GROUP)
+        .is_some_and(|group| group.0.contains_key(key));
+    if locale_map.is_empty() && !has_key {
+        return;
+    }
+
+    let (_value, entry_locale_map) = desktop_entry
+        .groups
+        .0
+        .entry(DESKTOP_ENTRY_GROUP.to_string())
+        .or_default()
+        .0
+        .entry(key.to_string())
+        .or_default();
+
+    *entry_locale_map = locale_map;
+}
+
+/// Same as `DesktopEntry::to_string` but with the `[Desktop Entry]` group first as the spec requires
+pub fn to_string(desktop_entry: &DesktopEntry) -> String {
+    let mut txt = String::new();
+    let groups = &desktop_entry.groups.0;
+    let desktop_entry_group = groups.get_key_value(DESKTOP_ENTRY_GROUP);
+    let other_groups = groups
+        .iter()
+        .filter(|(group_name, _group)| *group_name != DESKTOP_ENTRY_GROUP);
+
+    for (group_name, group) in desktop_entry_group.into_iter().chain(other_groups) {
+        let _ = writeln!(txt, "[{group_name}]");
+        for (key, (value, locale_map)) in &group.0 {
+            let _ = writeln!(txt, "{key}={value}");
+            for (locale, localized_value) in locale_map {
+                let _ = writeln!(txt, "{key}[{locale}]={localized_value}");
+            }
+        }
+        let _ = writeln!(txt);
+    }
+
+    txt
+}
@@ -1,5 +1,4 @@
 use anyhow::{Result, bail};
-use gtk::gio::{self};
 use std::time::Duration;
 use tracing::{debug, error};
 use ureq::Agent;
@@ -31,7 +30,7 @@ impl Fetch {
         let url = url.to_string();
         let url_clone = url.clone();
 
-        match gio::spawn_blocking(move || -> Result<(String, Option<String>)> {
+        match gtk::gio::spawn_blocking(move || -> Result<(String, Option<String>)> {
             let mut call = agent_clone.get(url_clone).call()?;
             let body = call.body_mut();
             let mimetype = body.mime_type().map(std::string::ToString::to_string);
@@ -55,7 +54,7 @@ impl Fetch {
         let url = url.to_string();
         let url_clone = url.clone();
 
-        match gio::spawn_blocking(move || -> Result<(Vec<u8>, Option<String>)> {
+        match gtk::gio::spawn_blocking(move || -> Result<(Vec<u8>, Option<String>)> {
             let mut call = agent_clone.get(url_clone).call()?;
             let body = call.body_mut();
             let mimetype = body.mime_type().map(std::string::ToString::to_string);
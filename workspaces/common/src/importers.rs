@@ -0,0 +1,490 @@
+mod epiphany;
+mod ice;
+mod mint;
+
+use crate::{
+    app_dirs::AppDirs,
+    browser_apps::BrowserApp,
+    browsers::{Base, Browser, BrowserConfigs},
+    desktop_file::{DesktopFile, category::Category, error::DesktopFileError, exec},
+    transaction::Transaction,
+    utils,
+};
+use anyhow::{Context, Result};
+use freedesktop_desktop_entry::DesktopEntry;
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use tracing::{debug, error, info};
+
+/// Other web app managers that can be imported from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    /// `webapp-manager` of Linux Mint
+    Mint,
+    /// GNOME Web
+    Epiphany,
+    /// Peppermint ICE
+    Ice,
+}
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Mint => write!(f, "Web Apps (Linux Mint)"),
+            Self::Epiphany => write!(f, "GNOME Web"),
+            Self::Ice => write!(f, "Peppermint ICE"),
+        }
+    }
+}
+
+/// A web app of another web app manager, found in the user applications
+pub struct ForeignApp {
+    pub source: Source,
+    pub path: PathBuf,
+    pub name: String,
+    pub url: String,
+    /// `None` when the browser is not installed or has no config
+    pub browser: Option<Rc<Browser>>,
+    pub is_isolated: bool,
+    pub profile_path: Option<PathBuf>,
+    /// Engine of the profile, a profile is only copied to a browser with the same engine
+    pub profile_base: Option<Base>,
+    pub icon_path: Option<PathBuf>,
+    pub category: Option<Category>,
+}
+impl ForeignApp {
+    /// All web apps of other managers in the user applications
+    pub fn scan(browser_configs: &Rc<BrowserConfigs>, app_dirs: &Rc<AppDirs>) -> Vec<Self> {
+        debug!("Scanning for web apps of other managers");
+
+        let dirs = [
+            app_dirs.user_applications.clone(),
+            // Web apps that GNOME Web installs through the portal
+            app_dirs
+                .user_data
+                .join("xdg-desktop-portal")
+                .join("applications"),
+        ];
+        let mut foreign_apps = Vec::new();
+
+        for dir in dirs {
+            for file in utils::files::get_entries_in_dir(&dir).unwrap_or_default() {
+                let path = file.path();
+                if path
+                    .extension()
+                    .is_none_or(|extension| extension != "desktop")
+                {
+                    continue;
+                }
+
+                match Self::from_path(&path, browser_configs, app_dirs) {
+                    Ok(Some(foreign_app)) => foreign_apps.push(foreign_app),
+                    Ok(None) => {}
+                    Err(error) => {
+                        error!(path = %path.display(), "Failed to read desktop file: {error:?}");
+                    }
+                }
+            }
+        }
+
+        foreign_apps.sort_by_key(|foreign_app| foreign_app.name.to_lowercase());
+        foreign_apps
+    }
+
+    fn from_path(
+        path: &Path,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<Option<Self>> {
+        if DesktopFile::is_owned(path)? {
+            return Ok(None);
+        }
+
+        let desktop_entry = DesktopEntry::from_path(path, None::<&[String]>)?;
+        let Some(exec) = desktop_entry.desktop_entry("Exec") else {
+            return Ok(None);
+        };
+        // Split with shell quoting, some managers use quoting that the spec does not allow
+        let args = unwrap_shell(utils::command::split_args(&exec::unescape(exec))?);
+
+        let foreign_app = mint::from_entry(path, &desktop_entry, &args, browser_configs, app_dirs)
+            .or_else(|| {
+                epiphany::from_entry(path, &desktop_entry, &args, browser_configs, app_dirs)
+            })
+            .or_else(|| ice::from_entry(path, &desktop_entry, &args, browser_configs, app_dirs));
+
+        Ok(foreign_app)
+    }
+
+    /// Create a web app of this app, the original is left in place.
+    /// `fallback_browser` is used when the browser of the app is not available.
+    pub fn import(
+        &self,
+        fallback_browser: Option<&Rc<Browser>>,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Result<DesktopFile, DesktopFileError> {
+        let browser = self
+            .browser
+            .as_ref()
+            .or(fallback_browser)
+            .cloned()
+            .context(format!("No installed browser for: {}", self.name))?;
+        let icon_path = self
+            .icon_path
+            .as_ref()
+            .context(format!("No icon found for: {}", self.name))?;
+
+        let mut desktop_file = DesktopFile::new(browser_configs, app_dirs);
+        desktop_file.set_name(&self.name);
+        desktop_file.set_url(&self.url);
+        desktop_file.set_browser(&browser);
+        desktop_file.set_maximized(false);
+        if let Some(category) = &self.category {
+            desktop_file.set_category(category);
+        }
+
+        // Icon, profile and desktop file are rolled back when one of them fails
+        let mut transaction = Transaction::new();
+        desktop_file.copy_icon_in(icon_path, &mut transaction)?;
+
+        let is_isolated = self.is_isolated && browser.can_isolate;
+        desktop_file.set_isolated(is_isolated);
+
+        if is_isolated {
+            let profile_path = desktop_file.build_profile_path_in(&mut transaction)?;
+
+            if let Some(source_profile_path) = &self.profile_path
+                && source_profile_path.is_dir()
+                && self.profile_base == Some(browser.base)
+            {
+                DesktopFile::copy_profile(source_profile_path, &profile_path, &mut transaction)?;
+            }
+
+            desktop_file.set_profile_path(&profile_path);
+        }
+
+        let desktop_file = desktop_file.save_in(&mut transaction)?;
+        transaction.commit();
+
+        info!(
+            name = self.name,
+            source = self.source.to_string(),
+            id = desktop_file.get_id().unwrap_or_default(),
+            "Imported web app"
+        );
+        Ok(desktop_file)
+    }
+}
+
+/// Value of `--name=value` or `--name value`
+fn get_option(args: &[String], name: &str) -> Option<String> {
+    let option = format!("--{name}");
+    let prefix = format!("{option}=");
+
+    args.iter().enumerate().find_map(|(index, arg)| {
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+        (*arg == option)
+            .then(|| args.get(index + 1))
+            .flatten()
+            .filter(|value| !value.starts_with('-'))
+            .cloned()
+    })
+}
+
+/// Last argument that is a website
+fn get_url_arg(args: &[String]) -> Option<String> {
+    args.iter()
+        .rev()
+        .find(|arg| arg.starts_with("http://") || arg.starts_with("https://"))
+        .cloned()
+}
+
+/// Profile of a Firefox (`--profile`) or Chromium (`--user-data-dir`) based browser
+fn get_profile_arg(args: &[String]) -> Option<PathBuf> {
+    get_option(args, "profile")
+        .or_else(|| get_option(args, "user-data-dir"))
+        .map(PathBuf::from)
+}
+
+fn find_icon(desktop_entry: &DesktopEntry, app_dirs: &AppDirs) -> Option<PathBuf> {
+    desktop_entry
+        .desktop_entry("Icon")
+        .and_then(|icon| BrowserApp::find_icon(icon, app_dirs))
+}
+
+fn get_category(desktop_entry: &DesktopEntry) -> Option<Category> {
+    let categories = desktop_entry
+        .desktop_entry("Categories")
+        .unwrap_or_default()
+        .split(';')
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    Category::from_categories(&categories)
+}
+
+/// The command in `sh -c '<command>'` without leading environment variables
+fn unwrap_shell(args: Vec<String>) -> Vec<String> {
+    let program = args
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let is_shell = matches!(program.as_str(), "sh" | "bash")
+        && args.get(1).is_some_and(|option| option == "-c");
+
+    let shell_args = if is_shell {
+        args.get(2)
+            .and_then(|command| utils::command::split_args(command).ok())
+    } else {
+        None
+    };
+
+    shell_args
+        .unwrap_or(args)
+        .into_iter()
+        .skip_while(|arg| arg == "env" || (arg.contains('=') && !arg.starts_with('-')))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app_dirs::BaseDirs,
+        assets,
+        browsers::{Installation, StaticDetector},
+        config,
+        desktop_file::key::Key,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// `$HOME` is replaced by the temp home
+    pub(super) const MINT_FIXTURE: &str = r#"[Desktop Entry]
+Version=1.0
+Name=Mint App
+Exec=sh -c 'XAPP_FORCE_GTKWINDOW_ICON="$HOME/icon.png" firefox --class WebApp-mint1234 --profile $HOME/.local/share/ice/firefox/mint1234 --no-remote "https://mint.example.com"'
+Terminal=false
+Type=Application
+Icon=$HOME/icon.png
+Categories=GTK;WebApps;Network;
+StartupWMClass=WebApp-mint1234
+X-WebApp-Browser=Firefox
+X-WebApp-URL=https://mint.example.com
+X-WebApp-Isolated=true
+"#;
+    pub(super) const EPIPHANY_FIXTURE: &str = "[Desktop Entry]
+Name=Epiphany App
+Exec=epiphany --application-mode --profile=$HOME/epiphany/app-abcd https://epiphany.example.com
+Terminal=false
+Type=Application
+Categories=GNOME;GTK;
+Icon=missing-icon
+StartupWMClass=org.gnome.Epiphany.WebApp_abcd
+";
+    pub(super) const ICE_FIXTURE: &str = "[Desktop Entry]
+Version=1.0
+Name=Ice App
+Exec=chromium-browser --app=https://ice.example.com --class=ICE-SSB-iceapp --user-data-dir=$HOME/.local/share/ice/profiles/iceapp
+Terminal=false
+Type=Application
+Icon=$HOME/icon.png
+Categories=GTK;Office;
+StartupWMClass=ICE-SSB-iceapp
+";
+
+    /// A home in a temp dir with the shipped assets, only `installations` are installed
+    pub(super) fn setup(
+        installations: Vec<Installation>,
+    ) -> (TempDir, Rc<AppDirs>, Rc<BrowserConfigs>) {
+        config::init();
+        let home = tempfile::tempdir().unwrap();
+        let app_dirs = AppDirs::from_base_dirs(BaseDirs::from_home(home.path())).unwrap();
+        assets::init(&app_dirs).unwrap();
+        let browser_configs = BrowserConfigs::new_with_detector(
+            &app_dirs,
+            Box::new(StaticDetector { installations }),
+        );
+        browser_configs.init();
+        fs::write(home.path().join("icon.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+
+        (home, app_dirs, browser_configs)
+    }
+
+    /// Write a fixture to the user applications and read it
+    pub(super) fn parse(
+        file_name: &str,
+        fixture: &str,
+        home: &TempDir,
+        browser_configs: &Rc<BrowserConfigs>,
+        app_dirs: &Rc<AppDirs>,
+    ) -> Option<ForeignApp> {
+        let path = write_fixture(file_name, fixture, home, app_dirs);
+        ForeignApp::from_path(&path, browser_configs, app_dirs).unwrap()
+    }
+
+    fn write_fixture(
+        file_name: &str,
+        fixture: &str,
+        home: &TempDir,
+        app_dirs: &Rc<AppDirs>,
+    ) -> PathBuf {
+        let path = app_dirs.user_applications.join(file_name);
+        fs::create_dir_all(&app_dirs.user_applications).unwrap();
+        fs::write(
+            &path,
+            fixture.replace("$HOME", &home.path().to_string_lossy()),
+        )
+        .unwrap();
+        path
+    }
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn get_option_reads_both_forms() {
+        let args = to_args(&[
+            "browser",
+            "--profile=/a",
+            "--app",
+            "https://a.com",
+            "--flag",
+            "--name",
+        ]);
+
+        assert_eq!(get_option(&args, "profile").as_deref(), Some("/a"));
+        assert_eq!(get_option(&args, "app").as_deref(), Some("https://a.com"));
+        // Followed by another option or nothing
+        assert_eq!(get_option(&args, "flag"), None);
+        assert_eq!(get_option(&args, "name"), None);
+        assert_eq!(get_option(&args, "prof"), None);
+    }
+
+    #[test]
+    fn get_args_of_url_and_profile() {
+        let args = to_args(&[
+            "chromium",
+            "--user-data-dir",
+            "/profile",
+            "http://first.com",
+            "https://last.com",
+            "--flag",
+        ]);
+
+        assert_eq!(get_url_arg(&args).as_deref(), Some("https://last.com"));
+        assert_eq!(get_profile_arg(&args), Some(PathBuf::from("/profile")));
+        assert_eq!(get_url_arg(&to_args(&["firefox", "example.com"])), None);
+    }
+
+    #[test]
+    fn unwrap_shell_removes_shell_and_env() {
+        let args = to_args(&[
+            "sh",
+            "-c",
+            r#"XAPP_FORCE_GTKWINDOW_ICON="/a b.png" firefox --profile '/c d' "https://a.com""#,
+        ]);
+        assert_eq!(
+            unwrap_shell(args),
+            ["firefox", "--profile", "/c d", "https://a.com"]
+        );
+
+        let args = to_args(&[
+            "/usr/bin/bash",
+            "-c",
+            "env A=1 B=2 chromium --app=https://a.com",
+        ]);
+        assert_eq!(unwrap_shell(args), ["chromium", "--app=https://a.com"]);
+
+        let args = to_args(&["env", "A=1", "firefox", "--flag=a=b"]);
+        assert_eq!(unwrap_shell(args), ["firefox", "--flag=a=b"]);
+
+        // Not a shell command
+        let args = to_args(&["sh", "script.sh", "A=1"]);
+        assert_eq!(unwrap_shell(args.clone()), args);
+    }
+
+    #[test]
+    fn scan_detects_formats() {
+        let (home, app_dirs, browser_configs) = setup(vec![
+            Installation::System("firefox".to_string()),
+            Installation::System("chromium-browser".to_string()),
+        ]);
+        write_fixture("webapp-mint1234.desktop", MINT_FIXTURE, &home, &app_dirs);
+        write_fixture(
+            "org.gnome.Epiphany.WebApp_abcd.desktop",
+            EPIPHANY_FIXTURE,
+            &home,
+            &app_dirs,
+        );
+        write_fixture("iceapp.desktop", ICE_FIXTURE, &home, &app_dirs);
+        // The file name of GNOME Web is not enough
+        write_fixture(
+            "epiphany-copy.desktop",
+            &ICE_FIXTURE.replace("Name=Ice App", "Name=Ice Copy"),
+            &home,
+            &app_dirs,
+        );
+        write_fixture(
+            "owned.desktop",
+            &format!("{MINT_FIXTURE}{}=true\n", Key::Gwa),
+            &home,
+            &app_dirs,
+        );
+        write_fixture(
+            "org.gnome.Epiphany.WebApp_app.desktop",
+            &ICE_FIXTURE.replace("StartupWMClass=ICE-SSB-iceapp", ""),
+            &home,
+            &app_dirs,
+        );
+        write_fixture(
+            "editor.desktop",
+            "[Desktop Entry]\nName=Editor\nExec=gedit %U\nType=Application\n",
+            &home,
+            &app_dirs,
+        );
+        write_fixture(
+            "no-exec.desktop",
+            "[Desktop Entry]\nName=Link\nType=Link\nURL=https://a.com\n",
+            &home,
+            &app_dirs,
+        );
+
+        let foreign_apps = ForeignApp::scan(&browser_configs, &app_dirs);
+        let found = foreign_apps
+            .iter()
+            .map(|foreign_app| {
+                (
+                    foreign_app
+                        .path
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string(),
+                    foreign_app.source,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            found,
+            [
+                (
+                    "org.gnome.Epiphany.WebApp_abcd.desktop".to_string(),
+                    Source::Epiphany
+                ),
+                ("iceapp.desktop".to_string(), Source::Ice),
+                ("epiphany-copy.desktop".to_string(), Source::Ice),
+                ("webapp-mint1234.desktop".to_string(), Source::Mint),
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use super::{ForeignApp, Source, find_icon, get_category, get_option, get_url_arg};
+use crate::{app_dirs::AppDirs, browsers::BrowserConfigs};
+use freedesktop_desktop_entry::DesktopEntry;
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/*
+    GNOME Web writes `org.gnome.Epiphany.WebApp_<id>.desktop` files (`epiphany-<name>-<id>.desktop`
+    in older versions) that run `epiphany --application-mode --profile=<dir> <url>`.
+    The profile is of WebKit and can't be used by other browsers.
+*/
+
+const FILE_NAME_PREFIXES: &[&str] = &["org.gnome.Epiphany.WebApp", "epiphany-"];
+
+pub fn from_entry(
+    path: &Path,
+    desktop_entry: &DesktopEntry,
+    args: &[String],
+    browser_configs: &Rc<BrowserConfigs>,
+    app_dirs: &Rc<AppDirs>,
+) -> Option<ForeignApp> {
+    let file_name = path.file_name()?.to_string_lossy();
+    if !FILE_NAME_PREFIXES
+        .iter()
+        .any(|prefix| file_name.starts_with(prefix))
+        || !args.iter().any(|arg| arg == "--application-mode")
+    {
+        return None;
+    }
+
+    let profile_path = get_option(args, "profile").map(PathBuf::from);
+    // Kept in the profile by newer versions
+    let icon_path = find_icon(desktop_entry, app_dirs).or_else(|| {
+        profile_path
+            .as_ref()
+            .map(|profile_path| profile_path.join("app-icon.png"))
+            .filter(|icon_path| icon_path.is_file())
+    });
+
+    Some(ForeignApp {
+        source: Source::Epiphany,
+        path: path.to_path_buf(),
+        name: desktop_entry
+            .desktop_entry("Name")
+            .unwrap_or_default()
+            .to_string(),
+        url: get_url_arg(args)?,
+        // Only when a custom config exists
+        browser: browser_configs.get_by_run_args(args),
+        // Every web app has its own profile
+        is_isolated: true,
+        profile_path,
+        profile_base: None,
+        icon_path,
+        category: get_category(desktop_entry),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        browsers::Installation,
+        importers::tests::{EPIPHANY_FIXTURE, parse, setup},
+    };
+    use std::fs;
+
+    #[test]
+    fn from_entry_reads_fields() {
+        let (home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let profile_path = home.path().join("epiphany/app-abcd");
+        fs::create_dir_all(&profile_path).unwrap();
+        fs::write(profile_path.join("app-icon.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+
+        let foreign_app = parse(
+            "org.gnome.Epiphany.WebApp_abcd.desktop",
+            EPIPHANY_FIXTURE,
+            &home,
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+
+        assert_eq!(foreign_app.source, Source::Epiphany);
+        assert_eq!(foreign_app.name, "Epiphany App");
+        assert_eq!(foreign_app.url, "https://epiphany.example.com");
+        assert!(foreign_app.browser.is_none());
+        assert!(foreign_app.is_isolated);
+        assert_eq!(foreign_app.profile_path, Some(profile_path.clone()));
+        assert!(foreign_app.profile_base.is_none());
+        assert_eq!(
+            foreign_app.icon_path,
+            Some(profile_path.join("app-icon.png"))
+        );
+        assert!(foreign_app.category.is_none());
+    }
+
+    #[test]
+    fn from_entry_needs_file_name_and_application_mode() {
+        let (home, app_dirs, browser_configs) = setup(Vec::new());
+
+        assert!(
+            parse(
+                "epiphany-app-abcd.desktop",
+                EPIPHANY_FIXTURE,
+                &home,
+                &browser_configs,
+                &app_dirs,
+            )
+            .is_some()
+        );
+        assert!(
+            parse(
+                "web-app.desktop",
+                EPIPHANY_FIXTURE,
+                &home,
+                &browser_configs,
+                &app_dirs,
+            )
+            .is_none()
+        );
+        assert!(
+            parse(
+                "org.gnome.Epiphany.WebApp_abcd.desktop",
+                &EPIPHANY_FIXTURE.replace("--application-mode ", ""),
+                &home,
+                &browser_configs,
+                &app_dirs,
+            )
+            .is_none()
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use super::{
+    ForeignApp, Source, find_icon, get_category, get_option, get_profile_arg, get_url_arg,
+};
+use crate::{app_dirs::AppDirs, browsers::BrowserConfigs};
+use freedesktop_desktop_entry::DesktopEntry;
+use std::{path::Path, rc::Rc};
+
+/*
+    Peppermint ICE writes desktop files with an `ICE-SSB-<codename>` window class, that run
+    the browser with `--app=<url>` (Chromium) or the url (Firefox) and an optional profile
+    in `~/.local/share/ice`.
+*/
+
+const WM_CLASS_PREFIX: &str = "ICE-SSB";
+
+pub fn from_entry(
+    path: &Path,
+    desktop_entry: &DesktopEntry,
+    args: &[String],
+    browser_configs: &Rc<BrowserConfigs>,
+    app_dirs: &Rc<AppDirs>,
+) -> Option<ForeignApp> {
+    if !desktop_entry
+        .desktop_entry("StartupWMClass")
+        .is_some_and(|wm_class| wm_class.starts_with(WM_CLASS_PREFIX))
+    {
+        return None;
+    }
+
+    let url = get_option(args, "app").or_else(|| get_url_arg(args))?;
+    let browser = browser_configs.get_by_run_args(args);
+    let profile_path = get_profile_arg(args);
+
+    Some(ForeignApp {
+        source: Source::Ice,
+        path: path.to_path_buf(),
+        name: desktop_entry
+            .desktop_entry("Name")
+            .unwrap_or_default()
+            .to_string(),
+        url,
+        profile_base: browser.as_ref().map(|browser| browser.base),
+        browser,
+        is_isolated: profile_path.is_some(),
+        profile_path,
+        icon_path: find_icon(desktop_entry, app_dirs),
+        category: get_category(desktop_entry),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        browsers::{Base, Installation},
+        desktop_file::category::Category,
+        importers::tests::{ICE_FIXTURE, parse, setup},
+    };
+
+    #[test]
+    fn from_entry_reads_fields() {
+        let (home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("chromium-browser".to_string())]);
+        let foreign_app = parse(
+            "iceapp.desktop",
+            ICE_FIXTURE,
+            &home,
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+
+        assert_eq!(foreign_app.source, Source::Ice);
+        assert_eq!(foreign_app.name, "Ice App");
+        assert_eq!(foreign_app.url, "https://ice.example.com");
+        assert_eq!(foreign_app.browser.unwrap().id, "chromium-browser");
+        assert!(foreign_app.profile_base == Some(Base::Chromium));
+        assert!(foreign_app.is_isolated);
+        assert_eq!(
+            foreign_app.profile_path,
+            Some(home.path().join(".local/share/ice/profiles/iceapp"))
+        );
+        assert_eq!(foreign_app.icon_path, Some(home.path().join("icon.png")));
+        assert!(foreign_app.category == Some(Category::Office));
+    }
+
+    #[test]
+    fn from_entry_reads_firefox_url_without_profile() {
+        let (home, app_dirs, browser_configs) = setup(Vec::new());
+        let fixture = ICE_FIXTURE.replace(
+            "chromium-browser --app=https://ice.example.com --class=ICE-SSB-iceapp --user-data-dir=$HOME/.local/share/ice/profiles/iceapp",
+            "firefox --class ICE-SSB-iceapp https://ice.example.com/path",
+        );
+        let foreign_app = parse(
+            "iceapp.desktop",
+            &fixture,
+            &home,
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+
+        assert_eq!(foreign_app.url, "https://ice.example.com/path");
+        // Not installed
+        assert!(foreign_app.browser.is_none());
+        assert!(foreign_app.profile_base.is_none());
+        assert!(!foreign_app.is_isolated);
+        assert!(foreign_app.profile_path.is_none());
+    }
+}
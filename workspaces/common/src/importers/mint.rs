@@ -0,0 +1,156 @@
+use super::{ForeignApp, Source, find_icon, get_category, get_profile_arg};
+use crate::{
+    app_dirs::AppDirs,
+    browsers::{Browser, BrowserConfigs},
+};
+use freedesktop_desktop_entry::DesktopEntry;
+use std::{path::Path, rc::Rc};
+
+/*
+    `webapp-manager` of Linux Mint writes `webapp-<codename>.desktop` files with its settings in
+    `X-WebApp-*` keys. Isolated profiles are in `~/.local/share/ice`, passed with `--profile`
+    (Firefox) or `--user-data-dir` (Chromium).
+*/
+
+const URL_KEY: &str = "X-WebApp-URL";
+const BROWSER_KEY: &str = "X-WebApp-Browser";
+const ISOLATED_KEY: &str = "X-WebApp-Isolated";
+
+pub fn from_entry(
+    path: &Path,
+    desktop_entry: &DesktopEntry,
+    args: &[String],
+    browser_configs: &Rc<BrowserConfigs>,
+    app_dirs: &Rc<AppDirs>,
+) -> Option<ForeignApp> {
+    let url = desktop_entry.desktop_entry(URL_KEY)?.to_string();
+    let browser = browser_configs.get_by_run_args(args).or_else(|| {
+        desktop_entry
+            .desktop_entry(BROWSER_KEY)
+            .and_then(|browser_name| find_browser_by_name(browser_name, browser_configs))
+    });
+    let profile_path = get_profile_arg(args);
+    let is_isolated = desktop_entry
+        .desktop_entry(ISOLATED_KEY)
+        .map_or(profile_path.is_some(), |is_isolated| is_isolated == "true");
+
+    Some(ForeignApp {
+        source: Source::Mint,
+        path: path.to_path_buf(),
+        name: desktop_entry
+            .desktop_entry("Name")
+            .unwrap_or_default()
+            .to_string(),
+        url,
+        profile_base: browser.as_ref().map(|browser| browser.base),
+        browser,
+        is_isolated,
+        profile_path,
+        icon_path: find_icon(desktop_entry, app_dirs),
+        category: get_category(desktop_entry),
+    })
+}
+
+/// E.g. `Firefox (Flatpak)` or `Google Chrome`
+fn find_browser_by_name(
+    browser_name: &str,
+    browser_configs: &Rc<BrowserConfigs>,
+) -> Option<Rc<Browser>> {
+    let browser_name = browser_name.to_lowercase();
+    let is_flatpak = browser_name.contains("flatpak");
+    let browsers = if is_flatpak {
+        browser_configs.get_flatpak_browsers()
+    } else {
+        browser_configs.get_system_browsers()
+    };
+
+    browsers
+        .into_iter()
+        .find(|browser| browser_name.contains(&browser.name.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        browsers::{Base, Installation},
+        desktop_file::category::Category,
+        importers::tests::{MINT_FIXTURE, parse, setup},
+    };
+
+    #[test]
+    fn from_entry_reads_fields() {
+        let (home, app_dirs, browser_configs) =
+            setup(vec![Installation::System("firefox".to_string())]);
+        let foreign_app = parse(
+            "webapp-mint1234.desktop",
+            MINT_FIXTURE,
+            &home,
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+
+        assert_eq!(foreign_app.source, Source::Mint);
+        assert_eq!(foreign_app.name, "Mint App");
+        assert_eq!(foreign_app.url, "https://mint.example.com");
+        assert_eq!(foreign_app.browser.unwrap().id, "firefox");
+        assert!(foreign_app.profile_base == Some(Base::Firefox));
+        assert!(foreign_app.is_isolated);
+        assert_eq!(
+            foreign_app.profile_path,
+            Some(home.path().join(".local/share/ice/firefox/mint1234"))
+        );
+        assert_eq!(foreign_app.icon_path, Some(home.path().join("icon.png")));
+        assert!(foreign_app.category == Some(Category::Network));
+    }
+
+    #[test]
+    fn from_entry_finds_browser_by_name() {
+        let (home, app_dirs, browser_configs) = setup(vec![Installation::Flatpak(
+            "org.mozilla.firefox".to_string(),
+        )]);
+        let fixture = MINT_FIXTURE
+            .replace(" firefox ", " /opt/unknown-browser ")
+            .replace(
+                "X-WebApp-Browser=Firefox",
+                "X-WebApp-Browser=Firefox (Flatpak)",
+            )
+            .replace("X-WebApp-Isolated=true", "X-WebApp-Isolated=false");
+        let foreign_app = parse(
+            "webapp-mint1234.desktop",
+            &fixture,
+            &home,
+            &browser_configs,
+            &app_dirs,
+        )
+        .unwrap();
+
+        assert_eq!(foreign_app.browser.unwrap().id, "org.mozilla.firefox");
+        assert!(!foreign_app.is_isolated);
+    }
+
+    #[test]
+    fn find_browser_by_name_matches_installation() {
+        let (_home, _app_dirs, browser_configs) = setup(vec![
+            Installation::Flatpak("org.mozilla.firefox".to_string()),
+            Installation::System("google-chrome-stable".to_string()),
+        ]);
+        let find = |browser_name| {
+            find_browser_by_name(browser_name, &browser_configs).map(|browser| browser.id.clone())
+        };
+
+        assert_eq!(
+            find("Firefox (Flatpak)").as_deref(),
+            Some("org.mozilla.firefox")
+        );
+        assert_eq!(
+            find("Google Chrome").as_deref(),
+            Some("google-chrome-stable")
+        );
+        // Only the flatpak is installed
+        assert_eq!(find("Firefox"), None);
+        assert_eq!(find("Chrome (Flatpak)"), None);
+        assert_eq!(find("Epiphany"), None);
+    }
+}
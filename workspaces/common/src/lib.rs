@@ -5,10 +5,17 @@
 
 pub mod app_dirs;
 pub mod assets;
+pub mod browser_apps;
 pub mod browsers;
+pub mod bundle;
 pub mod cache_settings;
 pub mod config;
 pub mod desktop_file;
+#[cfg(feature = "gtk")]
 pub mod fetch;
+pub mod importers;
+pub mod orphans;
+pub mod transaction;
 pub mod url;
+pub mod user_settings;
 pub mod utils;
@@ -0,0 +1,322 @@
+use crate::{
+    app_dirs::AppDirs,
+    config::{self},
+    desktop_file::key::Key,
+    utils::{self, OnceLockExt},
+};
+use anyhow::{Context, Result};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, info};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrphanKind {
+    Profile,
+    Icon,
+}
+impl Display for OrphanKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Profile => write!(f, "profile"),
+            Self::Icon => write!(f, "icon"),
+        }
+    }
+}
+
+/// A profile or icon of a web app that no desktop file references anymore,
+/// e.g. when a web app was removed outside the app or a delete failed halfway.
+pub struct Orphan {
+    pub kind: OrphanKind,
+    pub path: PathBuf,
+    /// Size in bytes, a profile includes all its files
+    pub size: u64,
+}
+impl Orphan {
+    /// All orphaned profiles and icons
+    pub fn scan(app_dirs: &AppDirs) -> Result<Vec<Self>> {
+        debug!("Scanning for orphaned profiles and icons");
+
+        let references = References::from_applications(&app_dirs.user_applications)?;
+        let mut orphans = Vec::new();
+
+        for path in Self::get_profile_paths(app_dirs) {
+            if references.contains(&path) {
+                continue;
+            }
+            let size = fs_extra::dir::get_size(&path).unwrap_or_default();
+            orphans.push(Self {
+                kind: OrphanKind::Profile,
+                path,
+                size,
+            });
+        }
+
+        for file in utils::files::get_entries_in_dir(&app_dirs.app_data_icons).unwrap_or_default() {
+            let path = file.path();
+            if !path.is_file() || references.contains(&path) {
+                continue;
+            }
+            let size = file
+                .metadata()
+                .map(|metadata| metadata.len())
+                .unwrap_or_default();
+            orphans.push(Self {
+                kind: OrphanKind::Icon,
+                path,
+                size,
+            });
+        }
+
+        Ok(orphans)
+    }
+
+    /// Profiles of the app per browser, and the profiles in the data of sandboxed browsers
+    fn get_profile_paths(app_dirs: &AppDirs) -> Vec<PathBuf> {
+        let mut profiles = Vec::new();
+
+        for browser_dir in
+            utils::files::get_entries_in_dir(&app_dirs.app_data_profiles).unwrap_or_default()
+        {
+            profiles
+                .extend(utils::files::get_entries_in_dir(&browser_dir.path()).unwrap_or_default());
+        }
+
+        for flatpak in utils::files::get_entries_in_dir(&app_dirs.user_flatpak).unwrap_or_default()
+        {
+            let profiles_dir = flatpak
+                .path()
+                .join("data")
+                .join(config::APP_NAME_HYPHEN.get_value())
+                .join("profiles");
+            profiles.extend(utils::files::get_entries_in_dir(&profiles_dir).unwrap_or_default());
+        }
+
+        profiles
+            .iter()
+            .map(fs::DirEntry::path)
+            .filter(|path| path.is_dir())
+            .collect()
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        match self.kind {
+            OrphanKind::Profile => fs::remove_dir_all(&self.path),
+            OrphanKind::Icon => fs::remove_file(&self.path),
+        }
+        .context(format!(
+            "Failed to remove {}: {}",
+            self.kind,
+            self.path.display()
+        ))?;
+
+        info!(kind = %self.kind, path = %self.path.display(), "Removed orphan");
+        Ok(())
+    }
+}
+
+/// Ids, profiles and icons in all desktop files of the user
+struct References {
+    ids: HashSet<String>,
+    paths: HashSet<PathBuf>,
+}
+impl References {
+    /// Read line by line instead of parsed, a desktop file that fails to parse
+    /// must still protect its profile and icon
+    fn from_applications(applications_dir: &Path) -> Result<Self> {
+        let id_key = Key::Id.to_string();
+        let path_keys = [Key::Profile.to_string(), Key::Icon.to_string()];
+        let mut ids = HashSet::new();
+        let mut paths = HashSet::new();
+
+        let files = utils::files::get_entries_in_dir(applications_dir)
+            .context("Failed to read user applications")?;
+        for file in files {
+            let path = file.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "desktop")
+            {
+                continue;
+            }
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(error) => {
+                    error!(path = %path.display(), "Failed to read desktop file: {error:?}");
+                    continue;
+                }
+            };
+
+            for line in content.lines() {
+                let Some((key, value)) = line.split_once('=') else {
+                    continue;
+                };
+                let key = key.trim();
+                let value = value.trim();
+                if value.is_empty() {
+                    continue;
+                }
+
+                if key == id_key {
+                    ids.insert(value.to_string());
+                } else if path_keys.iter().any(|path_key| path_key == key) {
+                    paths.insert(PathBuf::from(value));
+                }
+            }
+        }
+
+        Ok(Self { ids, paths })
+    }
+
+    /// Referenced by path, or named after the id of an existing web app
+    fn contains(&self, path: &Path) -> bool {
+        if self.paths.contains(path) {
+            return true;
+        }
+        path.file_stem()
+            .is_some_and(|stem| self.ids.contains(&*stem.to_string_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_dirs::BaseDirs;
+    use std::rc::Rc;
+    use tempfile::TempDir;
+
+    const ID: &str = "abcd1234";
+
+    fn setup() -> (TempDir, Rc<AppDirs>) {
+        config::init();
+        let home = tempfile::tempdir().unwrap();
+        let app_dirs = AppDirs::from_base_dirs(BaseDirs::from_home(home.path())).unwrap();
+
+        (home, app_dirs)
+    }
+
+    fn create_profile(path: &Path) -> PathBuf {
+        fs::create_dir_all(path).unwrap();
+        fs::write(path.join("prefs.js"), "// prefs").unwrap();
+        path.to_path_buf()
+    }
+
+    fn create_icon(app_dirs: &AppDirs, file_name: &str) -> PathBuf {
+        let path = app_dirs.app_data_icons.join(file_name);
+        fs::write(&path, "icon").unwrap();
+        path
+    }
+
+    fn write_desktop_file(app_dirs: &AppDirs, file_name: &str, content: &str) {
+        fs::write(app_dirs.user_applications.join(file_name), content).unwrap();
+    }
+
+    #[test]
+    fn scan_reports_only_unreferenced() {
+        let (_home, app_dirs) = setup();
+        let firefox_profiles = app_dirs.app_data_profiles.join("firefox");
+        let flatpak_profiles = app_dirs
+            .user_flatpak
+            .join("org.mozilla.firefox")
+            .join("data")
+            .join(config::APP_NAME_HYPHEN.get_value())
+            .join("profiles");
+
+        // Referenced by a web app
+        let profile_path = create_profile(&firefox_profiles.join("referenced"));
+        write_desktop_file(
+            &app_dirs,
+            "referenced.desktop",
+            &format!(
+                "[Desktop Entry]\nName=Referenced\n{}={}\n",
+                Key::Profile,
+                profile_path.display()
+            ),
+        );
+
+        // Named after the id of a web app
+        create_profile(&flatpak_profiles.join(ID));
+        create_icon(&app_dirs, &format!("{ID}.png"));
+        write_desktop_file(
+            &app_dirs,
+            "by-id.desktop",
+            &format!("[Desktop Entry]\nName=By id\n{}={ID}\n", Key::Id),
+        );
+
+        // A desktop file that fails to parse still protects its files
+        let broken_profile_path = create_profile(&firefox_profiles.join("broken"));
+        let broken_icon_path = create_icon(&app_dirs, "broken.png");
+        write_desktop_file(
+            &app_dirs,
+            "broken.desktop",
+            &format!(
+                "No group header\n{} = {}\n{}={}\n[Desktop Entry\n",
+                Key::Profile,
+                broken_profile_path.display(),
+                Key::Icon,
+                broken_icon_path.display()
+            ),
+        );
+
+        assert!(
+            freedesktop_desktop_entry::DesktopEntry::from_path(
+                app_dirs.user_applications.join("broken.desktop"),
+                None::<&[String]>
+            )
+            .is_err()
+        );
+
+        // Real orphans, also in a flatpak and next to a file that is not a desktop file
+        let orphan_profile_path = create_profile(&firefox_profiles.join("orphan"));
+        let orphan_flatpak_profile_path = create_profile(&flatpak_profiles.join("orphan"));
+        let orphan_icon_path = create_icon(&app_dirs, "orphan.png");
+        write_desktop_file(
+            &app_dirs,
+            "not-desktop.txt",
+            &format!("{}={}\n", Key::Icon, orphan_icon_path.display()),
+        );
+
+        let mut orphans = Orphan::scan(&app_dirs)
+            .unwrap()
+            .into_iter()
+            .map(|orphan| (orphan.kind, orphan.path, orphan.size))
+            .collect::<Vec<_>>();
+        orphans.sort_by(|a, b| a.1.cmp(&b.1));
+
+        let mut expected = vec![
+            (OrphanKind::Profile, orphan_profile_path, 8),
+            (OrphanKind::Profile, orphan_flatpak_profile_path, 8),
+            (OrphanKind::Icon, orphan_icon_path, 4),
+        ];
+        expected.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(orphans, expected);
+    }
+
+    #[test]
+    fn references_contains_paths_and_ids() {
+        let (_home, app_dirs) = setup();
+        write_desktop_file(
+            &app_dirs,
+            "app.desktop",
+            &format!(
+                "[Desktop Entry]\n{}={ID}\n{}=/profiles/a\n{}=\n",
+                Key::Id,
+                Key::Profile,
+                Key::Icon
+            ),
+        );
+
+        let references = References::from_applications(&app_dirs.user_applications).unwrap();
+
+        assert!(references.contains(Path::new("/profiles/a")));
+        assert!(references.contains(&app_dirs.app_data_icons.join(format!("{ID}.png"))));
+        assert!(references.contains(Path::new(&format!("/profiles/{ID}"))));
+        assert!(!references.contains(Path::new("/profiles/a/b")));
+        assert!(!references.contains(Path::new("/profiles/b")));
+        // An empty value references nothing
+        assert!(!references.contains(Path::new("")));
+    }
+}
@@ -0,0 +1,367 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error};
+
+/// Filesystem changes of a web app (desktop file, icon and profile) that are rolled back
+/// when the transaction is dropped without `commit`.
+/// Files are written to a temp file first and renamed into place.
+#[derive(Default)]
+pub struct Transaction {
+    /// `(path, backup_path)` of replaced or removed files and dirs
+    backups: Vec<(PathBuf, PathBuf)>,
+    /// Files and dirs that did not exist before
+    created: Vec<PathBuf>,
+    /// `(from, to)` of moved files and dirs
+    moved: Vec<(PathBuf, PathBuf)>,
+    /// Side effects that only make sense for committed files
+    on_commit: Vec<Box<dyn FnOnce()>>,
+    is_done: bool,
+}
+impl Transaction {
+    const BACKUP_EXTENSION: &str = "wah-backup";
+    const TEMP_EXTENSION: &str = "wah-tmp";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+        self.write_with(path, |temp_path| {
+            fs::write(temp_path, contents).map_err(Into::into)
+        })
+    }
+
+    /// `write` writes the new file to the given temp path
+    pub fn write_with<F>(&mut self, path: &Path, write: F) -> Result<()>
+    where
+        F: FnOnce(&Path) -> Result<()>,
+    {
+        let temp_path = Self::get_sibling_path(path, Self::TEMP_EXTENSION);
+
+        if let Err(error) = write(&temp_path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(error.context(format!("Failed to write: {}", path.display())));
+        }
+        if let Err(error) = fs::File::open(&temp_path).and_then(|file| file.sync_all()) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(anyhow::Error::from(error)
+                .context(format!("Failed to sync: {}", temp_path.display())));
+        }
+
+        if self.is_tracked(path) {
+            // The original is already backed up or did not exist
+        } else if path.is_file() {
+            let backup_path = Self::get_sibling_path(path, Self::BACKUP_EXTENSION);
+            if let Err(error) = fs::copy(path, &backup_path) {
+                let _ = fs::remove_file(&temp_path);
+                return Err(anyhow::Error::from(error)
+                    .context(format!("Failed to backup: {}", path.display())));
+            }
+            self.backups.push((path.to_path_buf(), backup_path));
+        } else {
+            self.created.push(path.to_path_buf());
+        }
+
+        debug!("Writing file: {}", path.display());
+        fs::rename(&temp_path, path).context(format!("Failed to write: {}", path.display()))
+    }
+
+    /// Remove a file or a dir, it is moved aside until commit
+    pub fn remove(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        if self.is_tracked(path) {
+            // The original is already backed up or did not exist
+            debug!("Removing: {}", path.display());
+            let result = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            return result.context(format!("Failed to remove: {}", path.display()));
+        }
+
+        let backup_path = Self::get_sibling_path(path, Self::BACKUP_EXTENSION);
+        if backup_path.is_dir() {
+            fs::remove_dir_all(&backup_path)?;
+        }
+
+        debug!("Removing: {}", path.display());
+        fs::rename(path, &backup_path).context(format!("Failed to remove: {}", path.display()))?;
+        self.backups.push((path.to_path_buf(), backup_path));
+
+        Ok(())
+    }
+
+    /// Move a file or a dir to a path that does not exist, it is moved back on rollback
+    pub fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
+        if to.exists() {
+            bail!("Path already exists: {}", to.display())
+        }
+        if let Some(parent) = to.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        debug!("Moving '{}' to '{}'", from.display(), to.display());
+        fs::rename(from, to).context(format!(
+            "Failed to move '{}' to '{}'",
+            from.display(),
+            to.display()
+        ))?;
+        self.moved.push((from.to_path_buf(), to.to_path_buf()));
+
+        Ok(())
+    }
+
+    /// Copy the contents of dir `from` into `to`, replaced files are restored on rollback.
+    /// Symlinks are skipped, in a profile they are locks of a running browser.
+    pub fn copy_dir(&mut self, from: &Path, to: &Path) -> Result<()> {
+        self.create_dir_all(to)?;
+
+        for entry in
+            fs::read_dir(from).context(format!("Failed to read dir: {}", from.display()))?
+        {
+            let entry = entry?;
+            let from_path = entry.path();
+            let to_path = to.join(entry.file_name());
+            let file_type = entry.file_type()?;
+
+            if file_type.is_symlink() {
+                debug!("Skipping symlink: {}", from_path.display());
+            } else if file_type.is_dir() {
+                self.copy_dir(&from_path, &to_path)?;
+            } else if self.is_tracked(&to_path) && !to_path.exists() {
+                // In a dir of this transaction, which is removed as a whole on rollback
+                fs::copy(&from_path, &to_path)
+                    .context(format!("Failed to copy: {}", from_path.display()))?;
+            } else {
+                self.write_with(&to_path, |temp_path| {
+                    fs::copy(&from_path, temp_path)
+                        .map(|_| ())
+                        .map_err(Into::into)
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run `action` when the transaction is committed, e.g. to notify other apps of new files
+    pub fn on_commit(&mut self, action: impl FnOnce() + 'static) {
+        self.on_commit.push(Box::new(action));
+    }
+
+    pub fn create_dir_all(&mut self, path: &Path) -> Result<()> {
+        if path.is_dir() {
+            return Ok(());
+        }
+
+        // Only remove the top most dir that is new on rollback
+        let mut top_new_dir = path;
+        while let Some(parent) = top_new_dir.parent()
+            && !parent.exists()
+        {
+            top_new_dir = parent;
+        }
+
+        debug!("Creating dir: {}", path.display());
+        fs::create_dir_all(path).context(format!("Failed to create dir: {}", path.display()))?;
+        self.created.push(top_new_dir.to_path_buf());
+
+        Ok(())
+    }
+
+    pub fn commit(mut self) {
+        for (_path, backup_path) in self.backups.drain(..) {
+            let result = if backup_path.is_dir() {
+                fs::remove_dir_all(&backup_path)
+            } else {
+                fs::remove_file(&backup_path)
+            };
+            if let Err(error) = result {
+                error!(
+                    "Failed to remove backup '{}': {error:?}",
+                    backup_path.display()
+                );
+            }
+        }
+        self.is_done = true;
+
+        for action in self.on_commit.drain(..) {
+            action();
+        }
+    }
+
+    pub fn rollback(mut self) {
+        self.rollback_changes();
+    }
+
+    fn rollback_changes(&mut self) {
+        if self.is_done {
+            return;
+        }
+        debug!("Rolling back changes");
+
+        // Before removing new dirs, a path can be moved into one
+        for (from, to) in self.moved.drain(..).rev() {
+            if let Err(error) = fs::rename(&to, &from) {
+                error!("Failed to move back '{}': {error:?}", to.display());
+            }
+        }
+
+        for path in self.created.drain(..).rev() {
+            if !path.exists() {
+                continue;
+            }
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            if let Err(error) = result {
+                error!("Failed to roll back '{}': {error:?}", path.display());
+            }
+        }
+
+        for (path, backup_path) in self.backups.drain(..).rev() {
+            if path.is_dir() {
+                let _ = fs::remove_dir_all(&path);
+            }
+            if let Err(error) = fs::rename(&backup_path, &path) {
+                error!("Failed to restore '{}': {error:?}", path.display());
+            }
+        }
+
+        self.is_done = true;
+    }
+
+    fn is_tracked(&self, path: &Path) -> bool {
+        self.created.iter().any(|created| path.starts_with(created))
+            || self
+                .backups
+                .iter()
+                .any(|(original_path, _backup_path)| original_path == path)
+    }
+
+    fn get_sibling_path(path: &Path, extension: &str) -> PathBuf {
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        path.with_file_name(format!(".{file_name}.{extension}"))
+    }
+}
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        self.rollback_changes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn rollback_restores_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing");
+        let new = dir.path().join("new/file");
+        let removed = dir.path().join("removed");
+        fs::write(&existing, "old").unwrap();
+        fs::write(&removed, "removed").unwrap();
+
+        let mut transaction = Transaction::new();
+        transaction.write(&existing, b"new").unwrap();
+        transaction.create_dir_all(new.parent().unwrap()).unwrap();
+        transaction.write(&new, b"new").unwrap();
+        transaction.remove(&removed).unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "new");
+        drop(transaction);
+
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "old");
+        assert_eq!(fs::read_to_string(&removed).unwrap(), "removed");
+        assert!(!dir.path().join("new").exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn commit_keeps_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing");
+        let removed = dir.path().join("removed");
+        fs::write(&existing, "old").unwrap();
+        fs::write(&removed, "removed").unwrap();
+
+        let mut transaction = Transaction::new();
+        transaction.write(&existing, b"new").unwrap();
+        transaction.remove(&removed).unwrap();
+        transaction.commit();
+
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "new");
+        assert!(!removed.exists());
+        // No backups are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn rollback_copied_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("config");
+        let to = dir.path().join("profile");
+        fs::create_dir_all(from.join("chrome")).unwrap();
+        fs::write(from.join("user.js"), "new").unwrap();
+        fs::write(from.join("chrome/userChrome.css"), "css").unwrap();
+        fs::create_dir_all(&to).unwrap();
+        fs::write(to.join("user.js"), "old").unwrap();
+
+        let mut transaction = Transaction::new();
+        transaction.copy_dir(&from, &to).unwrap();
+        assert_eq!(fs::read_to_string(to.join("user.js")).unwrap(), "new");
+        assert!(to.join("chrome/userChrome.css").is_file());
+        drop(transaction);
+
+        assert_eq!(fs::read_to_string(to.join("user.js")).unwrap(), "old");
+        assert_eq!(fs::read_dir(&to).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn only_commit_runs_actions() {
+        let is_run = Rc::new(Cell::new(false));
+
+        let mut transaction = Transaction::new();
+        let is_run_clone = is_run.clone();
+        transaction.on_commit(move || is_run_clone.set(true));
+        drop(transaction);
+        assert!(!is_run.get());
+
+        let mut transaction = Transaction::new();
+        let is_run_clone = is_run.clone();
+        transaction.on_commit(move || is_run_clone.set(true));
+        transaction.commit();
+        assert!(is_run.get());
+    }
+
+    #[test]
+    fn rollback_moves_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("profile");
+        let to = dir.path().join("profiles/browser/profile");
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("prefs.js"), "prefs").unwrap();
+
+        let mut transaction = Transaction::new();
+        transaction.rename(&from, &to).unwrap();
+        assert!(to.join("prefs.js").is_file());
+        assert!(transaction.rename(&to, &to).is_err());
+        drop(transaction);
+
+        assert_eq!(fs::read_to_string(from.join("prefs.js")).unwrap(), "prefs");
+        assert!(!dir.path().join("profiles").exists());
+    }
+}
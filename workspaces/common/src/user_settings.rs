@@ -0,0 +1,71 @@
+use std::{fs, path::PathBuf};
+
+use crate::app_dirs::AppDirs;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, instrument};
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct UserSettingsYaml {
+    /// Browser ids for web apps of which the browser is uninstalled, in order of preference
+    #[serde(default)]
+    pub fallback_browsers: Vec<String>,
+}
+
+/// Settings of the user for all web apps, kept with the data so a reset of the cache
+/// or the config files leaves them alone
+#[derive(Debug)]
+pub struct UserSettings {
+    pub settings: UserSettingsYaml,
+    settings_path: PathBuf,
+}
+impl UserSettings {
+    const USER_SETTINGS_FILE: &str = "settings.yml";
+
+    pub fn new(app_dirs: &AppDirs) -> Self {
+        let settings_path = app_dirs.app_data.join(Self::USER_SETTINGS_FILE);
+
+        let yaml_string = fs::read_to_string(&settings_path).unwrap_or_default();
+        let settings: UserSettingsYaml = serde_yaml::from_str(&yaml_string)
+            .inspect_err(
+                |error| error!(%error, path = %settings_path.display(), "Failed to parse user settings yaml file"),
+            )
+            .unwrap_or_default();
+
+        Self {
+            settings,
+            settings_path,
+        }
+    }
+
+    pub fn set_fallback_browsers(&mut self, browser_ids: &[String]) {
+        self.settings.fallback_browsers = browser_ids.to_vec();
+    }
+
+    #[instrument(err, skip(self))]
+    pub fn save(&self) -> Result<()> {
+        debug!("Saving user settings");
+
+        let dir_path = self.settings_path.parent().context(format!(
+            "Failed to get parent of settings path: {}",
+            self.settings_path.display()
+        ))?;
+
+        if !dir_path.is_dir() {
+            fs::create_dir_all(dir_path).context(format!(
+                "Failed to create data dir for app: {}",
+                dir_path.display()
+            ))?;
+        }
+
+        let yaml_string = serde_yaml::to_string(&self.settings)
+            .context("Failed to parse settings to yaml string")?;
+
+        fs::write(&self.settings_path, &yaml_string).context(format!(
+            "Failed to write new settings file: {}",
+            self.settings_path.display()
+        ))?;
+
+        Ok(())
+    }
+}
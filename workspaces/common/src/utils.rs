@@ -66,14 +66,14 @@ pub mod files {
 
 pub mod env {
     use anyhow::Context;
-    use std::{env, str::FromStr};
+    use std::{env, path::PathBuf, str::FromStr};
     use tracing::Level;
 
     pub fn get_log_level() -> Option<Level> {
         std::env::var("WAH_LOG")
             .with_context(|| {
                 let info = "No LOG environment variable set";
-                println!("{info}");
+                eprintln!("{info}");
                 info
             })
             .and_then(|level_str| {
@@ -95,6 +95,40 @@ pub mod env {
         env::var("container").is_ok_and(|value| value == "flatpak")
     }
 
+    /// The first executable file `name` in the dirs of `PATH`
+    pub fn find_in_path(name: &str) -> Option<PathBuf> {
+        let paths = env::var_os("PATH")?;
+
+        env::split_paths(&paths)
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    pub fn get_home_dir() -> PathBuf {
+        env::home_dir().unwrap_or_else(|| PathBuf::from("/"))
+    }
+
+    /// An XDG base dir, the default in the home dir when unset or not absolute
+    pub fn get_xdg_dir(name: &str, default_in_home: &str) -> PathBuf {
+        env::var_os(name)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .unwrap_or_else(|| get_home_dir().join(default_in_home))
+    }
+
+    pub fn get_xdg_data_dirs() -> Vec<PathBuf> {
+        let data_dirs = env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|data_dirs| !data_dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+        data_dirs
+            .split(':')
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .collect()
+    }
+
     pub fn get_language() -> Option<String> {
         env::var("LANG").ok().and_then(|language| {
             language
@@ -143,8 +177,7 @@ pub mod log {
 pub mod command {
     use crate::utils::env;
     use anyhow::{Result, bail};
-    use gtk::glib;
-    use std::{fmt::Write, process::Command};
+    use std::{fmt::Write, process::Command, thread};
     use tracing::debug;
 
     pub struct Response {
@@ -169,6 +202,23 @@ pub mod command {
         true
     }
 
+    /// Quote an argument for a shell command, when needed
+    pub fn quote_arg(arg: &str) -> String {
+        let is_safe = !arg.is_empty()
+            && arg.chars().all(|char| {
+                char.is_ascii_alphanumeric()
+                    || matches!(
+                        char,
+                        '-' | '_' | '.' | '/' | '=' | ':' | ',' | '+' | '@' | '%'
+                    )
+            });
+        if is_safe {
+            return arg.to_string();
+        }
+
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+
     pub fn run_command_background(command: &str) -> Result<()> {
         let mut run_command = String::new();
 
@@ -178,8 +228,18 @@ pub mod command {
         write!(run_command, " {command}")?;
         let run_command = run_command.trim();
 
+        let mut args = split_args(run_command)?;
+        if args.is_empty() {
+            bail!("Incorrect command")
+        }
+        let command = args.remove(0);
+
         debug!(command = run_command, "Running background command");
-        glib::spawn_command_line_async(run_command).map_err(Into::into)
+        let mut child = Command::new(command).args(args).spawn()?;
+        // Reap the process when it exits
+        thread::spawn(move || child.wait());
+
+        Ok(())
     }
 
     pub fn run_command_sync(command: &str) -> Result<Response> {
@@ -191,7 +251,7 @@ pub mod command {
         write!(run_command, " {command}")?;
         let run_command = run_command.trim();
 
-        let mut args = glib::shell_parse_argv(run_command)?;
+        let mut args = split_args(run_command)?;
         if args.is_empty() {
             bail!("Incorrect command")
         }
@@ -210,9 +270,106 @@ pub mod command {
         Ok(response)
     }
 
+    /// Split a shell command in arguments, with the quoting of `quote_arg`: single quotes,
+    /// double quotes and backslash escapes. No expansions, a command is not run by a shell.
+    pub fn split_args(command: &str) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        let mut arg = String::new();
+        let mut is_started = false;
+        let mut chars = command.chars();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '\'' => {
+                    is_started = true;
+                    loop {
+                        match chars.next() {
+                            Some('\'') => break,
+                            Some(char) => arg.push(char),
+                            None => bail!("Unclosed quote in command: {command}"),
+                        }
+                    }
+                }
+                '"' => {
+                    is_started = true;
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => match chars.next() {
+                                Some(escaped @ ('"' | '\\' | '$' | '`')) => arg.push(escaped),
+                                Some('\n') => {}
+                                Some(other) => {
+                                    arg.push('\\');
+                                    arg.push(other);
+                                }
+                                None => bail!("Unclosed quote in command: {command}"),
+                            },
+                            Some(char) => arg.push(char),
+                            None => bail!("Unclosed quote in command: {command}"),
+                        }
+                    }
+                }
+                '\\' => {
+                    is_started = true;
+                    match chars.next() {
+                        Some('\n') => {}
+                        Some(escaped) => arg.push(escaped),
+                        None => bail!("Command ends with an escape character: {command}"),
+                    }
+                }
+                _ if char.is_whitespace() => {
+                    if is_started {
+                        args.push(std::mem::take(&mut arg));
+                    }
+                    is_started = false;
+                }
+                _ => {
+                    arg.push(char);
+                    is_started = true;
+                }
+            }
+        }
+        if is_started {
+            args.push(arg);
+        }
+
+        Ok(args)
+    }
+
     pub fn parse_output(std_descriptor: &[u8]) -> String {
         String::from_utf8_lossy(std_descriptor).trim().to_string()
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{quote_arg, split_args};
+
+        #[test]
+        fn split_args_reverses_quote_arg() {
+            let args = [
+                "firefox",
+                "--name=web app",
+                "it's",
+                "$HOME/\\path",
+                "\"quoted\"",
+                "",
+                "tab\tand\nnewline",
+            ];
+            let command = args.map(quote_arg).join(" ");
+
+            assert_eq!(split_args(&command).unwrap(), args);
+        }
+
+        #[test]
+        fn split_args_quoting() {
+            assert_eq!(
+                split_args(r#"a "b \"c\" \$d" e\ f 'g "h"'  "#).unwrap(),
+                ["a", "b \"c\" $d", "e f", "g \"h\""]
+            );
+            assert!(split_args("'unclosed").is_err());
+            assert!(split_args("\"unclosed").is_err());
+        }
+    }
 }
 
 pub trait OnceLockExt<T> {